@@ -14,6 +14,25 @@ pub struct Carnot<O: Overlay> {
     safe_blocks: HashMap<BlockId, Block>,
     last_view_timeout_qc: Option<TimeoutQc>,
     overlay: O,
+    epochs: Option<EpochSchedule>,
+}
+
+/// Engine state needed to resume participating in the protocol after a restart.
+///
+/// Only the genesis block and the blocks from the latest committed view onwards are part of
+/// the checkpoint, older blocks are expected to be persisted together with the rest of the
+/// chain and handed back to [`Carnot::from_checkpoint`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint<O> {
+    pub id: NodeId,
+    pub current_view: View,
+    pub highest_voted_view: View,
+    pub local_high_qc: StandardQc,
+    pub last_view_timeout_qc: Option<TimeoutQc>,
+    /// Blocks are keyed by id as block ids are not serialized along with them
+    pub safe_blocks: HashMap<BlockId, Block>,
+    pub overlay: O,
 }
 
 impl<O: Overlay> Carnot<O> {
@@ -26,6 +45,72 @@ impl<O: Overlay> Carnot<O> {
             last_view_timeout_qc: None,
             overlay,
             safe_blocks: [(genesis_block.id, genesis_block)].into(),
+            epochs: None,
+        }
+    }
+
+    /// Resume from a previously taken checkpoint.
+    ///
+    /// `ancestors` are committed blocks older than the ones in the checkpoint, they are not
+    /// needed to make progress but are kept as safe blocks as they were before the checkpoint.
+    pub fn from_checkpoint(
+        checkpoint: Checkpoint<O>,
+        ancestors: impl IntoIterator<Item = Block>,
+    ) -> Self {
+        let Checkpoint {
+            id,
+            current_view,
+            highest_voted_view,
+            local_high_qc,
+            last_view_timeout_qc,
+            safe_blocks,
+            overlay,
+        } = checkpoint;
+        Self {
+            id,
+            current_view,
+            highest_voted_view,
+            local_high_qc,
+            safe_blocks: ancestors
+                .into_iter()
+                .map(|block| (block.id, block))
+                .chain(
+                    safe_blocks
+                        .into_iter()
+                        .map(|(id, block)| (id, Block { id, ..block })),
+                )
+                .collect(),
+            last_view_timeout_qc,
+            overlay,
+            epochs: None,
+        }
+    }
+
+    /// Change the validator set at the epoch boundaries of `epochs`.
+    ///
+    /// The schedule is not part of checkpoints and has to be set again when resuming.
+    pub fn with_epochs(self, epochs: EpochSchedule) -> Self {
+        Self {
+            epochs: Some(epochs),
+            ..self
+        }
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint<O> {
+        let committed_view = self.latest_committed_view();
+        Checkpoint {
+            id: self.id,
+            current_view: self.current_view,
+            highest_voted_view: self.highest_voted_view,
+            local_high_qc: self.local_high_qc.clone(),
+            last_view_timeout_qc: self.last_view_timeout_qc.clone(),
+            safe_blocks: self
+                .safe_blocks
+                .iter()
+                .filter(|(_, block)| block.view >= committed_view || block.view == View(0))
+                .map(|(id, block)| (*id, block.clone()))
+                .collect(),
+            overlay: self.overlay.clone(),
         }
     }
 
@@ -74,7 +159,7 @@ impl<O: Overlay> Carnot<O> {
         if self.blocks_in_view(block.view).contains(&block)
             || block.view <= self.latest_committed_view()
         {
-            //  TODO: Report malicious leader
+            //  Conflicting proposals are reported by the consensus service
             //  TODO: it could be possible that a malicious leader send a block to a node and another one to
             //  the rest of the network. The node should be able to catch up with the rest of the network after having
             //  validated that the history of the block is correct and diverged from its fork.
@@ -104,11 +189,26 @@ impl<O: Overlay> Carnot<O> {
         new_state.update_high_qc(Qc::Standard(timeout_qc.high_qc().clone()));
         new_state.update_timeout_qc(timeout_qc.clone());
 
-        new_state.current_view = timeout_qc.view().next();
+        new_state.advance_to(timeout_qc.view().next());
 
         new_state
     }
 
+    /// Move to the view of an aggregated qc built while this node was not following the protocol.
+    ///
+    /// Only the aggregated qc is known, not the timeout qc that preceded it, so unlike
+    /// [`Carnot::receive_timeout_qc`] no timeout qc is recorded. The qc must have been verified
+    /// by the caller against the validator set of its view.
+    pub fn catch_up_to(&self, qc: &AggregateQc) -> Self {
+        let mut new_state = self.clone();
+        if qc.view <= new_state.current_view {
+            return new_state;
+        }
+        new_state.update_high_qc(Qc::Standard(qc.high_qc.clone()));
+        new_state.advance_to(qc.view);
+        new_state
+    }
+
     /// Upon reception of a supermajority of votes for a safe block from children
     /// of the current node. It signals approval of the block to the network.
     ///
@@ -173,10 +273,10 @@ impl<O: Overlay> Carnot<O> {
                     .unwrap_or(View(0)),
             "can't vote for a new view not bigger than the last timeout_qc"
         );
-        assert_eq!(
-            new_views.len(),
-            self.overlay.super_majority_threshold(self.id)
-        );
+        assert!(self
+            .overlay
+            .super_majority_threshold(self.id)
+            .is_reached(new_views.iter().map(|nv| &nv.sender)));
         assert!(new_views.iter().all(|nv| self
             .overlay
             .is_member_of_child_committee(self.id, nv.sender)));
@@ -265,8 +365,20 @@ impl<O: Overlay> Carnot<O> {
             _ => {}
         }
         if qc_view == self.current_view {
-            self.current_view += View(1);
+            self.advance_to(self.current_view.next());
+        }
+    }
+
+    /// Move to `view`, switching to the validator set of its epoch if it changed
+    fn advance_to(&mut self, view: View) {
+        if let Some(nodes) = self
+            .epochs
+            .as_ref()
+            .and_then(|epochs| epochs.validators_change(self.current_view, view))
+        {
+            self.overlay = self.overlay.update_nodes(nodes.to_vec());
         }
+        self.current_view = view;
     }
 
     fn update_timeout_qc(&mut self, timeout_qc: TimeoutQc) {
@@ -350,11 +462,11 @@ impl<O: Overlay> Carnot<O> {
         self.overlay.next_leader() == self.id
     }
 
-    pub fn super_majority_threshold(&self) -> usize {
+    pub fn super_majority_threshold(&self) -> Quorum {
         self.overlay.super_majority_threshold(self.id)
     }
 
-    pub fn leader_super_majority_threshold(&self) -> usize {
+    pub fn leader_super_majority_threshold(&self) -> Quorum {
         self.overlay.leader_super_majority_threshold(self.id)
     }
 
@@ -378,6 +490,47 @@ impl<O: Overlay> Carnot<O> {
         self.overlay.root_committee()
     }
 
+    /// Root committee and quorum the qcs of each view up to the current one are signed by.
+    ///
+    /// Views of past epochs are resolved to the validator set of their epoch in the schedule,
+    /// with the current leader selection and committee membership.
+    pub fn qc_signers(&self) -> QcSigners {
+        let signers_of = |overlay: &O| {
+            (
+                overlay.root_committee(),
+                overlay.leader_super_majority_threshold(self.id),
+            )
+        };
+        let (committee, quorum) = signers_of(&self.overlay);
+        let Some(epochs) = &self.epochs else {
+            return QcSigners::new(committee, quorum);
+        };
+        let current_epoch = epochs.epoch(self.current_view);
+        let mut signers: Option<QcSigners> = None;
+        for (epoch, nodes) in epochs.changes_until(current_epoch) {
+            let (past_committee, past_quorum) =
+                signers_of(&self.overlay.update_nodes(nodes.to_vec()));
+            match &mut signers {
+                Some(signers) => {
+                    signers.change_at(epochs.first_view(epoch), past_committee, past_quorum)
+                }
+                None => signers = Some(QcSigners::new(past_committee, past_quorum)),
+            }
+        }
+        match signers {
+            Some(mut signers) => {
+                signers.change_at(epochs.first_view(current_epoch), committee, quorum);
+                signers
+            }
+            None => QcSigners::new(committee, quorum),
+        }
+    }
+
+    /// Whether this node is part of the current validator set
+    pub fn is_validator(&self) -> bool {
+        self.overlay.nodes().contains(&self.id)
+    }
+
     pub fn is_member_of_root_committee(&self) -> bool {
         self.overlay.is_member_of_root_committee(self.id)
     }
@@ -428,6 +581,7 @@ mod test {
                 view: View(0),
                 id: BlockId::zeros(),
                 parent_qc: Qc::Standard(StandardQc::genesis()),
+                state_root: [0; 32],
                 leader_proof: LeaderProof::LeaderId {
                     leader_id: *nodes.first().unwrap(),
                 },
@@ -453,7 +607,9 @@ mod test {
             parent_qc: Qc::Standard(StandardQc {
                 view: block.view,
                 id: block.id,
+                signature: None,
             }),
+            state_root: [0; 32],
             leader_proof: LeaderProof::LeaderId {
                 leader_id: engine.overlay().next_leader(),
             },
@@ -528,7 +684,9 @@ mod test {
             parent_qc: Qc::Standard(StandardQc {
                 view: engine.current_view(),
                 id: parent_block_id,
+                signature: None,
             }),
+            state_root: [0; 32],
             leader_proof: LeaderProof::LeaderId {
                 leader_id: NodeId::new([0; 32]),
             },
@@ -632,8 +790,11 @@ mod test {
                     // a known parent block
                     id: block1.id,
                     view: block1.view,
+                    signature: None,
                 },
+                signature: None,
             }),
+            state_root: [0; 32],
             leader_proof: LeaderProof::LeaderId {
                 leader_id: engine.overlay().next_leader(),
             },
@@ -720,6 +881,7 @@ mod test {
                     high_qc: StandardQc {
                         view: View(0), // genesis
                         id: BlockId::zeros(),
+                        signature: None,
                     },
                     timeout_qc: None
                 }),
@@ -743,6 +905,7 @@ mod test {
             StandardQc {
                 view: View::new(0), // genesis
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -768,6 +931,7 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -795,6 +959,7 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -837,6 +1002,7 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -850,6 +1016,7 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -860,4 +1027,111 @@ mod test {
         // we expect new_view(timeout_qc2), but...
         let _ = engine.approve_new_view(timeout_qc1, HashSet::new());
     }
+
+    #[test]
+    // Ensure that the engine resumes from a checkpoint in the same state it was taken.
+    fn resume_from_checkpoint() {
+        let mut engine = init(vec![NodeId::new([0; 32])]);
+        let mut blocks = vec![engine.genesis_block()];
+        for _ in 0..5 {
+            let block = next_block(&engine, blocks.last().unwrap());
+            engine = engine.receive_block(block.clone()).unwrap();
+            engine = update_leader_selection(&engine);
+            blocks.push(block);
+        }
+        let (engine, _) = engine.approve_block(blocks[5].clone());
+        assert_eq!(engine.latest_committed_block(), blocks[3]);
+
+        let checkpoint = engine.checkpoint();
+        assert_eq!(checkpoint.highest_voted_view, View(5));
+        // blocks older than the latest committed one are left out
+        assert!(!checkpoint.safe_blocks.contains_key(&blocks[1].id));
+        assert!(!checkpoint.safe_blocks.contains_key(&blocks[2].id));
+
+        let resumed = Carnot::from_checkpoint(checkpoint.clone(), []);
+        assert_eq!(resumed.current_view(), engine.current_view());
+        assert_eq!(resumed.high_qc(), engine.high_qc());
+        assert_eq!(resumed.latest_committed_block(), blocks[3]);
+        assert_eq!(resumed.genesis_block(), blocks[0]);
+
+        let resumed = Carnot::from_checkpoint(checkpoint, blocks[1..3].to_vec());
+        assert_eq!(resumed, engine);
+    }
+
+    #[test]
+    // Ensure that the validator set changes when entering a new epoch, even when skipping views.
+    fn change_validators_at_epoch_boundaries() {
+        let nodes: Vec<_> = (0..3).map(|i| NodeId::new([i; 32])).collect();
+        let mut epochs = EpochSchedule::new(2.try_into().unwrap());
+        epochs.set_validators(1, vec![nodes[0], nodes[2]]);
+        epochs.set_validators(3, vec![nodes[2]]);
+        let mut engine = init(nodes[..2].to_vec()).with_epochs(epochs);
+
+        let block = next_block(&engine, &engine.genesis_block());
+        engine = engine.receive_block(block.clone()).unwrap();
+        engine = update_leader_selection(&engine);
+        assert_eq!(engine.current_view(), View(1));
+        assert_eq!(engine.overlay().nodes(), &nodes[..2]);
+
+        let block = next_block(&engine, &block);
+        engine = engine.receive_block(block).unwrap();
+        assert_eq!(engine.current_view(), View(2));
+        assert_eq!(engine.overlay().nodes(), &[nodes[0], nodes[2]]);
+        assert!(engine.is_validator());
+
+        // the network timed out up to a view in epoch 3, skipping epoch 2
+        let timeout_qc = TimeoutQc::new(View(5), engine.high_qc(), nodes[2]);
+        engine = engine.receive_timeout_qc(timeout_qc);
+        assert_eq!(engine.current_view(), View(6));
+        assert_eq!(engine.overlay().nodes(), &[nodes[2]]);
+        assert!(!engine.is_validator());
+    }
+
+    #[test]
+    // Ensure that qcs built before an epoch boundary are checked against the previous validator set.
+    fn qc_signers_across_epoch_boundaries() {
+        let nodes: Vec<_> = (0..3).map(|i| NodeId::new([i; 32])).collect();
+        let mut epochs = EpochSchedule::new(2.try_into().unwrap());
+        epochs.set_validators(0, nodes[..2].to_vec());
+        epochs.set_validators(1, vec![nodes[2]]);
+        let mut engine = init(nodes[..2].to_vec()).with_epochs(epochs);
+        let committee = |nodes: &[NodeId]| nodes.iter().copied().collect::<Committee>();
+
+        let signers = engine.qc_signers();
+        assert_eq!(signers.of(View(1)).0, &committee(&nodes[..2]));
+
+        let block = next_block(&engine, &engine.genesis_block());
+        engine = engine.receive_block(block.clone()).unwrap();
+        engine = update_leader_selection(&engine);
+        let block = next_block(&engine, &block);
+        engine = engine.receive_block(block).unwrap();
+        assert_eq!(engine.current_view(), View(2));
+
+        let signers = engine.qc_signers();
+        assert_eq!(signers.of(View(-1)).0, &committee(&nodes[..2]));
+        assert_eq!(signers.of(View(1)).0, &committee(&nodes[..2]));
+        assert_eq!(signers.of(View(2)).0, &engine.root_committee());
+        assert_eq!(signers.of(View(2)).0, &committee(&nodes[2..]));
+    }
+
+    #[test]
+    // Ensure that catching up to an aggregated qc switches validator set without recording a timeout qc.
+    fn catch_up_to_aggregated_qc() {
+        let nodes: Vec<_> = (0..3).map(|i| NodeId::new([i; 32])).collect();
+        let mut epochs = EpochSchedule::new(2.try_into().unwrap());
+        epochs.set_validators(2, vec![nodes[2]]);
+        let engine = init(nodes[..2].to_vec()).with_epochs(epochs);
+
+        let qc = AggregateQc {
+            high_qc: engine.high_qc(),
+            view: View(4),
+            signature: None,
+        };
+        let caught_up = engine.catch_up_to(&qc);
+        assert_eq!(caught_up.current_view(), View(4));
+        assert_eq!(caught_up.overlay().nodes(), &[nodes[2]]);
+        assert_eq!(caught_up.last_view_timeout_qc(), None);
+        // qcs from the past leave the engine untouched
+        assert_eq!(caught_up.catch_up_to(&qc), caught_up);
+    }
 }
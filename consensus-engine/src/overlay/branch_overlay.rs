@@ -1,6 +1,7 @@
+use super::threshold::{default_super_majority_threshold, stake_quorum};
 use super::LeaderSelection;
 use crate::overlay::CommitteeMembership;
-use crate::{Committee, CommitteeId, NodeId, Overlay};
+use crate::{Committee, CommitteeId, NodeId, Overlay, Quorum};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -13,6 +14,7 @@ pub struct BranchOverlaySettings<L: LeaderSelection, M: CommitteeMembership> {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Branch overlay with a single committee and round robin leader selection.
 pub struct BranchOverlay<L: LeaderSelection, M: CommitteeMembership> {
     nodes: Vec<NodeId>,
@@ -61,6 +63,10 @@ where
         }
     }
 
+    fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
     fn root_committee(&self) -> Committee {
         self.membership_committees[&0].clone()
     }
@@ -115,17 +121,15 @@ where
         self.leader.next_leader(&self.nodes)
     }
 
-    fn super_majority_threshold(&self, id: NodeId) -> usize {
+    fn super_majority_threshold(&self, id: NodeId) -> Quorum {
         if self.is_member_of_leaf_committee(id) {
-            return 0;
+            return Quorum::Count(0);
         }
-        let committee_size = self.root_committee().len();
-        (committee_size * 2 / 3) + 1
+        self.root_committee_threshold()
     }
 
-    fn leader_super_majority_threshold(&self, _id: NodeId) -> usize {
-        let committee_size = self.root_committee().len();
-        (committee_size * 2 / 3) + 1
+    fn leader_super_majority_threshold(&self, _id: NodeId) -> Quorum {
+        self.root_committee_threshold()
     }
 
     fn update_leader_selection<F, E>(&self, f: F) -> Result<Self, E>
@@ -156,6 +160,32 @@ where
             Self::new(settings)
         })
     }
+
+    fn update_nodes(&self, nodes: Vec<NodeId>) -> Self {
+        Self::new(BranchOverlaySettings {
+            nodes,
+            current_leader: self.current_leader,
+            branch_depth: self.number_of_committees,
+            leader: self.leader.clone(),
+            committee_membership: self.committee_membership.clone(),
+        })
+    }
+}
+
+impl<L: LeaderSelection, M: CommitteeMembership> BranchOverlay<L, M> {
+    fn root_committee_threshold(&self) -> Quorum {
+        let root_committee = &self.membership_committees[&0];
+        let stakes = self
+            .leader
+            .stakes()
+            .or_else(|| self.committee_membership.stakes());
+        match stakes {
+            Some(stakes) => {
+                stake_quorum(root_committee, stakes, default_super_majority_threshold())
+            }
+            None => Quorum::Count((root_committee.len() * 2 / 3) + 1),
+        }
+    }
 }
 
 fn build_committee_from_nodes_with_size(
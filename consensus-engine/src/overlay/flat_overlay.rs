@@ -1,16 +1,18 @@
-use super::threshold::{apply_threshold, default_super_majority_threshold, deser_fraction};
+use super::threshold::{apply_threshold_to, default_super_majority_threshold, deser_fraction};
 use super::LeaderSelection;
 use crate::overlay::CommitteeMembership;
-use crate::{NodeId, Overlay};
+use crate::{NodeId, Overlay, Quorum};
 use fraction::Fraction;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// Flat overlay with a single committee and round robin leader selection.
 pub struct FlatOverlay<L: LeaderSelection, M: CommitteeMembership> {
     nodes: Vec<NodeId>,
     leader: L,
+    #[cfg_attr(feature = "serde", serde(with = "deser_fraction::required"))]
     leader_threshold: Fraction,
     _committee_membership: PhantomData<M>,
 }
@@ -40,6 +42,10 @@ where
         }
     }
 
+    fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
     fn root_committee(&self) -> crate::Committee {
         self.nodes.clone().into_iter().collect()
     }
@@ -80,12 +86,16 @@ where
         self.leader.next_leader(&self.nodes)
     }
 
-    fn super_majority_threshold(&self, _id: NodeId) -> usize {
-        0
+    fn super_majority_threshold(&self, _id: NodeId) -> Quorum {
+        Quorum::Count(0)
     }
 
-    fn leader_super_majority_threshold(&self, _id: NodeId) -> usize {
-        apply_threshold(self.nodes.len(), self.leader_threshold)
+    fn leader_super_majority_threshold(&self, _id: NodeId) -> Quorum {
+        apply_threshold_to(
+            self.nodes.iter(),
+            self.leader.stakes(),
+            self.leader_threshold,
+        )
     }
 
     fn update_leader_selection<F, E>(&self, f: F) -> Result<Self, E>
@@ -107,6 +117,13 @@ where
     {
         Ok(self.clone())
     }
+
+    fn update_nodes(&self, nodes: Vec<NodeId>) -> Self {
+        Self {
+            nodes,
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -9,6 +9,7 @@ use crate::overlay::CommitteeMembership;
 use crate::NodeId;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FisherYatesShuffle {
     entropy: [u8; 32],
 }
@@ -36,6 +37,7 @@ impl CommitteeMembership for FisherYatesShuffle {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FreezeMembership;
 
 impl CommitteeMembership for FreezeMembership {
@@ -5,6 +5,7 @@ mod flat_overlay;
 mod leadership;
 mod membership;
 mod random_beacon;
+mod stake_weighted;
 mod threshold;
 mod tree_overlay;
 
@@ -13,6 +14,7 @@ pub use flat_overlay::*;
 pub use leadership::*;
 pub use membership::*;
 pub use random_beacon::*;
+pub use stake_weighted::*;
 pub use tree_overlay::*;
 
 use std::marker::Send;
@@ -23,6 +25,7 @@ pub trait Overlay: Clone {
     type CommitteeMembership: CommitteeMembership + Clone + Send + Sync + 'static;
 
     fn new(settings: Self::Settings) -> Self;
+    fn nodes(&self) -> &[NodeId];
     fn root_committee(&self) -> Committee;
     fn is_member_of_child_committee(&self, parent: NodeId, child: NodeId) -> bool;
     fn is_member_of_root_committee(&self, id: NodeId) -> bool;
@@ -33,22 +36,33 @@ pub trait Overlay: Clone {
     fn leaf_committees(&self, id: NodeId) -> Vec<Committee>;
     fn node_committee(&self, id: NodeId) -> Committee;
     fn next_leader(&self) -> NodeId;
-    fn super_majority_threshold(&self, id: NodeId) -> usize;
-    fn leader_super_majority_threshold(&self, id: NodeId) -> usize;
+    fn super_majority_threshold(&self, id: NodeId) -> Quorum;
+    fn leader_super_majority_threshold(&self, id: NodeId) -> Quorum;
     fn update_leader_selection<F, E>(&self, f: F) -> Result<Self, E>
     where
         F: FnOnce(Self::LeaderSelection) -> Result<Self::LeaderSelection, E>;
     fn update_committees<F, E>(&self, f: F) -> Result<Self, E>
     where
         F: FnOnce(Self::CommitteeMembership) -> Result<Self::CommitteeMembership, E>;
+    /// Rebuild the overlay from another set of nodes, keeping the current leader selection
+    /// and committee membership
+    fn update_nodes(&self, nodes: Vec<NodeId>) -> Self;
 }
 
 pub trait LeaderSelection: Clone {
     fn next_leader(&self, nodes: &[NodeId]) -> NodeId;
+    /// Stake of every node if the selection is weighted by it, weighting the thresholds too
+    fn stakes(&self) -> Option<&StakeRegistry> {
+        None
+    }
 }
 
 pub trait CommitteeMembership: Clone {
     fn reshape_committees(&self, nodes: &mut [NodeId]);
+    /// Stake of every node if the membership is weighted by it, weighting the thresholds too
+    fn stakes(&self) -> Option<&StakeRegistry> {
+        None
+    }
 }
 
 #[cfg(test)]
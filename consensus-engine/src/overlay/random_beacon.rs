@@ -33,6 +33,8 @@ pub enum RandomBeaconState {
 pub enum Error {
     #[error("Invalid random beacon transition")]
     InvalidRandomBeacon,
+    #[error("Random beacon was not generated by the leader")]
+    NotLeader,
 }
 
 impl RandomBeaconState {
@@ -70,22 +72,31 @@ impl RandomBeaconState {
         )
     }
 
-    pub fn check_advance_happy(&self, rb: RandomBeaconState, view: View) -> Result<Self, Error> {
-        let context = view_to_bytes(view);
-        match rb {
-            Self::Happy {
-                ref sig,
-                public_key,
-            } => {
-                let sig = Signature::from_bytes(sig).unwrap();
-                if !public_key.verify(sig, context) {
-                    return Err(Error::InvalidRandomBeacon);
-                }
-            }
-            Self::Sad { .. } => return Err(Error::InvalidRandomBeacon),
-        }
+    pub fn check_advance_happy(
+        &self,
+        rb: RandomBeaconState,
+        view: View,
+        leader: NodeId,
+    ) -> Result<Self, Error> {
+        rb.verify_happy(view, leader)?;
         Ok(rb)
     }
+
+    /// Check that this is a happy beacon for `view` generated by `leader`,
+    /// the node whose id is derived from the beacon public key.
+    pub fn verify_happy(&self, view: View, leader: NodeId) -> Result<(), Error> {
+        let Self::Happy { sig, public_key } = self else {
+            return Err(Error::InvalidRandomBeacon);
+        };
+        if NodeId::from_public_key(public_key) != leader {
+            return Err(Error::NotLeader);
+        }
+        let sig = Signature::from_bytes(sig).map_err(|_| Error::InvalidRandomBeacon)?;
+        if !public_key.verify(sig, view_to_bytes(view)) {
+            return Err(Error::InvalidRandomBeacon);
+        }
+        Ok(())
+    }
 }
 
 fn view_to_bytes(view: View) -> Box<[u8]> {
@@ -137,3 +148,32 @@ mod serialize_bls {
         bytes.serialize(serializer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn happy_beacon_from_leader() {
+        let sk = PrivateKey::new([1; 32]);
+        let leader = NodeId::from_public_key(&sk.public_key());
+        let view = View::new(1);
+        let prev = RandomBeaconState::initial_sad_from_entropy([0; 32]);
+        let rb = RandomBeaconState::generate_happy(view, &sk);
+        assert_eq!(
+            prev.check_advance_happy(rb.clone(), view, leader).unwrap(),
+            rb
+        );
+        assert!(matches!(
+            prev.check_advance_happy(rb.clone(), view.next(), leader),
+            Err(Error::InvalidRandomBeacon)
+        ));
+
+        // a valid beacon, but not from the leader
+        let other = RandomBeaconState::generate_happy(view, &PrivateKey::new([2; 32]));
+        assert!(matches!(
+            prev.check_advance_happy(other, view, leader),
+            Err(Error::NotLeader)
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+// std
+
+// crates
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+// internal
+use crate::overlay::{CommitteeMembership, LeaderSelection, RandomBeaconState};
+use crate::{NodeId, StakeRegistry};
+
+/// Stake weighted sortition of leaders and committee slots.
+///
+/// Nodes are drawn with a probability proportional to their stake, so that the leader and the
+/// first committee slots (the root committee in tree overlays) tend to go to the nodes with more
+/// at stake. Nodes without stake are only drawn once every node with stake has been.
+/// Draws are seeded by the random beacon, which is expected to be updated every view.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StakeWeighted {
+    stakes: StakeRegistry,
+    beacon: RandomBeaconState,
+}
+
+impl StakeWeighted {
+    pub fn new(stakes: StakeRegistry, beacon: RandomBeaconState) -> Self {
+        Self { stakes, beacon }
+    }
+
+    /// Same stakes, drawn with the entropy of a new beacon
+    pub fn with_beacon(&self, beacon: RandomBeaconState) -> Self {
+        Self {
+            stakes: self.stakes.clone(),
+            beacon,
+        }
+    }
+
+    pub fn beacon(&self) -> &RandomBeaconState {
+        &self.beacon
+    }
+
+    fn rng(&self) -> ChaCha20Rng {
+        let mut seed = [0; 32];
+        seed.copy_from_slice(&self.beacon.entropy()[..32]);
+        ChaCha20Rng::from_seed(seed)
+    }
+
+    // index of a node drawn with a probability proportional to its stake,
+    // or uniformly if none of them has stake
+    fn draw(&self, nodes: &[NodeId], rng: &mut ChaCha20Rng) -> usize {
+        let total = self.stakes.total_stake(nodes);
+        if total == 0 {
+            return rng.gen_range(0..nodes.len());
+        }
+        let mut target = rng.gen_range(0..total);
+        for (index, node) in nodes.iter().enumerate() {
+            let stake = self.stakes.stake(node) as u128;
+            if target < stake {
+                return index;
+            }
+            target -= stake;
+        }
+        unreachable!("target is below the total stake of the nodes")
+    }
+}
+
+impl LeaderSelection for StakeWeighted {
+    fn next_leader(&self, nodes: &[NodeId]) -> NodeId {
+        nodes[self.draw(nodes, &mut self.rng())]
+    }
+
+    fn stakes(&self) -> Option<&StakeRegistry> {
+        Some(&self.stakes)
+    }
+}
+
+impl CommitteeMembership for StakeWeighted {
+    fn reshape_committees(&self, nodes: &mut [NodeId]) {
+        let mut rng = self.rng();
+        let mut remaining = nodes.to_vec();
+        for slot in nodes.iter_mut() {
+            let index = self.draw(&remaining, &mut rng);
+            *slot = remaining.swap_remove(index);
+        }
+    }
+
+    fn stakes(&self) -> Option<&StakeRegistry> {
+        Some(&self.stakes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes(n: u8) -> Vec<NodeId> {
+        (0..n).map(|i| NodeId::new([i; 32])).collect()
+    }
+
+    fn beacon(entropy: u8) -> RandomBeaconState {
+        RandomBeaconState::initial_sad_from_entropy([entropy; 32])
+    }
+
+    #[test]
+    fn leaders_follow_stake() {
+        let nodes = nodes(3);
+        let stakes: StakeRegistry = [(nodes[0], 1), (nodes[1], 9)].into_iter().collect();
+        let mut counts = [0; 3];
+        for i in 0..=255 {
+            let selection = StakeWeighted::new(stakes.clone(), beacon(i));
+            let leader = selection.next_leader(&nodes);
+            counts[nodes.iter().position(|n| *n == leader).unwrap()] += 1;
+        }
+        assert_eq!(counts[2], 0);
+        assert!(counts[1] > counts[0] * 3);
+    }
+
+    #[test]
+    fn reshape_keeps_every_node() {
+        let nodes = nodes(10);
+        let stakes: StakeRegistry = nodes[..5].iter().map(|n| (*n, 100)).collect();
+        let mut reshaped = nodes.clone();
+        StakeWeighted::new(stakes, beacon(0)).reshape_committees(&mut reshaped);
+
+        // nodes without stake take the last slots
+        let mut staked = reshaped[..5].to_vec();
+        staked.sort();
+        assert_eq!(staked, nodes[..5]);
+        let mut rest = reshaped[5..].to_vec();
+        rest.sort();
+        assert_eq!(rest, nodes[5..]);
+    }
+}
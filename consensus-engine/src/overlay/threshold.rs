@@ -1,5 +1,7 @@
 use fraction::{Fraction, GenericFraction, ToPrimitive};
 
+use crate::{NodeId, Quorum, StakeRegistry};
+
 const SUPER_MAJORITY_THRESHOLD_NUM: u64 = 2;
 const SUPER_MAJORITY_THRESHOLD_DEN: u64 = 3;
 
@@ -15,6 +17,44 @@ pub(crate) fn apply_threshold(size: usize, threshold: GenericFraction<u64>) -> u
         .unwrap()
 }
 
+/// Supermajority of `members` weighted by their stake: voters must hold together at least
+/// `threshold` of the stake of all members.
+///
+/// Falls back to counting members when none of them has stake.
+pub(crate) fn stake_quorum<'a>(
+    members: impl IntoIterator<Item = &'a NodeId>,
+    stakes: &StakeRegistry,
+    threshold: GenericFraction<u64>,
+) -> Quorum {
+    // only the stake of members counts towards the quorum
+    let stakes: StakeRegistry = members
+        .into_iter()
+        .map(|id| (*id, stakes.stake(id)))
+        .collect();
+    let total = stakes.total_stake(stakes.iter().map(|(id, _)| id));
+    if total == 0 {
+        return Quorum::Count(apply_threshold(stakes.iter().count(), threshold));
+    }
+    let num = *threshold.numer().expect("threshold should be a number") as u128;
+    let den = *threshold.denom().expect("threshold should be a number") as u128;
+    Quorum::Stake {
+        required: (total * num).div_ceil(den),
+        stakes,
+    }
+}
+
+/// Threshold over `members`, weighted by their stake if there is a registry
+pub(crate) fn apply_threshold_to<'a>(
+    members: impl IntoIterator<Item = &'a NodeId>,
+    stakes: Option<&StakeRegistry>,
+    threshold: GenericFraction<u64>,
+) -> Quorum {
+    match stakes {
+        Some(stakes) => stake_quorum(members, stakes, threshold),
+        None => Quorum::Count(apply_threshold(members.into_iter().count(), threshold)),
+    }
+}
+
 pub mod deser_fraction {
     use fraction::Fraction;
     use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -35,4 +75,56 @@ pub mod deser_fraction {
     {
         value.map(|v| v.to_string()).serialize(serializer)
     }
+
+    /// Same encoding as the parent module, for fractions that are always present
+    pub mod required {
+        use fraction::Fraction;
+        use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+        use std::str::FromStr;
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Fraction, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            FromStr::from_str(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+        }
+
+        pub fn serialize<S>(value: &Fraction, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            value.to_string().serialize(serializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stake_quorum_sums_stake() {
+        let nodes: Vec<_> = (0..4).map(|i| NodeId::new([i; 32])).collect();
+        let threshold = default_super_majority_threshold();
+
+        let whale: StakeRegistry = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (*n, if i == 0 { 100 } else { 1 }))
+            .collect();
+        let quorum = stake_quorum(&nodes, &whale, threshold);
+        // the whale alone holds more than two thirds of the stake
+        assert!(quorum.is_reached(&nodes[..1]));
+        assert!(!quorum.is_reached(&nodes[1..]));
+
+        // stake of non members does not count
+        let quorum = stake_quorum(&nodes[1..], &whale, threshold);
+        assert!(!quorum.is_reached(&nodes[..2]));
+        assert!(quorum.is_reached(&nodes[1..3]));
+
+        assert_eq!(
+            stake_quorum(&nodes, &StakeRegistry::new(), threshold),
+            Quorum::Count(3)
+        );
+    }
 }
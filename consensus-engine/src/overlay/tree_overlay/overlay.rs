@@ -1,9 +1,9 @@
 use super::tree::Tree;
 use crate::overlay::threshold::{
-    apply_threshold, default_super_majority_threshold, deser_fraction,
+    apply_threshold_to, default_super_majority_threshold, deser_fraction,
 };
 use crate::overlay::CommitteeMembership;
-use crate::{overlay::LeaderSelection, Committee, NodeId, Overlay};
+use crate::{overlay::LeaderSelection, Committee, NodeId, Overlay, Quorum, StakeRegistry};
 use fraction::Fraction;
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +23,7 @@ pub struct TreeOverlaySettings<L: LeaderSelection, M: CommitteeMembership> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TreeOverlay<L, M> {
     pub(super) number_of_committees: usize,
     pub(super) nodes: Vec<NodeId>,
@@ -30,6 +31,7 @@ pub struct TreeOverlay<L, M> {
     pub(super) carnot_tree: Tree,
     pub(super) leader: L,
     pub(super) committee_membership: M,
+    #[cfg_attr(feature = "serde", serde(with = "deser_fraction::required"))]
     pub(super) threshold: Fraction,
 }
 
@@ -67,6 +69,10 @@ where
         }
     }
 
+    fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
     fn root_committee(&self) -> Committee {
         self.carnot_tree.root_committee().clone()
     }
@@ -142,13 +148,13 @@ where
         self.leader.next_leader(&self.nodes)
     }
 
-    fn super_majority_threshold(&self, id: NodeId) -> usize {
+    fn super_majority_threshold(&self, id: NodeId) -> Quorum {
         if self.is_member_of_leaf_committee(id) {
-            return 0;
+            return Quorum::Count(0);
         }
         self.carnot_tree
             .committee_by_member_id(&id)
-            .map(|c| apply_threshold(c.len(), self.threshold))
+            .map(|c| apply_threshold_to(c.iter(), self.stakes(), self.threshold))
             .expect("node is not part of any committee")
     }
 
@@ -156,7 +162,7 @@ where
     // root committee yet. *For now* leader super majority threshold should be calculated only from
     // the number of root committee nodes. The code will be reverted once vote sending from
     // child committee of root committee is added to Carnot node.
-    fn leader_super_majority_threshold(&self, _id: NodeId) -> usize {
+    fn leader_super_majority_threshold(&self, _id: NodeId) -> Quorum {
         // let root_committee = &self.carnot_tree.inner_committees[0];
         // let children = self.carnot_tree.child_committees(root_committee);
         // let children_size = children.0.map_or(0, |c| {
@@ -170,7 +176,7 @@ where
         // });
         // let root_size = self.root_committee().len();
         // let committee_size = root_size + children_size;
-        apply_threshold(self.root_committee().len(), self.threshold)
+        apply_threshold_to(self.root_committee().iter(), self.stakes(), self.threshold)
     }
 
     fn update_leader_selection<F, E>(&self, f: F) -> Result<Self, E>
@@ -202,6 +208,17 @@ where
             Self::new(settings)
         })
     }
+
+    fn update_nodes(&self, nodes: Vec<NodeId>) -> Self {
+        Self::new(TreeOverlaySettings {
+            nodes,
+            current_leader: self.current_leader,
+            number_of_committees: self.number_of_committees,
+            leader: self.leader.clone(),
+            committee_membership: self.committee_membership.clone(),
+            super_majority_threshold: Some(self.threshold),
+        })
+    }
 }
 
 impl<L, M> TreeOverlay<L, M>
@@ -220,6 +237,14 @@ where
         })
     }
 
+    /// Stake weighting the thresholds, if either the leader selection or the committee
+    /// membership is weighted by stake
+    fn stakes(&self) -> Option<&StakeRegistry> {
+        self.leader
+            .stakes()
+            .or_else(|| self.committee_membership.stakes())
+    }
+
     pub fn is_leader(&self, id: &NodeId) -> bool {
         id == &self.current_leader
     }
@@ -233,6 +258,7 @@ where
 mod tests {
     use crate::overlay::leadership::RoundRobin;
     use crate::overlay::membership::FisherYatesShuffle;
+    use crate::overlay::{RandomBeaconState, StakeWeighted};
     use crate::Overlay;
 
     use super::*;
@@ -333,7 +359,10 @@ mod tests {
             super_majority_threshold: None,
         });
 
-        assert_eq!(overlay.super_majority_threshold(overlay.nodes[8]), 0);
+        assert_eq!(
+            overlay.super_majority_threshold(overlay.nodes[8]),
+            Quorum::Count(0)
+        );
     }
 
     #[test]
@@ -348,7 +377,10 @@ mod tests {
             super_majority_threshold: None,
         });
 
-        assert_eq!(overlay.super_majority_threshold(overlay.nodes[0]), 3);
+        assert_eq!(
+            overlay.super_majority_threshold(overlay.nodes[0]),
+            Quorum::Count(3)
+        );
     }
 
     #[test]
@@ -365,7 +397,38 @@ mod tests {
 
         assert_eq!(
             overlay.leader_super_majority_threshold(NodeId::new([0; 32])),
-            3
+            Quorum::Count(3)
         );
     }
+
+    #[test]
+    fn test_stake_weighted_super_majority_threshold() {
+        let nodes: Vec<_> = (0..10).map(|i| NodeId::new([i as u8; 32])).collect();
+        let settings = |stakes| TreeOverlaySettings {
+            current_leader: nodes[0],
+            nodes: nodes.clone(),
+            number_of_committees: 3,
+            leader: StakeWeighted::new(
+                stakes,
+                RandomBeaconState::initial_sad_from_entropy(ENTROPY),
+            ),
+            committee_membership: FisherYatesShuffle::new(ENTROPY),
+            super_majority_threshold: None,
+        };
+        let mut stakes: StakeRegistry = nodes.iter().map(|n| (*n, 1)).collect();
+        let overlay = TreeOverlay::new(settings(stakes.clone()));
+        let root: Vec<NodeId> = overlay.root_committee().into_iter().collect();
+        // with equal stakes, votes count the same as without stakes
+        let quorum = overlay.super_majority_threshold(root[0]);
+        assert!(quorum.is_reached(&root[..3]));
+        assert!(!quorum.is_reached(&root[..2]));
+
+        // a single root member holds most of the stake of the committee
+        stakes.set_stake(root[0], 10);
+        let overlay = TreeOverlay::new(settings(stakes));
+        let quorum = overlay.super_majority_threshold(root[0]);
+        assert!(quorum.is_reached(&root[..1]));
+        assert!(!quorum.is_reached(&root[1..]));
+        assert_eq!(overlay.leader_super_majority_threshold(root[0]), quorum);
+    }
 }
@@ -2,6 +2,7 @@ use crate::{Committee, CommitteeId, NodeId};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(super) struct Tree {
     pub(super) inner_committees: Vec<CommitteeId>,
     pub(super) membership_committees: HashMap<usize, Committee>,
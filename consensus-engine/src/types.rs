@@ -12,6 +12,17 @@ mod block_id;
 pub use block_id::BlockId;
 mod view;
 pub use view::View;
+mod signature;
+pub use signature::{
+    new_view_payload, proposal_payload, timeout_payload, vote_payload, AggregateSignature,
+    SignatureError, SignerBitmap,
+};
+mod evidence;
+pub use evidence::{Claim, Equivocation, EquivocationDetector, Evidence, EvidenceError};
+mod stake;
+pub use stake::{Quorum, Stake, StakeRegistry};
+mod epoch;
+pub use epoch::{Epoch, EpochSchedule, QcSigners};
 
 /// The way the consensus engine communicates with the rest of the system is by returning
 /// actions to be performed.
@@ -63,6 +74,7 @@ pub struct TimeoutQc {
     view: View,
     high_qc: StandardQc,
     sender: NodeId,
+    signature: Option<AggregateSignature>,
 }
 
 impl TimeoutQc {
@@ -78,9 +90,22 @@ impl TimeoutQc {
             view,
             high_qc,
             sender,
+            signature: None,
         }
     }
 
+    /// Like [`TimeoutQc::new`], but returns `None` instead of panicking if the high qc is not
+    /// older than `view`, for timeout qcs built from data received from other nodes.
+    pub fn try_new(view: View, high_qc: StandardQc, sender: NodeId) -> Option<Self> {
+        (view >= high_qc.view).then(|| Self::new(view, high_qc, sender))
+    }
+
+    /// Attach the aggregated signature of the timeouts this qc was built from
+    pub fn with_signature(mut self, signature: AggregateSignature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
     pub fn view(&self) -> View {
         self.view
     }
@@ -92,6 +117,32 @@ impl TimeoutQc {
     pub fn sender(&self) -> NodeId {
         self.sender
     }
+
+    pub fn signature(&self) -> Option<&AggregateSignature> {
+        self.signature.as_ref()
+    }
+
+    /// Check that the timeout qc was signed by the `signers` of its view.
+    ///
+    /// The high qc is not part of the signed timeouts, so it is checked on its own against the
+    /// signers of the view it was built in.
+    pub fn verify(
+        &self,
+        signers: &QcSigners,
+        public_key: impl Fn(&NodeId) -> Option<bls_signatures::PublicKey>,
+    ) -> Result<(), SignatureError> {
+        let (committee, quorum) = signers.of(self.view);
+        self.signature
+            .as_ref()
+            .ok_or(SignatureError::Empty)?
+            .verify(
+                committee,
+                quorum,
+                |signer| timeout_payload(self.view, signer),
+                &public_key,
+            )?;
+        self.high_qc.verify(signers, public_key)
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -101,6 +152,8 @@ pub struct Block {
     pub id: BlockId,
     pub view: View,
     pub parent_qc: Qc,
+    /// Root of the ledger state after executing the block, opaque to consensus
+    pub state_root: [u8; 32],
     pub leader_proof: LeaderProof,
 }
 
@@ -120,6 +173,7 @@ impl Block {
             view: View(0),
             id: BlockId::zeros(),
             parent_qc: Qc::Standard(StandardQc::genesis()),
+            state_root: [0; 32],
             leader_proof: LeaderProof::LeaderId {
                 leader_id: NodeId::new([0; 32]),
             },
@@ -139,6 +193,9 @@ pub struct Send {
 pub struct StandardQc {
     pub view: View,
     pub id: BlockId,
+    /// Aggregated signature of the votes for the block, `None` only for qcs that were not
+    /// built from votes (e.g. genesis).
+    pub signature: Option<AggregateSignature>,
 }
 
 impl StandardQc {
@@ -146,7 +203,31 @@ impl StandardQc {
         Self {
             view: View(-1),
             id: BlockId::zeros(),
+            signature: None,
+        }
+    }
+
+    /// Check that the qc was signed by the `signers` of its view.
+    ///
+    /// The genesis qc is the only one accepted without a signature.
+    pub fn verify(
+        &self,
+        signers: &QcSigners,
+        public_key: impl Fn(&NodeId) -> Option<bls_signatures::PublicKey>,
+    ) -> Result<(), SignatureError> {
+        if *self == Self::genesis() {
+            return Ok(());
         }
+        let (committee, quorum) = signers.of(self.view);
+        self.signature
+            .as_ref()
+            .ok_or(SignatureError::Empty)?
+            .verify(
+                committee,
+                quorum,
+                |signer| vote_payload(self.view, self.id, signer),
+                public_key,
+            )
     }
 }
 
@@ -155,6 +236,8 @@ impl StandardQc {
 pub struct AggregateQc {
     pub high_qc: StandardQc,
     pub view: View,
+    /// Aggregated signature of the new view votes for `view`
+    pub signature: Option<AggregateSignature>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -188,6 +271,44 @@ impl Qc {
             Qc::Aggregated(AggregateQc { high_qc, .. }) => high_qc.clone(),
         }
     }
+
+    pub fn signature(&self) -> Option<&AggregateSignature> {
+        match self {
+            Qc::Standard(StandardQc { signature, .. }) => signature.as_ref(),
+            Qc::Aggregated(AggregateQc { signature, .. }) => signature.as_ref(),
+        }
+    }
+
+    /// Check that the qc was signed by the `signers` of its view.
+    ///
+    /// Standard qcs are signed over the votes for the block, aggregated ones over the
+    /// new view votes of the view they were built in, which do not cover the high qc,
+    /// so that is checked as well against the signers of its own view.
+    pub fn verify(
+        &self,
+        signers: &QcSigners,
+        public_key: impl Fn(&NodeId) -> Option<bls_signatures::PublicKey>,
+    ) -> Result<(), SignatureError> {
+        let signature = self.signature().ok_or(SignatureError::Empty)?;
+        let (committee, quorum) = signers.of(self.view());
+        match self {
+            Qc::Standard(StandardQc { view, id, .. }) => signature.verify(
+                committee,
+                quorum,
+                |signer| vote_payload(*view, *id, signer),
+                public_key,
+            ),
+            Qc::Aggregated(AggregateQc { view, high_qc, .. }) => {
+                signature.verify(
+                    committee,
+                    quorum,
+                    |signer| new_view_payload(*view, signer),
+                    &public_key,
+                )?;
+                high_qc.verify(signers, public_key)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -199,6 +320,7 @@ mod test {
         let standard_qc = StandardQc {
             view: View(10),
             id: BlockId::zeros(),
+            signature: None,
         };
         let qc = Qc::Standard(standard_qc.clone());
         assert_eq!(qc.view(), View(10));
@@ -213,7 +335,9 @@ mod test {
             high_qc: StandardQc {
                 view: View(10),
                 id: BlockId::zeros(),
+                signature: None,
             },
+            signature: None,
         };
         let qc = Qc::Aggregated(aggregated_qc.clone());
         assert_eq!(qc.view(), View(20));
@@ -221,6 +345,113 @@ mod test {
         assert_eq!(qc.high_qc(), aggregated_qc.high_qc);
     }
 
+    fn keys(n: u8) -> Vec<(NodeId, bls_signatures::PrivateKey)> {
+        (0..n)
+            .map(|i| {
+                let sk = bls_signatures::PrivateKey::new([i; 32]);
+                (NodeId::from_public_key(&sk.public_key()), sk)
+            })
+            .collect()
+    }
+
+    fn sign(
+        keys: &[(NodeId, bls_signatures::PrivateKey)],
+        payload: impl Fn(&NodeId) -> Vec<u8>,
+    ) -> AggregateSignature {
+        let committee = keys.iter().map(|(id, _)| *id).collect();
+        let signatures = keys.iter().map(|(id, sk)| (*id, sk.sign(payload(id))));
+        AggregateSignature::aggregate(&committee, signatures).unwrap()
+    }
+
+    #[test]
+    fn verify_high_qc() {
+        let keys = keys(3);
+        let committee: Committee = keys.iter().map(|(id, _)| *id).collect();
+        let signers = QcSigners::new(committee, Quorum::Count(3));
+        let public_key = |id: &NodeId| {
+            keys.iter()
+                .find(|(other, _)| other == id)
+                .map(|(_, sk)| sk.public_key())
+        };
+        let (view, block) = (View(1), BlockId::new([1; 32]));
+        let signed = StandardQc {
+            view,
+            id: block,
+            signature: Some(sign(&keys, |id| vote_payload(view, block, id))),
+        };
+        // a qc claiming a block nobody voted for, reusing the votes of another one
+        let forged = StandardQc {
+            id: BlockId::new([2; 32]),
+            ..signed.clone()
+        };
+        let unsigned = StandardQc {
+            signature: None,
+            ..signed.clone()
+        };
+
+        for (high_qc, valid) in [
+            (StandardQc::genesis(), true),
+            (signed, true),
+            (forged, false),
+            (unsigned, false),
+        ] {
+            let timeout_qc = TimeoutQc {
+                view: View(2),
+                high_qc: high_qc.clone(),
+                sender: keys[0].0,
+                signature: Some(sign(&keys, |id| timeout_payload(View(2), id))),
+            };
+            assert_eq!(timeout_qc.verify(&signers, public_key).is_ok(), valid);
+            let aggregated = Qc::Aggregated(AggregateQc {
+                high_qc,
+                view: View(3),
+                signature: Some(sign(&keys, |id| new_view_payload(View(3), id))),
+            });
+            assert_eq!(aggregated.verify(&signers, public_key).is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn verify_high_qc_across_epochs() {
+        let keys = keys(4);
+        let (previous, current) = keys.split_at(2);
+        let committee = |keys: &[(NodeId, bls_signatures::PrivateKey)]| -> Committee {
+            keys.iter().map(|(id, _)| *id).collect()
+        };
+        let public_key = |id: &NodeId| {
+            keys.iter()
+                .find(|(other, _)| other == id)
+                .map(|(_, sk)| sk.public_key())
+        };
+        // the validator set changed at view 2, after the high qc was built
+        let mut signers = QcSigners::new(committee(previous), Quorum::Count(2));
+        signers.change_at(View(2), committee(current), Quorum::Count(2));
+        let (view, block) = (View(1), BlockId::new([1; 32]));
+        let high_qc = StandardQc {
+            view,
+            id: block,
+            signature: Some(sign(previous, |id| vote_payload(view, block, id))),
+        };
+        let timeout_qc = TimeoutQc {
+            view: View(2),
+            high_qc: high_qc.clone(),
+            sender: current[0].0,
+            signature: Some(sign(current, |id| timeout_payload(View(2), id))),
+        };
+        let aggregated = Qc::Aggregated(AggregateQc {
+            high_qc,
+            view: View(3),
+            signature: Some(sign(current, |id| new_view_payload(View(3), id))),
+        });
+        assert!(timeout_qc.verify(&signers, public_key).is_ok());
+        assert!(aggregated.verify(&signers, public_key).is_ok());
+
+        // the high qc was not signed by the validator set of the timeout qc
+        let current_only = QcSigners::new(committee(current), Quorum::Count(2));
+        assert!(timeout_qc.verify(&current_only, public_key).is_err());
+        assert!(aggregated.verify(&current_only, public_key).is_err());
+    }
+
     #[test]
     fn new_timeout_qc() {
         let timeout_qc = TimeoutQc::new(
@@ -228,6 +459,7 @@ mod test {
             StandardQc {
                 view: View(1),
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -241,6 +473,7 @@ mod test {
             StandardQc {
                 view: View(2),
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -250,6 +483,17 @@ mod test {
         assert_eq!(timeout_qc.sender(), NodeId::new([0; 32]));
     }
 
+    #[test]
+    fn try_new_timeout_qc() {
+        let high_qc = StandardQc {
+            view: View(2),
+            id: BlockId::zeros(),
+            signature: None,
+        };
+        assert!(TimeoutQc::try_new(View(2), high_qc.clone(), NodeId::new([0; 32])).is_some());
+        assert!(TimeoutQc::try_new(View(1), high_qc, NodeId::new([0; 32])).is_none());
+    }
+
     #[test]
     #[should_panic(
         expected = "timeout_qc.view:1 shouldn't be lower than timeout_qc.high_qc.view:2"
@@ -260,6 +504,7 @@ mod test {
             StandardQc {
                 view: View(2),
                 id: BlockId::zeros(),
+                signature: None,
             },
             NodeId::new([0; 32]),
         );
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroU64;
+
+use crate::{Committee, NodeId, Quorum, View};
+
+/// Index of a span of `epoch_length` consecutive views, the first epoch starting at view 0
+pub type Epoch = u64;
+
+/// Validator set changes, taking effect at epoch boundaries.
+///
+/// Every entry is the full validator set from its epoch onwards, until the next entry.
+/// Since the epoch only depends on the view, every node switches to the same validator set
+/// when moving to the first view of the epoch. The initial validator set is the one the overlay
+/// was built with, an entry for epoch 0 does not change it but is needed to check the qcs of
+/// the views before the first change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EpochSchedule {
+    epoch_length: NonZeroU64,
+    validators: BTreeMap<Epoch, Vec<NodeId>>,
+}
+
+impl EpochSchedule {
+    pub fn new(epoch_length: NonZeroU64) -> Self {
+        Self {
+            epoch_length,
+            validators: BTreeMap::new(),
+        }
+    }
+
+    /// Replace the validator set from `epoch` onwards.
+    ///
+    /// All nodes must use the same order, as the overlay is built from it.
+    pub fn set_validators(&mut self, epoch: Epoch, validators: Vec<NodeId>) {
+        self.validators.insert(epoch, validators);
+    }
+
+    pub fn epoch(&self, view: View) -> Epoch {
+        view.0.max(0) as u64 / self.epoch_length.get()
+    }
+
+    /// First view of `epoch`
+    pub fn first_view(&self, epoch: Epoch) -> View {
+        View((epoch * self.epoch_length.get()) as i64)
+    }
+
+    /// Validator set changes up to `epoch` included, from the oldest to the newest
+    pub fn changes_until(&self, epoch: Epoch) -> impl Iterator<Item = (Epoch, &[NodeId])> {
+        self.validators
+            .range(..=epoch)
+            .map(|(epoch, validators)| (*epoch, validators.as_slice()))
+    }
+
+    /// Validator set to switch to when moving from view `from` to view `to`,
+    /// if it changes in the epochs started in between
+    pub fn validators_change(&self, from: View, to: View) -> Option<&[NodeId]> {
+        let (from, to) = (self.epoch(from), self.epoch(to));
+        if to <= from {
+            return None;
+        }
+        self.validators
+            .range(from + 1..=to)
+            .next_back()
+            .map(|(_, validators)| validators.as_slice())
+    }
+}
+
+/// Root committee and quorum the qcs of each view are signed by.
+///
+/// Qcs are signed by the root committee of the validator set of the epoch of their view,
+/// so qcs built before a validator set change are checked against the previous one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QcSigners {
+    /// Signers of the qcs from each view onwards, until the next one
+    signers: BTreeMap<View, (Committee, Quorum)>,
+}
+
+impl QcSigners {
+    /// Same signers for the qcs of every view
+    pub fn new(committee: Committee, quorum: Quorum) -> Self {
+        Self {
+            signers: [(View(i64::MIN), (committee, quorum))].into(),
+        }
+    }
+
+    /// Replace the signers of the qcs from `view` onwards
+    pub fn change_at(&mut self, view: View, committee: Committee, quorum: Quorum) {
+        self.signers.split_off(&view);
+        self.signers.insert(view, (committee, quorum));
+    }
+
+    /// Root committee and quorum the qcs of `view` are signed by
+    pub fn of(&self, view: View) -> (&Committee, &Quorum) {
+        let (_, (committee, quorum)) = self
+            .signers
+            .range(..=view)
+            .next_back()
+            .expect("signers are known from the first view");
+        (committee, quorum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qc_signers_across_epochs() {
+        let committee =
+            |ids: &[u8]| -> Committee { ids.iter().map(|i| NodeId::new([*i; 32])).collect() };
+        let mut signers = QcSigners::new(committee(&[0, 1]), Quorum::Count(2));
+        signers.change_at(View(4), committee(&[2]), Quorum::Count(1));
+
+        assert_eq!(
+            signers.of(View(-1)),
+            (&committee(&[0, 1]), &Quorum::Count(2))
+        );
+        assert_eq!(
+            signers.of(View(3)),
+            (&committee(&[0, 1]), &Quorum::Count(2))
+        );
+        assert_eq!(signers.of(View(4)), (&committee(&[2]), &Quorum::Count(1)));
+        assert_eq!(signers.of(View(9)), (&committee(&[2]), &Quorum::Count(1)));
+
+        // later changes replace the ones they overlap with
+        signers.change_at(View(2), committee(&[1]), Quorum::Count(1));
+        assert_eq!(signers.of(View(5)), (&committee(&[1]), &Quorum::Count(1)));
+    }
+}
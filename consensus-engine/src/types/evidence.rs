@@ -0,0 +1,291 @@
+// std
+use std::collections::{BTreeMap, HashMap};
+// crates
+use bls_signatures::{PublicKey, Serialize as BlsSerialize, Signature};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+// internal
+use super::signature::{proposal_payload, vote_payload, SignatureError};
+use crate::{BlockId, NodeId, View};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EvidenceError {
+    #[error("Claims are for the same block")]
+    NotConflicting,
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+}
+
+/// Messages a node is allowed to sign only once per view
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Equivocation {
+    /// The leader proposed two different blocks
+    Proposal,
+    /// A node voted for two different blocks
+    Vote,
+}
+
+impl Equivocation {
+    fn payload(&self, view: View, block: BlockId, signer: &NodeId) -> Vec<u8> {
+        match self {
+            Self::Proposal => proposal_payload(view, block, signer),
+            Self::Vote => vote_payload(view, block, signer),
+        }
+    }
+}
+
+/// A block signed by a node, either as a proposal or as a vote
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Claim {
+    pub block: BlockId,
+    pub signature: Box<[u8]>,
+}
+
+/// Proof that `offender` signed two different blocks in `view` where only one was allowed.
+/// It can be verified by anyone, regardless of the node that reported it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Evidence {
+    pub equivocation: Equivocation,
+    pub offender: NodeId,
+    pub view: View,
+    /// BLS public key of the offender, its id is derived from it
+    pub public_key: Box<[u8]>,
+    pub claims: [Claim; 2],
+}
+
+impl Evidence {
+    /// Check that the claims conflict and were both signed by the offender
+    pub fn verify(&self) -> Result<(), EvidenceError> {
+        let [first, second] = &self.claims;
+        if first.block == second.block {
+            return Err(EvidenceError::NotConflicting);
+        }
+        let public_key = signer_key(&self.public_key, &self.offender)?;
+        for claim in &self.claims {
+            verify_claim(
+                self.equivocation,
+                self.view,
+                &self.offender,
+                &public_key,
+                claim,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// First claim of each signer in a view, along with the signer public key
+type Claims = HashMap<(Equivocation, NodeId), (Box<[u8]>, Claim)>;
+
+/// Number of views after the oldest one kept for which claims are recorded
+const VIEWS_AHEAD: View = View(2);
+
+/// Remembers the blocks signed by each node in recent views to detect conflicting ones.
+///
+/// Signatures are only checked once a conflict shows up, so messages can be observed before
+/// being verified. Only claims for a few views from the one last pruned up to are recorded,
+/// so that claims for arbitrary views can't grow the detector unbounded.
+#[derive(Debug, Clone, Default)]
+pub struct EquivocationDetector {
+    seen: BTreeMap<View, Claims>,
+    oldest: View,
+}
+
+impl EquivocationDetector {
+    /// Record a `claim` signed by `signer`, returning evidence if it conflicts with a claim
+    /// observed before for the same view.
+    ///
+    /// Claims for views outside of the window of recent views are ignored.
+    pub fn observe(
+        &mut self,
+        equivocation: Equivocation,
+        view: View,
+        signer: NodeId,
+        public_key: &[u8],
+        claim: Claim,
+    ) -> Option<Evidence> {
+        if view < self.oldest || view > self.oldest + VIEWS_AHEAD {
+            return None;
+        }
+        let seen = self.seen.entry(view).or_default();
+        let Some((first_key, first)) = seen.get(&(equivocation, signer)) else {
+            seen.insert((equivocation, signer), (public_key.into(), claim));
+            return None;
+        };
+        if first.block == claim.block {
+            return None;
+        }
+        let is_genuine = |public_key: &[u8], claim: &Claim| {
+            signer_key(public_key, &signer)
+                .and_then(|key| verify_claim(equivocation, view, &signer, &key, claim))
+                .is_ok()
+        };
+        if !is_genuine(public_key, &claim) {
+            return None;
+        }
+        if !is_genuine(first_key, first) {
+            // the first claim was forged, keep the genuine one instead
+            seen.insert((equivocation, signer), (public_key.into(), claim));
+            return None;
+        }
+        Some(Evidence {
+            equivocation,
+            offender: signer,
+            view,
+            public_key: public_key.into(),
+            claims: [first.clone(), claim],
+        })
+    }
+
+    /// Forget about claims for views older than `view` and move the window of recorded views
+    /// forward to start from it
+    pub fn prune(&mut self, view: View) {
+        self.seen = self.seen.split_off(&view);
+        self.oldest = self.oldest.max(view);
+    }
+}
+
+fn signer_key(public_key: &[u8], signer: &NodeId) -> Result<PublicKey, SignatureError> {
+    let public_key = PublicKey::from_bytes(public_key).map_err(|_| SignatureError::Malformed)?;
+    if NodeId::from_public_key(&public_key) != *signer {
+        return Err(SignatureError::Invalid);
+    }
+    Ok(public_key)
+}
+
+fn verify_claim(
+    equivocation: Equivocation,
+    view: View,
+    signer: &NodeId,
+    public_key: &PublicKey,
+    claim: &Claim,
+) -> Result<(), SignatureError> {
+    let signature =
+        Signature::from_bytes(&claim.signature).map_err(|_| SignatureError::Malformed)?;
+    public_key
+        .verify(signature, equivocation.payload(view, claim.block, signer))
+        .then_some(())
+        .ok_or(SignatureError::Invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls_signatures::PrivateKey;
+
+    fn claim(private_key: &PrivateKey, view: View, block: BlockId) -> Claim {
+        let signer = NodeId::from_public_key(&private_key.public_key());
+        Claim {
+            block,
+            signature: private_key
+                .sign(vote_payload(view, block, &signer))
+                .as_bytes()
+                .into_boxed_slice(),
+        }
+    }
+
+    #[test]
+    fn double_vote() {
+        let private_key = PrivateKey::new([1; 32]);
+        let public_key = private_key.public_key().as_bytes();
+        let signer = NodeId::from_public_key(&private_key.public_key());
+        let view = View::new(1);
+        let (first, second) = (BlockId::new([1; 32]), BlockId::new([2; 32]));
+
+        let mut detector = EquivocationDetector::default();
+        let mut observe =
+            |claim| detector.observe(Equivocation::Vote, view, signer, &public_key, claim);
+        assert!(observe(claim(&private_key, view, first)).is_none());
+        // the same vote can be received multiple times
+        assert!(observe(claim(&private_key, view, first)).is_none());
+        let evidence = observe(claim(&private_key, view, second)).unwrap();
+        assert_eq!(evidence.offender, signer);
+        evidence.verify().unwrap();
+
+        // votes signed for other views are not evidence of anything
+        let mut tampered = evidence.clone();
+        tampered.claims[1] = claim(&private_key, view.next(), second);
+        assert!(matches!(
+            tampered.verify(),
+            Err(EvidenceError::Signature(SignatureError::Invalid))
+        ));
+
+        let mut tampered = evidence;
+        tampered.claims[1] = tampered.claims[0].clone();
+        assert!(matches!(
+            tampered.verify(),
+            Err(EvidenceError::NotConflicting)
+        ));
+    }
+
+    #[test]
+    fn forged_claims() {
+        let private_key = PrivateKey::new([1; 32]);
+        let public_key = private_key.public_key().as_bytes();
+        let signer = NodeId::from_public_key(&private_key.public_key());
+        let other = PrivateKey::new([2; 32]);
+        let view = View::new(1);
+        let (first, second) = (BlockId::new([1; 32]), BlockId::new([2; 32]));
+
+        let mut detector = EquivocationDetector::default();
+        let mut observe =
+            |claim| detector.observe(Equivocation::Vote, view, signer, &public_key, claim);
+        // a forged claim seen first does not shadow the genuine one
+        assert!(observe(claim(&other, view, first)).is_none());
+        assert!(observe(claim(&private_key, view, second)).is_none());
+        assert!(observe(claim(&other, view, first)).is_none());
+        assert!(observe(claim(&private_key, view, first)).is_some());
+    }
+
+    #[test]
+    fn prune() {
+        let private_key = PrivateKey::new([1; 32]);
+        let public_key = private_key.public_key().as_bytes();
+        let signer = NodeId::from_public_key(&private_key.public_key());
+        let view = View::new(1);
+
+        let mut detector = EquivocationDetector::default();
+        let claim_first = claim(&private_key, view, BlockId::new([1; 32]));
+        detector.observe(Equivocation::Vote, view, signer, &public_key, claim_first);
+        detector.prune(view.next());
+        let claim_second = claim(&private_key, view, BlockId::new([2; 32]));
+        assert!(detector
+            .observe(Equivocation::Vote, view, signer, &public_key, claim_second)
+            .is_none());
+    }
+
+    #[test]
+    fn views_outside_window() {
+        let private_key = PrivateKey::new([1; 32]);
+        let public_key = private_key.public_key().as_bytes();
+        let signer = NodeId::from_public_key(&private_key.public_key());
+        let (first, second) = (BlockId::new([1; 32]), BlockId::new([2; 32]));
+
+        let mut detector = EquivocationDetector::default();
+        detector.prune(View::new(5));
+        let mut observe = |view| {
+            detector.observe(
+                Equivocation::Vote,
+                view,
+                signer,
+                &public_key,
+                claim(&private_key, view, first),
+            );
+            detector.observe(
+                Equivocation::Vote,
+                view,
+                signer,
+                &public_key,
+                claim(&private_key, view, second),
+            )
+        };
+        assert!(observe(View::new(4)).is_none());
+        assert!(observe(View::new(5)).is_some());
+        assert!(observe(View::new(7)).is_some());
+        assert!(observe(View::new(8)).is_none());
+        assert_eq!(detector.seen.len(), 2);
+    }
+}
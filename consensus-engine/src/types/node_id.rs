@@ -23,6 +23,14 @@ impl NodeId {
         Self(val)
     }
 
+    /// Derive the id of a node from its BLS public key, binding the identity to the key
+    /// used to sign consensus messages.
+    pub fn from_public_key(public_key: &bls_signatures::PublicKey) -> Self {
+        use blake2::{digest::consts::U32, Blake2b, Digest};
+        use bls_signatures::Serialize;
+        Self(Blake2b::<U32>::digest(public_key.as_bytes()).into())
+    }
+
     /// Returns a random node id
     #[cfg(any(test, feature = "simulation"))]
     pub fn random<R: rand::Rng>(rng: &mut R) -> Self {
@@ -0,0 +1,277 @@
+// std
+// crates
+use bls_signatures::{PublicKey, Serialize as BlsSerialize, Signature};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+// internal
+use crate::{BlockId, Committee, NodeId, Quorum, View};
+
+const VOTE_TAG: &[u8] = b"NOMOS_CARNOT_VOTE";
+const TIMEOUT_TAG: &[u8] = b"NOMOS_CARNOT_TIMEOUT";
+const NEW_VIEW_TAG: &[u8] = b"NOMOS_CARNOT_NEW_VIEW";
+const PROPOSAL_TAG: &[u8] = b"NOMOS_CARNOT_PROPOSAL";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Malformed signature or public key")]
+    Malformed,
+    #[error("No signatures to aggregate")]
+    Empty,
+    #[error("Signer {0} is not a member of the committee")]
+    NotAMember(NodeId),
+    #[error("Missing public key for signer {0}")]
+    MissingPublicKey(NodeId),
+    #[error("Not enough signers for a quorum: {found}")]
+    InsufficientSigners { found: usize },
+    #[error("Signature verification failed")]
+    Invalid,
+}
+
+/// Bytes signed by a node when voting for `block` in `view`.
+///
+/// The signer id is part of the payload so that every aggregated message is distinct, which
+/// lets us verify aggregated signatures without being exposed to rogue key attacks.
+pub fn vote_payload(view: View, block: BlockId, signer: &NodeId) -> Vec<u8> {
+    payload(VOTE_TAG, view, Some(block), signer)
+}
+
+/// Bytes signed by a node when signaling a local timeout for `view`.
+pub fn timeout_payload(view: View, signer: &NodeId) -> Vec<u8> {
+    payload(TIMEOUT_TAG, view, None, signer)
+}
+
+/// Bytes signed by a node when voting for moving to `view` after a timeout.
+pub fn new_view_payload(view: View, signer: &NodeId) -> Vec<u8> {
+    payload(NEW_VIEW_TAG, view, None, signer)
+}
+
+/// Bytes signed by the leader of `view` when proposing `block`.
+pub fn proposal_payload(view: View, block: BlockId, signer: &NodeId) -> Vec<u8> {
+    payload(PROPOSAL_TAG, view, Some(block), signer)
+}
+
+fn payload(tag: &[u8], view: View, block: Option<BlockId>, signer: &NodeId) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(tag.len() + 8 + 32 + 32);
+    bytes.extend_from_slice(tag);
+    bytes.extend_from_slice(&view.0.to_be_bytes());
+    if let Some(block) = block {
+        bytes.extend_from_slice(&block.0);
+    }
+    bytes.extend_from_slice(&signer.0);
+    bytes
+}
+
+/// Bitmap marking which members of a committee took part in an aggregated signature.
+///
+/// Bit `i` refers to the `i`-th member of the committee in its canonical (sorted) order.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SignerBitmap(Box<[u8]>);
+
+impl SignerBitmap {
+    pub fn new(size: usize) -> Self {
+        Self(vec![0; size.div_ceil(8)].into_boxed_slice())
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.0[index / 8] |= 1 << (index % 8);
+    }
+
+    pub fn is_set(&self, index: usize) -> bool {
+        self.0
+            .get(index / 8)
+            .map(|byte| byte & (1 << (index % 8)) != 0)
+            .unwrap_or(false)
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|byte| byte.count_ones() as usize).sum()
+    }
+
+    /// Members of `committee` flagged in this bitmap
+    pub fn signers<'a>(&'a self, committee: &'a Committee) -> impl Iterator<Item = &'a NodeId> {
+        committee
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.is_set(*index))
+            .map(|(_, id)| id)
+    }
+
+    fn fits(&self, committee: &Committee) -> bool {
+        self.0.len() == committee.len().div_ceil(8)
+            && (committee.len()..self.0.len() * 8).all(|index| !self.is_set(index))
+    }
+}
+
+/// BLS signature aggregated over the members of a committee, together with the bitmap of
+/// the members that signed.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AggregateSignature {
+    signers: SignerBitmap,
+    // compressed signature bytes, checked when verifying
+    signature: Box<[u8]>,
+}
+
+impl AggregateSignature {
+    /// Aggregate the signatures of `committee` members.
+    /// Signatures from nodes outside the committee are rejected.
+    pub fn aggregate(
+        committee: &Committee,
+        signatures: impl IntoIterator<Item = (NodeId, Signature)>,
+    ) -> Result<Self, SignatureError> {
+        let members: Vec<&NodeId> = committee.iter().collect();
+        let mut signers = SignerBitmap::new(members.len());
+        let mut collected = Vec::new();
+        for (id, signature) in signatures {
+            let index = members
+                .binary_search(&&id)
+                .map_err(|_| SignatureError::NotAMember(id))?;
+            if !signers.is_set(index) {
+                signers.set(index);
+                collected.push(signature);
+            }
+        }
+        let signature = bls_signatures::aggregate(&collected).map_err(|_| SignatureError::Empty)?;
+        Ok(Self {
+            signers,
+            signature: signature.as_bytes().into_boxed_slice(),
+        })
+    }
+
+    pub fn signers(&self) -> &SignerBitmap {
+        &self.signers
+    }
+
+    /// Check that members of `committee` reaching `quorum` signed their own `payload`.
+    pub fn verify(
+        &self,
+        committee: &Committee,
+        quorum: &Quorum,
+        payload: impl Fn(&NodeId) -> Vec<u8>,
+        public_key: impl Fn(&NodeId) -> Option<PublicKey>,
+    ) -> Result<(), SignatureError> {
+        if !self.signers.fits(committee) {
+            return Err(SignatureError::Malformed);
+        }
+        let found = self.signers.count();
+        if found == 0 || !quorum.is_reached(self.signers.signers(committee)) {
+            return Err(SignatureError::InsufficientSigners { found });
+        }
+        let signature =
+            Signature::from_bytes(&self.signature).map_err(|_| SignatureError::Malformed)?;
+        let mut messages = Vec::with_capacity(found);
+        let mut public_keys = Vec::with_capacity(found);
+        for signer in self.signers.signers(committee) {
+            messages.push(payload(signer));
+            public_keys.push(public_key(signer).ok_or(SignatureError::MissingPublicKey(*signer))?);
+        }
+        let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+        if bls_signatures::verify_messages(&signature, &messages, &public_keys) {
+            Ok(())
+        } else {
+            Err(SignatureError::Invalid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls_signatures::PrivateKey;
+
+    fn keys(n: u8) -> Vec<(NodeId, PrivateKey)> {
+        (0..n)
+            .map(|i| {
+                let sk = PrivateKey::new([i; 32]);
+                (NodeId::from_public_key(&sk.public_key()), sk)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bitmap() {
+        let mut bitmap = SignerBitmap::new(10);
+        bitmap.set(0);
+        bitmap.set(9);
+        assert!(bitmap.is_set(0));
+        assert!(!bitmap.is_set(1));
+        assert!(bitmap.is_set(9));
+        assert!(!bitmap.is_set(100));
+        assert_eq!(bitmap.count(), 2);
+    }
+
+    #[test]
+    fn aggregate_and_verify() {
+        let keys = keys(4);
+        let committee: Committee = keys.iter().map(|(id, _)| *id).collect();
+        let (view, block) = (View::new(1), BlockId::new([1; 32]));
+        let signatures = keys[..3]
+            .iter()
+            .map(|(id, sk)| (*id, sk.sign(vote_payload(view, block, id))));
+        let signature = AggregateSignature::aggregate(&committee, signatures).unwrap();
+        assert_eq!(signature.signers().count(), 3);
+
+        let public_key = |id: &NodeId| {
+            keys.iter()
+                .find(|(other, _)| other == id)
+                .map(|(_, sk)| sk.public_key())
+        };
+        signature
+            .verify(
+                &committee,
+                &Quorum::Count(3),
+                |id| vote_payload(view, block, id),
+                public_key,
+            )
+            .unwrap();
+        assert!(matches!(
+            signature.verify(
+                &committee,
+                &Quorum::Count(4),
+                |id| vote_payload(view, block, id),
+                public_key
+            ),
+            Err(SignatureError::InsufficientSigners { .. })
+        ));
+        // the missing signer holds most of the stake
+        let stakes = keys
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _))| (*id, if i == 3 { 10 } else { 1 }))
+            .collect();
+        assert!(matches!(
+            signature.verify(
+                &committee,
+                &Quorum::Stake {
+                    stakes,
+                    required: 9
+                },
+                |id| vote_payload(view, block, id),
+                public_key
+            ),
+            Err(SignatureError::InsufficientSigners { .. })
+        ));
+        assert!(matches!(
+            signature.verify(
+                &committee,
+                &Quorum::Count(3),
+                |id| vote_payload(view.next(), block, id),
+                public_key
+            ),
+            Err(SignatureError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn reject_non_members() {
+        let keys = keys(3);
+        let committee: Committee = keys[..2].iter().map(|(id, _)| *id).collect();
+        let (outsider, sk) = &keys[2];
+        let result = AggregateSignature::aggregate(
+            &committee,
+            [(*outsider, sk.sign(timeout_payload(View::new(0), outsider)))],
+        );
+        assert!(matches!(result, Err(SignatureError::NotAMember(_))));
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use crate::NodeId;
+
+pub type Stake = u64;
+
+/// Stake bonded by every validator, weighting its chances of being selected and its votes.
+///
+/// Nodes missing from the registry have no stake.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct StakeRegistry(BTreeMap<NodeId, Stake>);
+
+impl StakeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stake(&self, id: &NodeId) -> Stake {
+        self.0.get(id).copied().unwrap_or_default()
+    }
+
+    pub fn set_stake(&mut self, id: NodeId, stake: Stake) {
+        self.0.insert(id, stake);
+    }
+
+    /// Stake held by `nodes` altogether
+    pub fn total_stake<'a>(&self, nodes: impl IntoIterator<Item = &'a NodeId>) -> u128 {
+        nodes.into_iter().map(|id| self.stake(id) as u128).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&NodeId, &Stake)> {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<(NodeId, Stake)> for StakeRegistry {
+    fn from_iter<T: IntoIterator<Item = (NodeId, Stake)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Votes needed to reach a supermajority of a committee.
+///
+/// Voters are expected to be distinct members of the committee.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quorum {
+    /// Votes from at least this many members, no vote is needed if zero
+    Count(usize),
+    /// Votes from members holding together at least `required` stake
+    Stake {
+        stakes: StakeRegistry,
+        required: u128,
+    },
+}
+
+impl Quorum {
+    /// Whether the votes of `voters` are enough
+    pub fn is_reached<'a>(&self, voters: impl IntoIterator<Item = &'a NodeId>) -> bool {
+        match self {
+            Self::Count(count) => voters.into_iter().count() >= *count,
+            Self::Stake { stakes, required } => stakes.total_stake(voters) >= *required,
+        }
+    }
+}
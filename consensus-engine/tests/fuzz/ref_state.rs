@@ -44,6 +44,7 @@ impl ReferenceStateMachine for RefState {
             view: View::new(0),
             id: BlockId::zeros(),
             parent_qc: Qc::Standard(StandardQc::genesis()),
+            state_root: [0; 32],
             leader_proof: LEADER_PROOF.clone(),
         };
 
@@ -272,6 +273,7 @@ impl RefState {
                                 StandardQc {
                                     view: block.view,
                                     id: block.id,
+                                    signature: None,
                                 },
                                 SENDER,
                             ))
@@ -337,7 +339,9 @@ impl RefState {
             parent_qc: Qc::Aggregated(AggregateQc {
                 high_qc: self.high_qc(),
                 view: current_view,
+                signature: None,
             }),
+            state_root: [0; 32],
             leader_proof: LEADER_PROOF.clone(),
         }))
         .boxed()
@@ -400,7 +404,9 @@ impl RefState {
             parent_qc: Qc::Standard(StandardQc {
                 view: parent.view,
                 id: parent.id,
+                signature: None,
             }),
+            state_root: [0; 32],
             leader_proof: LEADER_PROOF.clone(),
         }
     }
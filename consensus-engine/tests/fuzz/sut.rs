@@ -25,6 +25,7 @@ impl ConsensusEngineTest {
                 view: View::new(0),
                 id: BlockId::zeros(),
                 parent_qc: Qc::Standard(StandardQc::genesis()),
+                state_root: [0; 32],
                 leader_proof: LeaderProof::LeaderId {
                     leader_id: NodeId::new([0; 32]),
                 },
@@ -0,0 +1,173 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use mixnet_protocol::final_payload::AckId;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sphinx_packet::constants::DESTINATION_ADDRESS_LENGTH;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::sender::Sender;
+
+/// Acknowledgements of the fragments sent to the mixnet.
+///
+/// Every fragment carries a single-use reply block, used by its destination node to send an
+/// acknowledgement back to `reply_address`. Fragments not acknowledged in time are sent again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AckConfig {
+    /// Listen address of the mix node this client receives payloads from
+    pub reply_address: SocketAddr,
+    #[serde(default = "AckConfig::default_timeout")]
+    pub timeout: Duration,
+    #[serde(default = "AckConfig::default_max_retransmissions")]
+    pub max_retransmissions: usize,
+}
+
+impl AckConfig {
+    const fn default_timeout() -> Duration {
+        Duration::from_secs(30)
+    }
+
+    const fn default_max_retransmissions() -> usize {
+        3
+    }
+}
+
+/// Fragments waiting for their acknowledgement
+#[derive(Clone, Default)]
+pub(crate) struct AckTracker {
+    pending: Arc<Mutex<HashMap<AckId, oneshot::Sender<()>>>>,
+}
+
+impl AckTracker {
+    pub(crate) fn register(&self, id: AckId) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        rx
+    }
+
+    pub(crate) fn forget(&self, id: &AckId) {
+        self.pending.lock().unwrap().remove(id);
+    }
+
+    /// Returns whether the acknowledged fragment was waiting for it
+    pub(crate) fn ack(&self, id: &AckId) -> bool {
+        match self.pending.lock().unwrap().remove(id) {
+            Some(tx) => tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// A fragment to send again, since it was not acknowledged in time
+pub(crate) struct Retransmission {
+    pub(crate) fragment: Vec<u8>,
+    pub(crate) destination: [u8; DESTINATION_ADDRESS_LENGTH],
+    pub(crate) total_delay: Duration,
+    pub(crate) attempt: usize,
+}
+
+/// State shared by the senders of a client to track acknowledgements
+#[derive(Clone)]
+pub(crate) struct Acks {
+    pub(crate) config: AckConfig,
+    pub(crate) tracker: AckTracker,
+    pub(crate) retransmissions: mpsc::UnboundedSender<Retransmission>,
+}
+
+impl Acks {
+    /// Waits for the acknowledgement of `id`, scheduling a retransmission if it does not come
+    pub(crate) async fn wait(
+        &self,
+        id: AckId,
+        ack: oneshot::Receiver<()>,
+        retransmission: Retransmission,
+    ) {
+        if tokio::time::timeout(self.config.timeout, ack).await.is_ok() {
+            return;
+        }
+        self.tracker.forget(&id);
+        if retransmission.attempt > self.config.max_retransmissions {
+            tracing::warn!("giving up on a fragment never acknowledged");
+            return;
+        }
+        tracing::debug!("fragment not acknowledged in time, sending it again");
+        // the receiving half only closes along with the client
+        let _ = self.retransmissions.send(retransmission);
+    }
+}
+
+/// Sends again the fragments not acknowledged in time, running alongside the client
+pub struct Retransmitter<R: Rng> {
+    sender: Sender<R>,
+    retransmissions: mpsc::UnboundedReceiver<Retransmission>,
+}
+
+impl<R: Rng> Retransmitter<R> {
+    pub(crate) fn new(
+        sender: Sender<R>,
+        retransmissions: mpsc::UnboundedReceiver<Retransmission>,
+    ) -> Self {
+        Self {
+            sender,
+            retransmissions,
+        }
+    }
+
+    pub async fn run(mut self) {
+        while let Some(retransmission) = self.retransmissions.recv().await {
+            if let Err(e) = self.sender.retransmit(retransmission) {
+                tracing::error!("failed to send a fragment again: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn ack_pending_fragment() {
+        let tracker = AckTracker::default();
+        let ack = tracker.register([1; 16]);
+        assert!(!tracker.ack(&[2; 16]));
+        assert!(tracker.ack(&[1; 16]));
+        assert!(ack.await.is_ok());
+        // every acknowledgement is used once
+        assert!(!tracker.ack(&[1; 16]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retransmit_unacknowledged_fragment() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let acks = Acks {
+            config: AckConfig {
+                reply_address: ([127, 0, 0, 1], 7778).into(),
+                timeout: Duration::from_secs(1),
+                max_retransmissions: 1,
+            },
+            tracker: AckTracker::default(),
+            retransmissions: tx,
+        };
+        let retransmission = |attempt| Retransmission {
+            fragment: vec![1, 2, 3],
+            destination: [0; 32],
+            total_delay: Duration::ZERO,
+            attempt,
+        };
+
+        let ack = acks.tracker.register([1; 16]);
+        acks.wait([1; 16], ack, retransmission(1)).await;
+        assert_eq!(rx.try_recv().unwrap().attempt, 1);
+        assert!(!acks.tracker.ack(&[1; 16]));
+
+        let ack = acks.tracker.register([2; 16]);
+        acks.wait([2; 16], ack, retransmission(2)).await;
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -4,18 +4,33 @@ use futures::{stream, StreamExt};
 use mixnet_topology::MixnetTopology;
 use serde::{Deserialize, Serialize};
 
-use crate::{receiver::Receiver, MessageStream, MixnetClientError};
+use crate::{
+    ack::AckTracker, receiver::Receiver, AckConfig, CoverTrafficConfig, HopDelays, MessageStream,
+    MixnetClientError, TopologyProviderConfig,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct MixnetClientConfig {
     pub mode: MixnetClientMode,
     pub topology: MixnetTopology,
+    // Source of topology updates, replacing `topology` at runtime
+    #[serde(default)]
+    pub topology_provider: TopologyProviderConfig,
+    #[serde(default = "MixnetClientConfig::default_topology_refresh_interval")]
+    pub topology_refresh_interval: Duration,
     #[serde(default = "MixnetClientConfig::default_connection_pool_size")]
     pub connection_pool_size: usize,
     #[serde(default = "MixnetClientConfig::default_max_retries")]
     pub max_retries: usize,
     #[serde(default = "MixnetClientConfig::default_retry_delay")]
     pub retry_delay: std::time::Duration,
+    #[serde(default)]
+    pub hop_delays: HopDelays,
+    #[serde(default)]
+    pub cover_traffic: CoverTrafficConfig,
+    // Acknowledgements of the fragments sent, only possible in `SenderReceiver` mode
+    #[serde(default)]
+    pub acknowledgements: Option<AckConfig>,
 }
 
 impl MixnetClientConfig {
@@ -24,12 +39,21 @@ impl MixnetClientConfig {
         Self {
             mode,
             topology,
+            topology_provider: TopologyProviderConfig::default(),
+            topology_refresh_interval: Self::default_topology_refresh_interval(),
             connection_pool_size: Self::default_connection_pool_size(),
             max_retries: Self::default_max_retries(),
             retry_delay: Self::default_retry_delay(),
+            hop_delays: HopDelays::default(),
+            cover_traffic: CoverTrafficConfig::default(),
+            acknowledgements: None,
         }
     }
 
+    const fn default_topology_refresh_interval() -> Duration {
+        Duration::from_secs(60)
+    }
+
     const fn default_connection_pool_size() -> usize {
         256
     }
@@ -50,7 +74,7 @@ pub enum MixnetClientMode {
 }
 
 impl MixnetClientMode {
-    pub(crate) async fn run(&self) -> Result<MessageStream, MixnetClientError> {
+    pub(crate) async fn run(&self, acks: AckTracker) -> Result<MessageStream, MixnetClientError> {
         match self {
             Self::Sender => Ok(stream::empty().boxed()),
             Self::SenderReceiver(node_address) => {
@@ -62,7 +86,7 @@ impl MixnetClientMode {
                     .ok_or(MixnetClientError::MixnetNodeAddressError(
                         "No address provided".into(),
                     ))?;
-                Ok(Receiver::new(socket_addr).run().await?.boxed())
+                Ok(Receiver::new(socket_addr, acks).run().await?.boxed())
             }
         }
     }
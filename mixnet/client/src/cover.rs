@@ -0,0 +1,208 @@
+use std::{collections::VecDeque, time::Duration};
+
+use rand::Rng;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use tokio::sync::mpsc;
+
+use crate::sender::{OutgoingPacket, Sender};
+
+/// Rates of the cover traffic sent along with real messages, in packets per second.
+///
+/// Packets are sent following Poisson processes, a rate of zero disables that kind of traffic.
+/// When enabled, real messages are queued and each of their packets takes the place of the next
+/// scheduled cover packet, so that they cannot be told apart from cover traffic by their timing.
+///
+/// Rates other than zero must be within [`MIN_RATE`] and [`MAX_RATE`], others are rejected when
+/// deserializing and count as zero otherwise.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CoverTrafficConfig {
+    /// Packets going through a full route back to this client, which discards them.
+    /// They are sent to the mix node delivering to this client, only known when
+    /// acknowledgements are enabled: drop packets are sent in their place otherwise.
+    #[serde(default, deserialize_with = "deserialize_rate")]
+    pub loop_rate: f64,
+    /// Packets discarded by the last mix node of their route
+    #[serde(default, deserialize_with = "deserialize_rate")]
+    pub drop_rate: f64,
+}
+
+/// Lowest cover traffic rate, about a packet every eleven days
+pub const MIN_RATE: f64 = 1e-6;
+/// Highest cover traffic rate
+pub const MAX_RATE: f64 = 1e6;
+
+impl CoverTrafficConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.rate() > 0.0
+    }
+
+    fn rate(&self) -> f64 {
+        valid_rate(self.loop_rate) + valid_rate(self.drop_rate)
+    }
+}
+
+fn valid_rate(rate: f64) -> f64 {
+    if (MIN_RATE..=MAX_RATE).contains(&rate) {
+        rate
+    } else {
+        0.0
+    }
+}
+
+fn deserialize_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let rate = f64::deserialize(deserializer)?;
+    if rate == 0.0 || valid_rate(rate) > 0.0 {
+        Ok(rate)
+    } else {
+        Err(D::Error::custom(format!(
+            "invalid cover traffic rate {rate}, expected 0 or between {MIN_RATE} and {MAX_RATE}"
+        )))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CoverKind {
+    Loop,
+    Drop,
+}
+
+/// Real message waiting to be sent in place of cover packets
+pub(crate) struct QueuedMessage {
+    pub(crate) msg: Vec<u8>,
+    pub(crate) total_delay: Duration,
+}
+
+/// Generator of the traffic of a client, sending its real messages in place of cover packets
+pub struct CoverTraffic<R: Rng> {
+    config: CoverTrafficConfig,
+    sender: Sender<R>,
+    queue: mpsc::UnboundedReceiver<QueuedMessage>,
+    // packets of the real message being sent
+    pending: VecDeque<OutgoingPacket>,
+}
+
+impl<R: Rng> CoverTraffic<R> {
+    pub(crate) fn new(
+        config: CoverTrafficConfig,
+        sender: Sender<R>,
+        queue: mpsc::UnboundedReceiver<QueuedMessage>,
+    ) -> Self {
+        Self {
+            config,
+            sender,
+            queue,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Sends packets until the task is dropped, cover ones getting a total delay from
+    /// `total_delay`
+    pub async fn run(mut self, mut total_delay: impl FnMut() -> Duration) {
+        if !self.config.is_enabled() {
+            return;
+        }
+        // Both kinds of traffic together are a single Poisson process of the summed rates,
+        // each packet of which is a loop with probability proportional to the loop rate,
+        // unless a real packet is waiting to be sent
+        let rate = self.config.rate();
+        let mean_interval = Duration::from_secs_f64(1.0 / rate);
+        let mut loop_probability = valid_rate(self.config.loop_rate) / rate;
+        if loop_probability > 0.0 && self.sender.delivery_node().is_none() {
+            tracing::warn!(
+                "loop cover traffic requires acknowledgements, sending drop packets instead"
+            );
+            loop_probability = 0.0;
+        }
+        loop {
+            let interval = exponential_delay(self.sender.rng(), mean_interval);
+            tokio::time::sleep(interval).await;
+            if let Some(packet) = self.next_real_packet() {
+                self.sender.spawn_send(packet);
+                continue;
+            }
+            let kind = if self.sender.rng().gen_bool(loop_probability) {
+                CoverKind::Loop
+            } else {
+                CoverKind::Drop
+            };
+            if let Err(e) = self.sender.send_cover(kind, total_delay()) {
+                tracing::error!("failed to send cover traffic: {e}");
+            }
+        }
+    }
+
+    /// Next packet of the queued real messages, which takes the place of a cover packet
+    fn next_real_packet(&mut self) -> Option<OutgoingPacket> {
+        while self.pending.is_empty() {
+            let QueuedMessage { msg, total_delay } = self.queue.try_recv().ok()?;
+            match self.sender.prepare(msg, total_delay) {
+                Ok(packets) => self.pending.extend(packets),
+                Err(e) => tracing::error!("failed to prepare message: {e}"),
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Samples an exponential distribution of the given mean
+pub(crate) fn exponential_delay<R: Rng>(rng: &mut R, mean: Duration) -> Duration {
+    // inverse transform sampling, `1 - u` is in (0, 1] so the logarithm is finite
+    let u: f64 = rng.gen();
+    mean.mul_f64(-(1.0 - u).ln())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn exponential_delay_mean() {
+        let mut rng = rand::thread_rng();
+        let mean = Duration::from_millis(100);
+        let samples = 10_000;
+        let total: Duration = (0..samples)
+            .map(|_| exponential_delay(&mut rng, mean))
+            .sum();
+        let average = total / samples;
+        assert!(
+            average > Duration::from_millis(90) && average < Duration::from_millis(110),
+            "{average:?}"
+        );
+        assert_eq!(exponential_delay(&mut rng, Duration::ZERO), Duration::ZERO);
+    }
+
+    #[test]
+    fn disabled_cover_traffic() {
+        assert!(!CoverTrafficConfig::default().is_enabled());
+        assert!(!CoverTrafficConfig {
+            loop_rate: -1.0,
+            drop_rate: 0.0
+        }
+        .is_enabled());
+        assert!(CoverTrafficConfig {
+            loop_rate: 0.0,
+            drop_rate: 0.5
+        }
+        .is_enabled());
+        assert!(!CoverTrafficConfig {
+            loop_rate: f64::MIN_POSITIVE,
+            drop_rate: f64::INFINITY
+        }
+        .is_enabled());
+    }
+
+    #[test]
+    fn invalid_rates() {
+        let config: CoverTrafficConfig = serde_yaml::from_str("loop_rate: 0.5").unwrap();
+        assert_eq!(config.loop_rate, 0.5);
+        assert_eq!(config.drop_rate, 0.0);
+        for rate in ["-1.0", "1e-300", ".inf", ".nan", "1e300"] {
+            assert!(
+                serde_yaml::from_str::<CoverTrafficConfig>(&format!("drop_rate: {rate}")).is_err(),
+                "{rate}"
+            );
+        }
+    }
+}
@@ -15,6 +15,12 @@ pub enum MixnetClientError {
     InvalidPayload,
     #[error("invalid fragment")]
     InvalidFragment,
+    #[error("cover traffic stopped, real messages can no longer be sent")]
+    CoverTrafficStopped,
+    #[error("the mix node delivering to this client is unknown, acknowledgements are disabled")]
+    UnknownDeliveryNode,
+    #[error("invalid topology: {0}")]
+    Topology(String),
     #[error("invalid routing address: {0}")]
     InvalidRoutingAddress(#[from] NymNodeRoutingAddressError),
     #[error("{0}")]
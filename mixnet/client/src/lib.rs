@@ -1,22 +1,39 @@
+pub mod ack;
 pub mod config;
+pub mod cover;
 pub mod error;
 pub use error::*;
 mod receiver;
 mod sender;
+pub mod topology;
 
 use std::time::Duration;
 
+pub use ack::{AckConfig, Retransmitter};
+use ack::{AckTracker, Acks, Retransmission};
 pub use config::MixnetClientConfig;
 pub use config::MixnetClientMode;
-use futures::stream::BoxStream;
+use cover::QueuedMessage;
+pub use cover::{CoverTraffic, CoverTrafficConfig};
+use futures::{future::BoxFuture, stream::BoxStream};
 use mixnet_util::ConnectionPool;
 use rand::Rng;
+pub use sender::HopDelays;
 use sender::Sender;
+use tokio::sync::mpsc;
+pub use topology::{SharedTopology, TopologyProvider, TopologyProviderConfig};
 
 // A client for sending packets to Mixnet and receiving packets from Mixnet.
 pub struct MixnetClient<R: Rng> {
     mode: MixnetClientMode,
     sender: Sender<R>,
+    topology: SharedTopology,
+    cover_traffic: CoverTrafficConfig,
+    // real messages waiting to be sent in place of cover packets, if cover traffic is enabled
+    queue: Option<mpsc::UnboundedSender<QueuedMessage>>,
+    queued: Option<mpsc::UnboundedReceiver<QueuedMessage>>,
+    acks: AckTracker,
+    retransmissions: Option<mpsc::UnboundedReceiver<Retransmission>>,
 }
 
 pub type MessageStream = BoxStream<'static, Result<Vec<u8>>>;
@@ -24,23 +41,86 @@ pub type MessageStream = BoxStream<'static, Result<Vec<u8>>>;
 impl<R: Rng> MixnetClient<R> {
     pub fn new(config: MixnetClientConfig, rng: R) -> Self {
         let cache = ConnectionPool::new(config.connection_pool_size);
+        let topology = SharedTopology::new(config.topology, cache.clone());
+        let tracker = AckTracker::default();
+        let (acks, retransmissions) = match config.acknowledgements {
+            Some(config) => {
+                let (tx, rx) = mpsc::unbounded_channel();
+                let acks = Acks {
+                    config,
+                    tracker: tracker.clone(),
+                    retransmissions: tx,
+                };
+                (Some(acks), Some(rx))
+            }
+            None => (None, None),
+        };
+        let (queue, queued) = if config.cover_traffic.is_enabled() {
+            let (tx, rx) = mpsc::unbounded_channel();
+            (Some(tx), Some(rx))
+        } else {
+            (None, None)
+        };
         Self {
             mode: config.mode,
             sender: Sender::new(
-                config.topology,
+                topology.clone(),
                 cache,
                 rng,
                 config.max_retries,
                 config.retry_delay,
+                config.hop_delays,
+                acks,
             ),
+            topology,
+            cover_traffic: config.cover_traffic,
+            queue,
+            queued,
+            acks: tracker,
+            retransmissions,
         }
     }
 
-    pub async fn run(&self) -> Result<MessageStream> {
-        self.mode.run().await
+    /// Task sending again the fragments not acknowledged in time, if acknowledgements are
+    /// enabled in the config. Only the first call returns it.
+    pub fn retransmitter<C: Rng>(&mut self, rng: C) -> Option<Retransmitter<C>> {
+        self.retransmissions
+            .take()
+            .map(|rx| Retransmitter::new(self.sender.with_rng(rng), rx))
     }
 
+    /// Cover traffic generator sending through the same mixnet, if enabled in the config.
+    /// Only the first call returns it.
+    ///
+    /// Real messages are then sent by this task in place of cover packets, so it must be
+    /// running for [`Self::send`] to deliver anything.
+    pub fn cover_traffic<C: Rng>(&mut self, rng: C) -> Option<CoverTraffic<C>> {
+        self.queued
+            .take()
+            .map(|queue| CoverTraffic::new(self.cover_traffic, self.sender.with_rng(rng), queue))
+    }
+
+    /// Handle to update the topology used to send packets
+    pub fn topology(&self) -> SharedTopology {
+        self.topology.clone()
+    }
+
+    /// Connects to the mix node delivering payloads to this client.
+    ///
+    /// The returned future does not borrow the client, so it can keep sending meanwhile.
+    pub fn run(&self) -> BoxFuture<'static, Result<MessageStream>> {
+        let mode = self.mode.clone();
+        let acks = self.acks.clone();
+        Box::pin(async move { mode.run(acks).await })
+    }
+
+    /// Sends a message right away, or queues it for the cover traffic task if enabled
     pub fn send(&mut self, msg: Vec<u8>, total_delay: Duration) -> Result<()> {
-        self.sender.send(msg, total_delay)
+        match &self.queue {
+            Some(queue) => queue
+                .send(QueuedMessage { msg, total_delay })
+                .map_err(|_| MixnetClientError::CoverTrafficStopped),
+            None => self.sender.send(msg, total_delay),
+        }
     }
 }
@@ -1,7 +1,7 @@
 use std::net::SocketAddr;
 
 use futures::{stream, Stream, StreamExt};
-use mixnet_protocol::Body;
+use mixnet_protocol::{final_payload::FinalPayloadContent, Body};
 use nym_sphinx::{
     chunking::{fragment::Fragment, reconstruction::MessageReconstructor},
     message::{NymMessage, PaddedMessage},
@@ -10,16 +10,17 @@ use nym_sphinx::{
 use tokio::net::TcpStream;
 
 use super::error::*;
-use crate::MixnetClientError;
+use crate::{ack::AckTracker, MixnetClientError};
 
 // Receiver accepts TCP connections to receive incoming payloads from the Mixnet.
 pub struct Receiver {
     node_address: SocketAddr,
+    acks: AckTracker,
 }
 
 impl Receiver {
-    pub fn new(node_address: SocketAddr) -> Self {
-        Self { node_address }
+    pub fn new(node_address: SocketAddr, acks: AckTracker) -> Self {
+        Self { node_address, acks }
     }
 
     pub async fn run(&self) -> Result<impl Stream<Item = Result<Vec<u8>>> + Send + 'static> {
@@ -29,25 +30,46 @@ impl Receiver {
 
         Ok(Self::message_stream(Box::pin(Self::fragment_stream(
             socket,
+            self.acks.clone(),
         ))))
     }
 
-    fn fragment_stream(socket: TcpStream) -> impl Stream<Item = Result<Fragment>> + Send + 'static {
-        stream::unfold(socket, move |mut socket| {
+    fn fragment_stream(
+        socket: TcpStream,
+        acks: AckTracker,
+    ) -> impl Stream<Item = Result<Fragment>> + Send + 'static {
+        stream::unfold((socket, acks), move |(mut socket, acks)| {
             async move {
-                let Ok(body) = Body::read(&mut socket).await else {
-                    // TODO: Maybe this is a hard error and the stream is corrupted? In that case stop the stream
-                    return Some((Err(MixnetClientError::MixnetNodeStreamClosed), socket));
-                };
-
-                match body {
-                    Body::SphinxPacket(_) => {
-                        Some((Err(MixnetClientError::UnexpectedStreamBody), socket))
-                    }
-                    Body::FinalPayload(payload) => {
-                        Some((Self::fragment_from_payload(payload), socket))
-                    }
-                    _ => unreachable!(),
+                loop {
+                    let Ok(body) = Body::read(&mut socket).await else {
+                        // TODO: Maybe this is a hard error and the stream is corrupted? In that case stop the stream
+                        return Some((
+                            Err(MixnetClientError::MixnetNodeStreamClosed),
+                            (socket, acks),
+                        ));
+                    };
+
+                    let result = match body {
+                        Body::SphinxPacket(_) => Err(MixnetClientError::UnexpectedStreamBody),
+                        Body::FinalPayload(payload) => {
+                            match Self::content_from_payload(payload) {
+                                Ok(FinalPayloadContent::Ack(id)) => {
+                                    // acknowledgements of other clients of the node are ignored
+                                    if acks.ack(&id) {
+                                        tracing::trace!("Fragment acknowledged");
+                                    }
+                                    continue;
+                                }
+                                Ok(FinalPayloadContent::Fragment { fragment, .. }) => {
+                                    Fragment::try_from_bytes(&fragment)
+                                        .map_err(|_| MixnetClientError::InvalidPayload)
+                                }
+                                Err(e) => Err(e),
+                            }
+                        }
+                        _ => unreachable!(),
+                    };
+                    return Some((result, (socket, acks)));
                 }
             }
         })
@@ -71,14 +93,12 @@ impl Receiver {
         )
     }
 
-    fn fragment_from_payload(payload: Payload) -> Result<Fragment> {
+    fn content_from_payload(payload: Payload) -> Result<FinalPayloadContent> {
         let Ok(payload_plaintext) = payload.recover_plaintext() else {
             return Err(MixnetClientError::InvalidPayload);
         };
-        let Ok(fragment) = Fragment::try_from_bytes(&payload_plaintext) else {
-            return Err(MixnetClientError::InvalidPayload);
-        };
-        Ok(fragment)
+        FinalPayloadContent::from_bytes(&payload_plaintext)
+            .map_err(|_| MixnetClientError::InvalidPayload)
     }
 
     async fn reconstruct_message(
@@ -113,6 +133,11 @@ impl Receiver {
         match reconstruction_result {
             Some((padded_message, _)) => {
                 let message = Self::remove_padding(padded_message)?;
+                // real messages are never empty, those are cover traffic
+                if message.is_empty() {
+                    tracing::trace!("Discarding a cover message");
+                    return Ok(None);
+                }
                 Ok(Some(message))
             }
             None => Ok(None),
@@ -1,6 +1,9 @@
 use std::{net::SocketAddr, time::Duration};
 
-use mixnet_protocol::{Body, ProtocolError};
+use mixnet_protocol::{
+    final_payload::{AckId, FinalPayloadContent},
+    Body, ProtocolError, DROP_PACKET_IDENTIFIER,
+};
 use mixnet_topology::MixnetTopology;
 use mixnet_util::ConnectionPool;
 use nym_sphinx::{
@@ -9,27 +12,59 @@ use nym_sphinx::{
     IDENTIFIER_LENGTH, PAYLOAD_OVERHEAD_SIZE,
 };
 use rand::{distributions::Uniform, prelude::Distribution, Rng};
-use sphinx_packet::{route, SphinxPacket, SphinxPacketBuilder};
+use serde::{Deserialize, Serialize};
+use sphinx_packet::{
+    constants::DESTINATION_ADDRESS_LENGTH,
+    crypto::EphemeralSecret,
+    route,
+    surb::{SURBMaterial, SURB},
+    SphinxPacket, SphinxPacketBuilder,
+};
 
 use super::error::*;
+use crate::{
+    ack::{Acks, Retransmission},
+    cover::{exponential_delay, CoverKind},
+    topology::SharedTopology,
+};
+
+/// How the delay of each hop of a packet is chosen
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum HopDelays {
+    /// The total delay given for each message is split randomly between the hops
+    #[default]
+    Split,
+    /// Every hop delays the packet independently, following an exponential distribution with
+    /// the given mean. The total delay given for each message is ignored.
+    Exponential(Duration),
+}
 
 // Sender splits messages into Sphinx packets and sends them to the Mixnet.
 pub struct Sender<R: Rng> {
-    //TODO: handle topology update
-    topology: MixnetTopology,
+    topology: SharedTopology,
     pool: ConnectionPool,
     max_retries: usize,
     retry_delay: Duration,
+    hop_delays: HopDelays,
+    acks: Option<Acks>,
     rng: R,
 }
 
+pub(crate) struct OutgoingPacket {
+    packet: SphinxPacket,
+    first_node: route::Node,
+    ack: Option<(AckId, Retransmission)>,
+}
+
 impl<R: Rng> Sender<R> {
     pub fn new(
-        topology: MixnetTopology,
+        topology: SharedTopology,
         pool: ConnectionPool,
         rng: R,
         max_retries: usize,
         retry_delay: Duration,
+        hop_delays: HopDelays,
+        acks: Option<Acks>,
     ) -> Self {
         Self {
             topology,
@@ -37,41 +72,254 @@ impl<R: Rng> Sender<R> {
             pool,
             max_retries,
             retry_delay,
+            hop_delays,
+            acks,
         }
     }
 
+    /// Another sender to the same mixnet, drawing its randomness from `rng`
+    pub fn with_rng<C: Rng>(&self, rng: C) -> Sender<C> {
+        Sender::new(
+            self.topology.clone(),
+            self.pool.clone(),
+            rng,
+            self.max_retries,
+            self.retry_delay,
+            self.hop_delays,
+            self.acks.clone(),
+        )
+    }
+
+    pub(crate) fn rng(&mut self) -> &mut R {
+        &mut self.rng
+    }
+
     pub fn send(&mut self, msg: Vec<u8>, total_delay: Duration) -> Result<()> {
-        let destination = self.topology.random_destination(&mut self.rng)?;
-        let destination = Destination::new(
-            DestinationAddressBytes::from_bytes(destination.address.as_bytes()),
-            [0; IDENTIFIER_LENGTH], // TODO: use a proper SURBIdentifier if we need SURB
-        );
+        self.send_message(msg, None, [0; IDENTIFIER_LENGTH], total_delay, true)
+    }
 
-        self.pad_and_split_message(msg)
+    /// Builds the packets of a real message, to be sent later on with `spawn_send`
+    pub(crate) fn prepare(
+        &mut self,
+        msg: Vec<u8>,
+        total_delay: Duration,
+    ) -> Result<Vec<OutgoingPacket>> {
+        self.build_message(msg, None, [0; IDENTIFIER_LENGTH], total_delay, true)
+    }
+
+    /// Mix node delivering payloads to this client, known when acknowledgements are enabled
+    pub(crate) fn delivery_node(&self) -> Option<SocketAddr> {
+        self.acks.as_ref().map(|acks| acks.config.reply_address)
+    }
+
+    /// Sends a single packet of cover traffic, looking the same as a real one to the mixnet.
+    ///
+    /// Loop packets come back to this client through its delivery node, so they require it to
+    /// be known.
+    pub(crate) fn send_cover(&mut self, kind: CoverKind, total_delay: Duration) -> Result<()> {
+        // empty messages still fill a whole packet, and are discarded by receivers.
+        // Cover packets carry reply blocks as real ones do, but are never sent again.
+        match kind {
+            CoverKind::Loop => {
+                let node = self
+                    .delivery_node()
+                    .ok_or(MixnetClientError::UnknownDeliveryNode)?;
+                let address: NodeAddressBytes = NymNodeRoutingAddress::from(node).try_into()?;
+                self.send_message(
+                    Vec::new(),
+                    Some(address.as_bytes()),
+                    [0; IDENTIFIER_LENGTH],
+                    total_delay,
+                    false,
+                )
+            }
+            CoverKind::Drop => {
+                self.send_message(Vec::new(), None, DROP_PACKET_IDENTIFIER, total_delay, false)
+            }
+        }
+    }
+
+    /// Sends again a fragment that was not acknowledged in time.
+    ///
+    /// The destination stays the same, since only its clients can reconstruct the message.
+    pub(crate) fn retransmit(&mut self, retransmission: Retransmission) -> Result<()> {
+        let topology = self.topology.get();
+        let packet = self.build_outgoing_packet(
+            &topology,
+            retransmission.fragment,
+            retransmission.destination,
+            [0; IDENTIFIER_LENGTH],
+            retransmission.total_delay,
+            Some(retransmission.attempt),
+        )?;
+        self.spawn_send(packet);
+        Ok(())
+    }
+
+    fn send_message(
+        &mut self,
+        msg: Vec<u8>,
+        destination: Option<[u8; DESTINATION_ADDRESS_LENGTH]>,
+        identifier: [u8; IDENTIFIER_LENGTH],
+        total_delay: Duration,
+        track: bool,
+    ) -> Result<()> {
+        self.build_message(msg, destination, identifier, total_delay, track)?
             .into_iter()
-            .map(|fragment| self.build_sphinx_packet(fragment, &destination, total_delay))
-            .collect::<Result<Vec<_>>>()?
+            .for_each(|packet| self.spawn_send(packet));
+        Ok(())
+    }
+
+    // `destination` is a random node of the last layer if not given
+    fn build_message(
+        &mut self,
+        msg: Vec<u8>,
+        destination: Option<[u8; DESTINATION_ADDRESS_LENGTH]>,
+        identifier: [u8; IDENTIFIER_LENGTH],
+        total_delay: Duration,
+        track: bool,
+    ) -> Result<Vec<OutgoingPacket>> {
+        // the topology may be updated concurrently, stick to the same one for the whole message
+        let topology = self.topology.get();
+        let destination = match destination {
+            Some(destination) => destination,
+            None => topology
+                .random_destination(&mut self.rng)?
+                .address
+                .as_bytes(),
+        };
+
+        self.pad_and_split_message(msg)
             .into_iter()
-            .for_each(|(packet, first_node)| {
-                let pool = self.pool.clone();
-                let max_retries = self.max_retries;
-                let retry_delay = self.retry_delay;
-                tokio::spawn(async move {
-                    if let Err(e) = Self::send_packet(
-                        &pool,
-                        max_retries,
-                        retry_delay,
-                        Box::new(packet),
-                        first_node.address,
-                    )
-                    .await
-                    {
-                        tracing::error!("failed to send packet to the first node: {e}");
-                    }
-                });
+            .map(|fragment| {
+                self.build_outgoing_packet(
+                    &topology,
+                    fragment.into_bytes(),
+                    destination,
+                    identifier,
+                    total_delay,
+                    track.then_some(0),
+                )
+            })
+            .collect()
+    }
+
+    pub(crate) fn spawn_send(&self, packet: OutgoingPacket) {
+        let pool = self.pool.clone();
+        let max_retries = self.max_retries;
+        let retry_delay = self.retry_delay;
+        // register before sending, so that the acknowledgement cannot come first
+        let ack = packet
+            .ack
+            .zip(self.acks.clone())
+            .map(|((id, retransmission), acks)| {
+                (acks.tracker.register(id), id, retransmission, acks)
             });
+        tokio::spawn(async move {
+            if let Err(e) = Self::send_packet(
+                &pool,
+                max_retries,
+                retry_delay,
+                Box::new(packet.packet),
+                packet.first_node.address,
+            )
+            .await
+            {
+                tracing::error!("failed to send packet to the first node: {e}");
+            }
+            if let Some((rx, id, retransmission, acks)) = ack {
+                acks.wait(id, rx, retransmission).await;
+            }
+        });
+    }
 
-        Ok(())
+    // `attempt` is the number of times the fragment was already sent again,
+    // or `None` if it should not be sent again when not acknowledged
+    fn build_outgoing_packet(
+        &mut self,
+        topology: &MixnetTopology,
+        fragment: Vec<u8>,
+        destination: [u8; DESTINATION_ADDRESS_LENGTH],
+        identifier: [u8; IDENTIFIER_LENGTH],
+        total_delay: Duration,
+        attempt: Option<usize>,
+    ) -> Result<OutgoingPacket> {
+        let reply_address = self.acks.as_ref().map(|acks| acks.config.reply_address);
+        let surb = match reply_address {
+            Some(reply_address) => {
+                let id: AckId = self.rng.gen();
+                Some((
+                    id,
+                    self.build_surb(topology, reply_address, id, total_delay)?,
+                ))
+            }
+            None => None,
+        };
+        let ack_id = surb.as_ref().map(|(id, _)| *id);
+
+        let payload = FinalPayloadContent::Fragment {
+            ack: surb,
+            fragment: fragment.clone(),
+        }
+        .to_bytes();
+        let (packet, first_node) = self.build_sphinx_packet(
+            topology,
+            payload,
+            &Destination::new(DestinationAddressBytes::from_bytes(destination), identifier),
+            total_delay,
+        )?;
+
+        let ack = ack_id.zip(attempt).map(|(id, attempt)| {
+            (
+                id,
+                Retransmission {
+                    fragment,
+                    destination,
+                    total_delay,
+                    attempt: attempt + 1,
+                },
+            )
+        });
+        Ok(OutgoingPacket {
+            packet,
+            first_node,
+            ack,
+        })
+    }
+
+    // Reply block through a random route, used by the destination to acknowledge a fragment
+    fn build_surb(
+        &mut self,
+        topology: &MixnetTopology,
+        reply_address: SocketAddr,
+        id: AckId,
+        total_delay: Duration,
+    ) -> Result<SURB> {
+        let route = topology.random_route(&mut self.rng)?;
+        let delays = self.delays(route.len(), total_delay);
+        let address: NodeAddressBytes = NymNodeRoutingAddress::from(reply_address).try_into()?;
+        let destination =
+            Destination::new(DestinationAddressBytes::from_bytes(address.as_bytes()), id);
+        Ok(SURB::new(
+            EphemeralSecret::new(),
+            SURBMaterial::new(route, delays, destination),
+        )
+        .map_err(ProtocolError::InvalidSurb)?)
+    }
+
+    fn delays(&mut self, hops: usize, total_delay: Duration) -> Vec<Delay> {
+        let delays: Vec<Duration> = match self.hop_delays {
+            HopDelays::Split => {
+                RandomDelayIterator::new(&mut self.rng, hops as u64, total_delay).collect()
+            }
+            HopDelays::Exponential(mean) => (0..hops)
+                .map(|_| exponential_delay(&mut self.rng, mean))
+                .collect(),
+        };
+        delays
+            .into_iter()
+            .map(|d| Delay::new_from_millis(d.as_millis() as u64))
+            .collect()
     }
 
     fn pad_and_split_message(&mut self, msg: Vec<u8>) -> Vec<Fragment> {
@@ -90,21 +338,16 @@ impl<R: Rng> Sender<R> {
 
     fn build_sphinx_packet(
         &mut self,
-        fragment: Fragment,
+        topology: &MixnetTopology,
+        payload: Vec<u8>,
         destination: &Destination,
         total_delay: Duration,
     ) -> Result<(sphinx_packet::SphinxPacket, route::Node)> {
-        let route = self.topology.random_route(&mut self.rng)?;
-
-        let delays: Vec<Delay> =
-            RandomDelayIterator::new(&mut self.rng, route.len() as u64, total_delay)
-                .map(|d| Delay::new_from_millis(d.as_millis() as u64))
-                .collect();
+        let route = topology.random_route(&mut self.rng)?;
+        let delays = self.delays(route.len(), total_delay);
 
         // TODO: encrypt the payload for the destination, if we want
         // https://github.com/nymtech/nym/blob/3748ab77a132143d5fd1cd75dd06334d33294815/common/nymsphinx/src/preparer/payload.rs#L70
-        let payload = fragment.into_bytes();
-
         let packet = SphinxPacketBuilder::new()
             .with_payload_size(payload.len() + PAYLOAD_OVERHEAD_SIZE)
             .build_packet(payload, &route, destination, &delays)
@@ -0,0 +1,230 @@
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use mixnet_topology::MixnetTopology;
+use mixnet_util::ConnectionPool;
+use serde::{Deserialize, Serialize};
+
+use crate::{MixnetClientError, Result};
+
+/// A source of the mixnet topology, polled to follow changes of the mixnet membership
+#[async_trait::async_trait]
+pub trait TopologyProvider: Send {
+    /// Returns the latest topology, or `None` if it is known not to have changed since the
+    /// previous call
+    async fn fetch(&mut self) -> Result<Option<MixnetTopology>>;
+}
+
+/// Where to get topology updates from
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub enum TopologyProviderConfig {
+    /// The topology of the config never changes
+    #[default]
+    Static,
+    /// A YAML file, reloaded whenever it is modified
+    File(PathBuf),
+    /// An HTTP endpoint serving the topology as JSON
+    Http(String),
+}
+
+impl TopologyProviderConfig {
+    /// Returns the configured provider, or `None` if the topology is static
+    pub fn provider(&self) -> Option<Box<dyn TopologyProvider>> {
+        match self {
+            Self::Static => None,
+            Self::File(path) => Some(Box::new(FileTopologyProvider::new(path.clone()))),
+            Self::Http(url) => Some(Box::new(HttpTopologyProvider::new(url.clone()))),
+        }
+    }
+}
+
+pub struct FileTopologyProvider {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl FileTopologyProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            modified: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TopologyProvider for FileTopologyProvider {
+    async fn fetch(&mut self) -> Result<Option<MixnetTopology>> {
+        let modified = tokio::fs::metadata(&self.path)
+            .await
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| MixnetClientError::Topology(e.to_string()))?;
+        if self.modified == Some(modified) {
+            return Ok(None);
+        }
+        let content = tokio::fs::read(&self.path)
+            .await
+            .map_err(|e| MixnetClientError::Topology(e.to_string()))?;
+        let topology = serde_yaml::from_slice(&content)
+            .map_err(|e| MixnetClientError::Topology(e.to_string()))?;
+        self.modified = Some(modified);
+        Ok(Some(topology))
+    }
+}
+
+pub struct HttpTopologyProvider {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpTopologyProvider {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TopologyProvider for HttpTopologyProvider {
+    async fn fetch(&mut self) -> Result<Option<MixnetTopology>> {
+        let topology = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| MixnetClientError::Topology(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| MixnetClientError::Topology(e.to_string()))?;
+        Ok(Some(topology))
+    }
+}
+
+/// The current topology, shared between the sender and whatever keeps it up to date
+#[derive(Clone)]
+pub struct SharedTopology {
+    current: Arc<RwLock<Arc<MixnetTopology>>>,
+    pool: ConnectionPool,
+}
+
+impl SharedTopology {
+    pub fn new(topology: MixnetTopology, pool: ConnectionPool) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(topology))),
+            pool,
+        }
+    }
+
+    /// Snapshot of the current topology, routes are always built from a single snapshot
+    pub fn get(&self) -> Arc<MixnetTopology> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Replaces the topology and closes the connections to the nodes that were removed.
+    ///
+    /// Returns the addresses of the removed nodes.
+    pub async fn update(&self, topology: MixnetTopology) -> Result<Vec<SocketAddr>> {
+        validate(&topology)?;
+        let topology = Arc::new(topology);
+        let previous = std::mem::replace(&mut *self.current.write().unwrap(), topology.clone());
+        let current = addresses(&topology);
+        let removed: Vec<_> = addresses(&previous)
+            .into_iter()
+            .filter(|addr| !current.contains(addr))
+            .collect();
+        for addr in &removed {
+            self.pool.remove(addr).await;
+        }
+        Ok(removed)
+    }
+
+    /// Polls `provider` every `interval`, applying every new topology
+    pub async fn follow(&self, mut provider: Box<dyn TopologyProvider>, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            let topology = match provider.fetch().await {
+                Ok(Some(topology)) => topology,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::error!("failed to fetch the mixnet topology: {e}");
+                    continue;
+                }
+            };
+            if *self.get() == topology {
+                continue;
+            }
+            match self.update(topology).await {
+                Ok(removed) => {
+                    tracing::info!("mixnet topology updated, removed nodes: {removed:?}")
+                }
+                Err(e) => tracing::error!("ignoring mixnet topology update: {e}"),
+            }
+        }
+    }
+}
+
+// Routes go through one node of each layer, so none of them can be empty
+fn validate(topology: &MixnetTopology) -> Result<()> {
+    if topology.layers.is_empty() || topology.layers.iter().any(|l| l.nodes.is_empty()) {
+        return Err(MixnetClientError::Topology(
+            "every layer of the topology should have at least one node".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn addresses(topology: &MixnetTopology) -> HashSet<SocketAddr> {
+    topology
+        .layers
+        .iter()
+        .flat_map(|layer| layer.nodes.iter().map(|node| node.address))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use mixnet_topology::{Layer, Node};
+
+    use super::*;
+
+    fn topology(ports: &[&[u16]]) -> MixnetTopology {
+        MixnetTopology {
+            layers: ports
+                .iter()
+                .map(|layer| Layer {
+                    nodes: layer
+                        .iter()
+                        .map(|port| Node {
+                            address: SocketAddr::from(([127, 0, 0, 1], *port)),
+                            public_key: [0; 32],
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn update_topology() {
+        let shared =
+            SharedTopology::new(topology(&[&[7777, 7778], &[7779]]), ConnectionPool::new(8));
+        let removed = shared
+            .update(topology(&[&[7777], &[7779, 7780]]))
+            .await
+            .unwrap();
+        assert_eq!(removed, vec![SocketAddr::from(([127, 0, 0, 1], 7778))]);
+        assert_eq!(*shared.get(), topology(&[&[7777], &[7779, 7780]]));
+
+        assert!(shared.update(topology(&[&[7777], &[]])).await.is_err());
+        assert_eq!(*shared.get(), topology(&[&[7777], &[7779, 7780]]));
+    }
+}
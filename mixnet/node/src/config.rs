@@ -24,6 +24,21 @@ pub struct MixnetNodeConfig {
     /// The retry delay between retries.
     #[serde(default = "MixnetNodeConfig::default_retry_delay")]
     pub retry_delay: Duration,
+    /// The maximum number of inbound connections, others are refused.
+    #[serde(default = "MixnetNodeConfig::default_max_connections")]
+    pub max_connections: usize,
+    /// The number of packets per second each peer can send, beyond which its packets are read
+    /// more slowly. Zero disables the limit.
+    #[serde(default = "MixnetNodeConfig::default_peer_packet_rate")]
+    pub peer_packet_rate: u32,
+    /// The maximum number of packets being delayed or waiting to be forwarded.
+    /// Reading from peers pauses while it is reached.
+    #[serde(default = "MixnetNodeConfig::default_max_pending_packets")]
+    pub max_pending_packets: usize,
+    /// The longest a packet can ask to be delayed for, as it is pending all along.
+    /// Packets asking for more are dropped.
+    #[serde(default = "MixnetNodeConfig::default_max_delay")]
+    pub max_delay: Duration,
 }
 
 impl Default for MixnetNodeConfig {
@@ -38,6 +53,10 @@ impl Default for MixnetNodeConfig {
             connection_pool_size: 255,
             max_retries: 3,
             retry_delay: Duration::from_secs(5),
+            max_connections: 1024,
+            peer_packet_rate: 1000,
+            max_pending_packets: 10_000,
+            max_delay: Duration::from_secs(10),
         }
     }
 }
@@ -55,6 +74,22 @@ impl MixnetNodeConfig {
         Duration::from_secs(5)
     }
 
+    const fn default_max_connections() -> usize {
+        1024
+    }
+
+    const fn default_peer_packet_rate() -> u32 {
+        1000
+    }
+
+    const fn default_max_pending_packets() -> usize {
+        10_000
+    }
+
+    const fn default_max_delay() -> Duration {
+        Duration::from_secs(10)
+    }
+
     pub fn public_key(&self) -> [u8; PUBLIC_KEY_SIZE] {
         *PublicKey::from(&PrivateKey::from(self.private_key)).as_bytes()
     }
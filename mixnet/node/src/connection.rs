@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::metrics::Metrics;
+
+/// Bounds the number of inbound connections and the rate at which each peer sends packets.
+///
+/// Rates are enforced per IP address, whatever the number of connections a peer opens.
+#[derive(Clone)]
+pub(crate) struct ConnectionManager {
+    slots: Arc<Semaphore>,
+    peers: Arc<Mutex<HashMap<IpAddr, Peer>>>,
+    packet_rate: u32,
+    metrics: Arc<Metrics>,
+}
+
+struct Peer {
+    connections: usize,
+    limiter: RateLimiter,
+}
+
+impl ConnectionManager {
+    pub(crate) fn new(max_connections: usize, packet_rate: u32, metrics: Arc<Metrics>) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_connections)),
+            peers: Default::default(),
+            packet_rate,
+            metrics,
+        }
+    }
+
+    /// Returns `None` if the maximum number of connections is reached
+    pub(crate) fn accept(&self, peer: IpAddr) -> Option<Connection> {
+        let Ok(permit) = self.slots.clone().try_acquire_owned() else {
+            self.metrics.connection_rejected();
+            return None;
+        };
+        self.peers
+            .lock()
+            .unwrap()
+            .entry(peer)
+            .or_insert_with(|| Peer {
+                connections: 0,
+                limiter: RateLimiter::new(self.packet_rate, Instant::now()),
+            })
+            .connections += 1;
+        self.metrics.connection_opened();
+        Some(Connection {
+            peer,
+            manager: self.clone(),
+            _permit: permit,
+        })
+    }
+}
+
+/// An accepted connection, releasing its slot when dropped
+pub(crate) struct Connection {
+    peer: IpAddr,
+    manager: ConnectionManager,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Connection {
+    /// How long to wait before reading the next packet, to keep the peer within its rate
+    pub(crate) fn throttle(&self) -> Duration {
+        self.manager
+            .peers
+            .lock()
+            .unwrap()
+            .get_mut(&self.peer)
+            .map(|peer| peer.limiter.reserve(Instant::now()))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let mut peers = self.manager.peers.lock().unwrap();
+        if let Some(peer) = peers.get_mut(&self.peer) {
+            peer.connections -= 1;
+            if peer.connections == 0 {
+                peers.remove(&self.peer);
+            }
+        }
+        self.manager.metrics.connection_closed();
+    }
+}
+
+/// Spaces out packets at a steady rate, allowing bursts of up to a second worth of packets.
+///
+/// A rate of zero disables the limit.
+struct RateLimiter {
+    interval: Duration,
+    // time at which the next packet would be on schedule
+    next: Instant,
+}
+
+impl RateLimiter {
+    const BURST: Duration = Duration::from_secs(1);
+
+    fn new(rate: u32, now: Instant) -> Self {
+        let interval = if rate == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / rate
+        };
+        Self {
+            interval,
+            next: now,
+        }
+    }
+
+    /// Reserves the slot of a packet, returning how long to wait until it is reached
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let next = self.next.max(now);
+        self.next = next + self.interval;
+        next.saturating_duration_since(now)
+            .saturating_sub(Self::BURST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_after_burst() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(10, now);
+        for _ in 0..=10 {
+            assert_eq!(limiter.reserve(now), Duration::ZERO);
+        }
+        assert_eq!(limiter.reserve(now), Duration::from_millis(100));
+        assert_eq!(limiter.reserve(now), Duration::from_millis(200));
+        // the peer waited, so it is on schedule again
+        assert_eq!(
+            limiter.reserve(now + Duration::from_millis(300)),
+            Duration::ZERO
+        );
+
+        let mut unlimited = RateLimiter::new(0, now);
+        for _ in 0..1000 {
+            assert_eq!(unlimited.reserve(now), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn bound_connections() {
+        let metrics = Arc::new(Metrics::default());
+        let manager = ConnectionManager::new(1, 10, metrics.clone());
+        let peer = IpAddr::from([127, 0, 0, 1]);
+        let connection = manager.accept(peer).unwrap();
+        assert!(manager.accept(peer).is_none());
+        assert_eq!(metrics.snapshot().connections, 1);
+        assert_eq!(metrics.snapshot().connections_rejected, 1);
+
+        drop(connection);
+        assert!(manager.peers.lock().unwrap().is_empty());
+        assert!(manager.accept(peer).is_some());
+    }
+}
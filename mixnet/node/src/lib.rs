@@ -1,21 +1,27 @@
 mod client_notifier;
 pub mod config;
+mod connection;
+pub mod metrics;
 
-use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
 
 use client_notifier::ClientNotifier;
 pub use config::MixnetNodeConfig;
-use mixnet_protocol::{Body, ProtocolError};
+use connection::{Connection, ConnectionManager};
+pub use metrics::{Metrics, MetricsSnapshot};
+use mixnet_protocol::{
+    final_payload::FinalPayloadContent, Body, ProtocolError, DROP_PACKET_IDENTIFIER,
+};
 use mixnet_topology::MixnetNodeId;
 use nym_sphinx::{
     addressing::nodes::{NymNodeRoutingAddress, NymNodeRoutingAddressError},
     Delay, DestinationAddressBytes, NodeAddressBytes, PrivateKey,
 };
 pub use sphinx_packet::crypto::PRIVATE_KEY_SIZE;
-use sphinx_packet::{crypto::PUBLIC_KEY_SIZE, ProcessedPacket, SphinxPacket};
+use sphinx_packet::{crypto::PUBLIC_KEY_SIZE, payload::Payload, ProcessedPacket, SphinxPacket};
 use tokio::{
     net::{TcpListener, TcpStream},
-    sync::mpsc,
+    sync::{mpsc, Semaphore},
 };
 
 pub type Result<T> = core::result::Result<T, MixnetNodeError>;
@@ -32,16 +38,27 @@ pub enum MixnetNodeError {
     ClientSendError(#[from] tokio::sync::mpsc::error::TrySendError<Body>),
     #[error("client: {0}")]
     Client(ProtocolError),
+    #[error("packet delay of {0:?} exceeds the maximum")]
+    DelayTooLong(Duration),
 }
 
 // A mix node that routes packets in the Mixnet.
 pub struct MixnetNode {
     config: MixnetNodeConfig,
+    metrics: Arc<Metrics>,
 }
 
 impl MixnetNode {
     pub fn new(config: MixnetNodeConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            metrics: Default::default(),
+        }
+    }
+
+    /// Counters of the packets handled by the node, updated while it runs
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
     }
 
     pub fn id(&self) -> MixnetNodeId {
@@ -66,7 +83,6 @@ impl MixnetNode {
             }
         });
 
-        //TODO: Accepting ad-hoc TCP conns for now. Improve conn handling.
         let listener = TcpListener::bind(self.config.listen_address)
             .await
             .map_err(ProtocolError::IO)?;
@@ -75,9 +91,10 @@ impl MixnetNode {
             self.config.listen_address
         );
 
-        let (tx, rx) = mpsc::unbounded_channel();
+        let (tx, rx) = mpsc::channel(self.config.max_pending_packets);
 
-        let packet_forwarder = PacketForwarder::new(tx.clone(), rx, self.config);
+        let packet_forwarder =
+            PacketForwarder::new(tx.clone(), rx, self.config, self.metrics.clone());
 
         tokio::spawn(async move {
             packet_forwarder.run().await;
@@ -87,18 +104,29 @@ impl MixnetNode {
             config: self.config,
             client_tx,
             packet_tx: tx,
+            pending_packets: Arc::new(Semaphore::new(self.config.max_pending_packets)),
+            metrics: self.metrics.clone(),
         };
+        let connections = ConnectionManager::new(
+            self.config.max_connections,
+            self.config.peer_packet_rate,
+            self.metrics.clone(),
+        );
 
         loop {
             tokio::select! {
                 res = listener.accept() => {
                     match res {
                         Ok((socket, remote_addr)) => {
+                            let Some(connection) = connections.accept(remote_addr.ip()) else {
+                                tracing::warn!("Refusing connection from {remote_addr:?}: too many connections");
+                                continue;
+                            };
                             tracing::debug!("Accepted incoming connection from {remote_addr:?}");
 
                             let runner = runner.clone();
                             tokio::spawn(async move {
-                                if let Err(e) = runner.handle_connection(socket).await {
+                                if let Err(e) = runner.handle_connection(socket, connection).await {
                                     tracing::error!("failed to handle conn: {e}");
                                 }
                             });
@@ -119,18 +147,43 @@ impl MixnetNode {
 struct MixnetNodeRunner {
     config: MixnetNodeConfig,
     client_tx: mpsc::Sender<Body>,
-    packet_tx: mpsc::UnboundedSender<Packet>,
+    packet_tx: mpsc::Sender<Packet>,
+    // bounds the packets being handled, each one holding a permit until it is forwarded
+    pending_packets: Arc<Semaphore>,
+    metrics: Arc<Metrics>,
 }
 
 impl MixnetNodeRunner {
-    async fn handle_connection(&self, mut socket: TcpStream) -> Result<()> {
+    async fn handle_connection(&self, mut socket: TcpStream, connection: Connection) -> Result<()> {
         loop {
-            let body = Body::read(&mut socket).await?;
+            let throttle = connection.throttle();
+            if !throttle.is_zero() {
+                tokio::time::sleep(throttle).await;
+            }
+            let body = match Body::read(&mut socket).await {
+                Ok(body) => body,
+                Err(e) => {
+                    if matches!(e, ProtocolError::BodyTooLarge(_)) {
+                        self.metrics.packet_dropped();
+                    }
+                    return Err(e.into());
+                }
+            };
+            self.metrics.packet_received();
+            // stop reading from the peer until there is room for its packet
+            let permit = self
+                .pending_packets
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("the semaphore is never closed");
             let this = self.clone();
             tokio::spawn(async move {
                 if let Err(e) = this.handle_body(body).await {
+                    this.metrics.packet_dropped();
                     tracing::error!("failed to handle body: {e}");
                 }
+                drop(permit);
             });
         }
     }
@@ -139,6 +192,9 @@ impl MixnetNodeRunner {
         match pkt {
             Body::SphinxPacket(packet) => self.handle_sphinx_packet(packet).await,
             Body::FinalPayload(payload) => {
+                if let Err(e) = self.acknowledge(&payload).await {
+                    tracing::error!("failed to acknowledge the payload: {e}");
+                }
                 self.forward_body_to_client_notifier(Body::FinalPayload(payload))
                     .await
             }
@@ -155,6 +211,11 @@ impl MixnetNodeRunner {
                 self.forward_packet_to_next_hop(Body::SphinxPacket(packet), next_node_addr, delay)
                     .await
             }
+            ProcessedPacket::FinalHop(_, identifier, _) if identifier == DROP_PACKET_IDENTIFIER => {
+                tracing::trace!("Dropping a cover packet");
+                self.metrics.packet_dropped();
+                Ok(())
+            }
             ProcessedPacket::FinalHop(destination_addr, _, payload) => {
                 self.forward_payload_to_destination(Body::FinalPayload(payload), destination_addr)
                     .await
@@ -162,6 +223,33 @@ impl MixnetNodeRunner {
         }
     }
 
+    // Sends an acknowledgement back through the reply block attached to the payload, if any.
+    // The acknowledgement has the same size as the payload, so the two are indistinguishable.
+    async fn acknowledge(&self, payload: &Payload) -> Result<()> {
+        let plaintext = Payload::from_bytes(payload.as_bytes())
+            .and_then(Payload::recover_plaintext)
+            .map_err(ProtocolError::InvalidPayload)?;
+        let FinalPayloadContent::Fragment {
+            ack: Some((id, surb)),
+            ..
+        } = FinalPayloadContent::from_bytes(&plaintext)?
+        else {
+            return Ok(());
+        };
+        let (packet, first_hop) = surb
+            .use_surb(
+                &FinalPayloadContent::Ack(id).to_bytes(),
+                payload.as_bytes().len(),
+            )
+            .map_err(ProtocolError::InvalidSurb)?;
+        tracing::debug!("Acknowledging a fragment");
+        self.forward(
+            Body::SphinxPacket(Box::new(packet)),
+            NymNodeRoutingAddress::try_from(first_hop)?,
+        )
+        .await
+    }
+
     async fn forward_body_to_client_notifier(&self, body: Body) -> Result<()> {
         // TODO: Decrypt the final payload using the private key, if it's encrypted
 
@@ -176,8 +264,17 @@ impl MixnetNodeRunner {
         next_node_addr: NodeAddressBytes,
         delay: Delay,
     ) -> Result<()> {
-        tracing::debug!("Delaying the packet for {delay:?}");
-        tokio::time::sleep(delay.to_duration()).await;
+        let delay = delay.to_duration();
+        // the delay is chosen by the sender, who could otherwise hold the pending packets
+        // permits for as long as it likes
+        if delay > self.config.max_delay {
+            return Err(MixnetNodeError::DelayTooLong(delay));
+        }
+        if !delay.is_zero() {
+            tracing::debug!("Delaying the packet for {delay:?}");
+            self.metrics.packet_delayed();
+            tokio::time::sleep(delay).await;
+        }
 
         self.forward(packet, NymNodeRoutingAddress::try_from(next_node_addr)?)
             .await
@@ -200,29 +297,32 @@ impl MixnetNodeRunner {
     async fn forward(&self, pkt: Body, to: NymNodeRoutingAddress) -> Result<()> {
         let addr = SocketAddr::from(to);
 
-        self.packet_tx.send(Packet::new(addr, pkt))?;
+        self.packet_tx.send(Packet::new(addr, pkt)).await?;
         Ok(())
     }
 }
 
 struct PacketForwarder {
     config: MixnetNodeConfig,
-    packet_rx: mpsc::UnboundedReceiver<Packet>,
-    packet_tx: mpsc::UnboundedSender<Packet>,
+    packet_rx: mpsc::Receiver<Packet>,
+    packet_tx: mpsc::Sender<Packet>,
     connections: HashMap<SocketAddr, TcpStream>,
+    metrics: Arc<Metrics>,
 }
 
 impl PacketForwarder {
     pub fn new(
-        packet_tx: mpsc::UnboundedSender<Packet>,
-        packet_rx: mpsc::UnboundedReceiver<Packet>,
+        packet_tx: mpsc::Sender<Packet>,
+        packet_rx: mpsc::Receiver<Packet>,
         config: MixnetNodeConfig,
+        metrics: Arc<Metrics>,
     ) -> Self {
         Self {
             packet_tx,
             packet_rx,
             connections: HashMap::with_capacity(config.connection_pool_size),
             config,
+            metrics,
         }
     }
 
@@ -262,15 +362,15 @@ impl PacketForwarder {
     }
 
     async fn send(&mut self, pkt: Packet) {
-        if let Err(err) = self.try_send(pkt.target, &pkt.body).await {
-            match err {
-                MixnetNodeError::Protocol(ProtocolError::IO(e))
-                    if e.kind() == std::io::ErrorKind::Unsupported =>
-                {
-                    tracing::error!("fail to send message to {}: {e}", pkt.target);
-                }
-                _ => self.handle_retry(pkt),
+        match self.try_send(pkt.target, &pkt.body).await {
+            Ok(()) => self.metrics.packet_forwarded(),
+            Err(MixnetNodeError::Protocol(ProtocolError::IO(e)))
+                if e.kind() == std::io::ErrorKind::Unsupported =>
+            {
+                self.metrics.packet_dropped();
+                tracing::error!("fail to send message to {}: {e}", pkt.target);
             }
+            Err(_) => self.handle_retry(pkt),
         }
     }
 
@@ -283,11 +383,12 @@ impl PacketForwarder {
             tokio::spawn(async move {
                 tokio::time::sleep(delay).await;
                 pkt.retry_count += 1;
-                if let Err(e) = tx.send(pkt) {
+                if let Err(e) = tx.send(pkt).await {
                     tracing::error!("fail to enqueue retry message: {e}");
                 }
             });
         } else {
+            self.metrics.packet_dropped();
             tracing::error!(
                 "fail to send message to {}: reach maximum retries",
                 pkt.target
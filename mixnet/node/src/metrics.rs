@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+
+/// Counters of the packets handled by a mix node, shared by all its tasks
+#[derive(Debug, Default)]
+pub struct Metrics {
+    packets_received: AtomicU64,
+    packets_forwarded: AtomicU64,
+    packets_delayed: AtomicU64,
+    packets_dropped: AtomicU64,
+    connections: AtomicU64,
+    connections_rejected: AtomicU64,
+}
+
+/// Values of the [`Metrics`] at some point in time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct MetricsSnapshot {
+    /// Packets read from other nodes and clients
+    pub packets_received: u64,
+    /// Packets written to the next hop or to the destination
+    pub packets_forwarded: u64,
+    /// Packets held for the delay chosen by their sender
+    pub packets_delayed: u64,
+    /// Packets discarded, either cover traffic or packets that could not be handled
+    pub packets_dropped: u64,
+    /// Currently open inbound connections
+    pub connections: u64,
+    /// Inbound connections refused since the maximum number was reached
+    pub connections_rejected: u64,
+}
+
+impl Metrics {
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            packets_received: self.packets_received.load(Ordering::Relaxed),
+            packets_forwarded: self.packets_forwarded.load(Ordering::Relaxed),
+            packets_delayed: self.packets_delayed.load(Ordering::Relaxed),
+            packets_dropped: self.packets_dropped.load(Ordering::Relaxed),
+            connections: self.connections.load(Ordering::Relaxed),
+            connections_rejected: self.connections_rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn packet_received(&self) {
+        self.packets_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn packet_forwarded(&self) {
+        self.packets_forwarded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn packet_delayed(&self) {
+        self.packets_delayed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn packet_dropped(&self) {
+        self.packets_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn connection_opened(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn connection_closed(&self) {
+        self.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn connection_rejected(&self) {
+        self.connections_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+}
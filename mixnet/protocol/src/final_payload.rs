@@ -0,0 +1,124 @@
+use sphinx_packet::{constants::IDENTIFIER_LENGTH, surb::SURB};
+
+use crate::{ProtocolError, Result};
+
+/// Identifier of a fragment to acknowledge, chosen randomly by its sender
+pub type AckId = [u8; IDENTIFIER_LENGTH];
+
+const FRAGMENT: u8 = 0;
+const FRAGMENT_WITH_ACK: u8 = 1;
+const ACK: u8 = 2;
+
+/// Plaintext of the payload delivered to the destination of a packet.
+///
+/// A fragment may come with a single-use reply block, used by the destination node to
+/// acknowledge it without learning who sent it.
+pub enum FinalPayloadContent {
+    Fragment {
+        ack: Option<(AckId, SURB)>,
+        fragment: Vec<u8>,
+    },
+    /// Acknowledgement of the fragment sent along with the reply block
+    Ack(AckId),
+}
+
+impl FinalPayloadContent {
+    /// Encoded as a tag byte, followed by:
+    /// - a fragment: the fragment
+    /// - a fragment with a reply block: the ack id, the length of the reply block (`u16`, big
+    ///   endian), the reply block and the fragment
+    /// - an acknowledgement: the ack id
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Fragment {
+                ack: None,
+                fragment,
+            } => [&[FRAGMENT][..], &fragment[..]].concat(),
+            Self::Fragment {
+                ack: Some((id, surb)),
+                fragment,
+            } => {
+                let surb = surb.to_bytes();
+                let len = u16::try_from(surb.len()).expect("reply blocks are a few KiB at most");
+                [
+                    &[FRAGMENT_WITH_ACK][..],
+                    &id[..],
+                    &len.to_be_bytes()[..],
+                    &surb[..],
+                    &fragment[..],
+                ]
+                .concat()
+            }
+            Self::Ack(id) => [&[ACK][..], &id[..]].concat(),
+        }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let (tag, data) = data
+            .split_first()
+            .ok_or(ProtocolError::InvalidFinalPayload)?;
+        match *tag {
+            FRAGMENT => Ok(Self::Fragment {
+                ack: None,
+                fragment: data.to_vec(),
+            }),
+            FRAGMENT_WITH_ACK => {
+                let (id, data) = split_ack_id(data)?;
+                if data.len() < 2 {
+                    return Err(ProtocolError::InvalidFinalPayload);
+                }
+                let (len, data) = data.split_at(2);
+                let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+                if data.len() < len {
+                    return Err(ProtocolError::InvalidFinalPayload);
+                }
+                let (surb, fragment) = data.split_at(len);
+                let surb = SURB::from_bytes(surb).map_err(ProtocolError::InvalidSurb)?;
+                Ok(Self::Fragment {
+                    ack: Some((id, surb)),
+                    fragment: fragment.to_vec(),
+                })
+            }
+            ACK => match split_ack_id(data)? {
+                (id, []) => Ok(Self::Ack(id)),
+                _ => Err(ProtocolError::InvalidFinalPayload),
+            },
+            _ => Err(ProtocolError::InvalidFinalPayload),
+        }
+    }
+}
+
+fn split_ack_id(data: &[u8]) -> Result<(AckId, &[u8])> {
+    if data.len() < IDENTIFIER_LENGTH {
+        return Err(ProtocolError::InvalidFinalPayload);
+    }
+    let (id, data) = data.split_at(IDENTIFIER_LENGTH);
+    Ok((id.try_into().expect("length checked above"), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn final_payload_roundtrip() {
+        let fragment = FinalPayloadContent::Fragment {
+            ack: None,
+            fragment: vec![1, 2, 3],
+        };
+        assert!(matches!(
+            FinalPayloadContent::from_bytes(&fragment.to_bytes()).unwrap(),
+            FinalPayloadContent::Fragment { ack: None, fragment } if fragment == [1, 2, 3]
+        ));
+
+        let ack = FinalPayloadContent::Ack([7; IDENTIFIER_LENGTH]);
+        assert!(matches!(
+            FinalPayloadContent::from_bytes(&ack.to_bytes()).unwrap(),
+            FinalPayloadContent::Ack(id) if id == [7; IDENTIFIER_LENGTH]
+        ));
+
+        assert!(FinalPayloadContent::from_bytes(&[]).is_err());
+        assert!(FinalPayloadContent::from_bytes(&[ACK, 7]).is_err());
+        assert!(FinalPayloadContent::from_bytes(&[FRAGMENT_WITH_ACK; 20]).is_err());
+    }
+}
@@ -1,4 +1,6 @@
-use sphinx_packet::{payload::Payload, SphinxPacket};
+pub mod final_payload;
+
+use sphinx_packet::{constants::IDENTIFIER_LENGTH, payload::Payload, SphinxPacket};
 
 use std::{io::ErrorKind, net::SocketAddr, sync::Arc, time::Duration};
 
@@ -10,6 +12,12 @@ use tokio::{
 
 pub type Result<T> = core::result::Result<T, ProtocolError>;
 
+/// Bodies bigger than this are rejected before being read, whatever their type
+pub const MAX_BODY_SIZE: u64 = 64 * 1024;
+
+/// Identifier marking cover packets that the last mix node discards instead of delivering
+pub const DROP_PACKET_IDENTIFIER: [u8; IDENTIFIER_LENGTH] = [0xff; IDENTIFIER_LENGTH];
+
 #[derive(Debug, thiserror::Error)]
 pub enum ProtocolError {
     #[error("Unknown body type {0}")]
@@ -18,8 +26,14 @@ pub enum ProtocolError {
     InvalidSphinxPacket(sphinx_packet::Error),
     #[error("{0}")]
     InvalidPayload(sphinx_packet::Error),
+    #[error("invalid reply block: {0}")]
+    InvalidSurb(sphinx_packet::Error),
+    #[error("invalid final payload content")]
+    InvalidFinalPayload,
     #[error("{0}")]
     IO(#[from] io::Error),
+    #[error("body of {0} bytes exceeds the maximum size of {MAX_BODY_SIZE} bytes")]
+    BodyTooLarge(u64),
     #[error("fail to send packet, reach maximum retries {0}")]
     ReachMaxRetries(usize),
 }
@@ -64,12 +78,24 @@ impl Body {
             .map_err(ProtocolError::InvalidPayload)
     }
 
-    async fn read_sphinx_packet<R>(reader: &mut R) -> Result<Body>
+    // The size is sent by the remote, check it before allocating anything
+    async fn read_size<R>(reader: &mut R) -> Result<usize>
     where
         R: AsyncRead + Unpin,
     {
         let size = reader.read_u64().await?;
-        let mut buf = vec![0; size as usize];
+        if size > MAX_BODY_SIZE {
+            return Err(ProtocolError::BodyTooLarge(size));
+        }
+        Ok(size as usize)
+    }
+
+    async fn read_sphinx_packet<R>(reader: &mut R) -> Result<Body>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let size = Self::read_size(reader).await?;
+        let mut buf = vec![0; size];
         reader.read_exact(&mut buf).await?;
         Self::sphinx_packet_from_bytes(&buf)
     }
@@ -84,8 +110,8 @@ impl Body {
     where
         R: AsyncRead + Unpin,
     {
-        let size = reader.read_u64().await?;
-        let mut buf = vec![0; size as usize];
+        let size = Self::read_size(reader).await?;
+        let mut buf = vec![0; size];
         reader.read_exact(&mut buf).await?;
 
         Self::final_payload_from_bytes(&buf)
@@ -147,3 +173,18 @@ pub async fn retry_backoff(
     }
     Err(ProtocolError::ReachMaxRetries(max_retries))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reject_oversized_body() {
+        let mut data = vec![1];
+        data.extend_from_slice(&(MAX_BODY_SIZE + 1).to_be_bytes());
+        assert!(matches!(
+            Body::read(&mut data.as_slice()).await,
+            Err(ProtocolError::BodyTooLarge(size)) if size == MAX_BODY_SIZE + 1
+        ));
+    }
+}
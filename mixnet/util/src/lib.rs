@@ -32,4 +32,9 @@ impl ConnectionPool {
             }
         }
     }
+
+    /// Drops the connection to `addr`, which is closed once it is not in use anymore
+    pub async fn remove(&self, addr: &SocketAddr) {
+        self.pool.lock().await.remove(addr);
+    }
 }
@@ -6,6 +6,7 @@ use overwatch_rs::services::handle::ServiceHandle;
 use overwatch_rs::services::ServiceData;
 use serde::{Deserialize, Serialize};
 use services::mixnet::MixnetNodeService;
+pub use services::mixnet::MixnetNodeServiceSettings;
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub struct Config {
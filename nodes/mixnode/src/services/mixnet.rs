@@ -1,15 +1,32 @@
-use mixnet_node::{MixnetNode, MixnetNodeConfig};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{routing::get, Router};
+use mixnet_node::{Metrics, MixnetNode, MixnetNodeConfig};
 use overwatch_rs::services::handle::ServiceStateHandle;
 use overwatch_rs::services::relay::NoMessage;
 use overwatch_rs::services::state::{NoOperator, NoState};
 use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
 use overwatch_rs::DynError;
+use serde::{Deserialize, Serialize};
 
-pub struct MixnetNodeService(MixnetNode);
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MixnetNodeServiceSettings {
+    #[serde(flatten)]
+    pub node: MixnetNodeConfig,
+    /// A local address serving the node metrics in the Prometheus text format, at `/metrics`
+    #[serde(default)]
+    pub metrics_listen_address: Option<SocketAddr>,
+}
+
+pub struct MixnetNodeService {
+    node: MixnetNode,
+    metrics_listen_address: Option<SocketAddr>,
+}
 
 impl ServiceData for MixnetNodeService {
     const SERVICE_ID: ServiceId = "mixnet-node";
-    type Settings = MixnetNodeConfig;
+    type Settings = MixnetNodeServiceSettings;
     type State = NoState<Self::Settings>;
     type StateOperator = NoOperator<Self::State>;
     type Message = NoMessage;
@@ -19,13 +36,74 @@ impl ServiceData for MixnetNodeService {
 impl ServiceCore for MixnetNodeService {
     fn init(service_state: ServiceStateHandle<Self>) -> Result<Self, DynError> {
         let settings: Self::Settings = service_state.settings_reader.get_updated_settings();
-        Ok(Self(MixnetNode::new(settings)))
+        Ok(Self {
+            node: MixnetNode::new(settings.node),
+            metrics_listen_address: settings.metrics_listen_address,
+        })
     }
 
     async fn run(self) -> Result<(), DynError> {
-        if let Err(_e) = self.0.run().await {
+        if let Some(address) = self.metrics_listen_address {
+            let metrics = self.node.metrics();
+            tokio::spawn(async move {
+                if let Err(e) = serve_metrics(address, metrics).await {
+                    tracing::error!("failed to serve metrics: {e}");
+                }
+            });
+        }
+        if let Err(_e) = self.node.run().await {
             todo!("Errors should match");
         }
         Ok(())
     }
 }
+
+async fn serve_metrics(address: SocketAddr, metrics: Arc<Metrics>) -> Result<(), DynError> {
+    let router = Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move { render(&metrics) }
+        }),
+    );
+    tracing::info!("Serving metrics on {address}");
+    axum::Server::bind(&address)
+        .serve(router.into_make_service())
+        .await?;
+    Ok(())
+}
+
+fn render(metrics: &Metrics) -> String {
+    let snapshot = metrics.snapshot();
+    [
+        (
+            "mixnode_packets_received_total",
+            "counter",
+            snapshot.packets_received,
+        ),
+        (
+            "mixnode_packets_forwarded_total",
+            "counter",
+            snapshot.packets_forwarded,
+        ),
+        (
+            "mixnode_packets_delayed_total",
+            "counter",
+            snapshot.packets_delayed,
+        ),
+        (
+            "mixnode_packets_dropped_total",
+            "counter",
+            snapshot.packets_dropped,
+        ),
+        ("mixnode_connections", "gauge", snapshot.connections),
+        (
+            "mixnode_connections_rejected_total",
+            "counter",
+            snapshot.connections_rejected,
+        ),
+    ]
+    .into_iter()
+    .map(|(name, kind, value)| format!("# TYPE {name} {kind}\n{name} {value}\n"))
+    .collect()
+}
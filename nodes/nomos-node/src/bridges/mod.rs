@@ -1,5 +1,5 @@
 mod libp2p;
-use consensus_engine::BlockId;
+use consensus_engine::{BlockId, Evidence};
 use libp2p::*;
 
 // std
@@ -23,9 +23,10 @@ use nomos_da::DaMsg;
 use nomos_http::backends::axum::AxumBackend;
 use nomos_http::bridge::{build_http_bridge, HttpBridgeRunner};
 use nomos_http::http::{HttpMethod, HttpRequest, HttpResponse};
-use nomos_mempool::backend::mockpool::MockPool;
+use nomos_mempool::backend::{mockpool::MockPool, priority::PriorityPool};
 use nomos_mempool::network::adapters::libp2p::Libp2pAdapter;
 use nomos_mempool::network::NetworkAdapter;
+use nomos_mempool::validator::TxValidator;
 use nomos_mempool::{Certificate as CertDiscriminant, Transaction as TxDiscriminant};
 use nomos_mempool::{MempoolMetrics, MempoolMsg, MempoolService};
 use nomos_network::backends::libp2p::Libp2p;
@@ -43,8 +44,9 @@ type DaMempoolService = MempoolService<
 >;
 type ClMempoolService = MempoolService<
     Libp2pAdapter<Tx, <Tx as Transaction>::Hash>,
-    MockPool<Tx, <Tx as Transaction>::Hash>,
+    PriorityPool<Tx, <Tx as Transaction>::Hash>,
     TxDiscriminant,
+    TxValidator,
 >;
 
 macro_rules! get_handler {
@@ -88,6 +90,14 @@ pub fn carnot_info_bridge(
     }))
 }
 
+pub fn carnot_evidence_bridge(
+    handle: overwatch_rs::overwatch::handle::OverwatchHandle,
+) -> HttpBridgeRunner {
+    Box::new(Box::pin(async move {
+        get_handler!(handle, Carnot, "evidence" => handle_carnot_evidence_req)
+    }))
+}
+
 pub fn cl_mempool_metrics_bridge(
     handle: overwatch_rs::overwatch::handle::OverwatchHandle,
 ) -> HttpBridgeRunner {
@@ -227,7 +237,12 @@ where
     Box::new(Box::pin(async move {
         let (mempool_channel, mut http_request_channel) =
             build_http_bridge::<
-                MempoolService<A, MockPool<Tx, <Tx as Transaction>::Hash>, TxDiscriminant>,
+                MempoolService<
+                    A,
+                    PriorityPool<Tx, <Tx as Transaction>::Hash>,
+                    TxDiscriminant,
+                    TxValidator,
+                >,
                 AxumBackend,
                 _,
             >(handle.clone(), HttpMethod::POST, "add")
@@ -311,6 +326,23 @@ async fn handle_carnot_info_req(
     Ok(())
 }
 
+async fn handle_carnot_evidence_req(
+    carnot_channel: &OutboundRelay<ConsensusMsg>,
+    res_tx: Sender<HttpResponse>,
+) -> Result<(), overwatch_rs::DynError> {
+    let (sender, receiver) = oneshot::channel();
+    carnot_channel
+        .send(ConsensusMsg::Evidence { tx: sender })
+        .await
+        .map_err(|(e, _)| e)?;
+    let evidence: Vec<Evidence> = receiver.await.unwrap();
+    res_tx
+        .send(Ok(serde_json::to_vec(&evidence)?.into()))
+        .await?;
+
+    Ok(())
+}
+
 async fn handle_mempool_metrics_req<K, V>(
     mempool_channel: &OutboundRelay<MempoolMsg<K, V>>,
     res_tx: Sender<HttpResponse>,
@@ -14,6 +14,7 @@ use metrics::{backend::map::MapMetricsBackend, types::MetricsData, MetricsServic
 use nomos_http::{backends::axum::AxumBackend, http::HttpService};
 use nomos_libp2p::{secp256k1::SecretKey, Multiaddr};
 use nomos_log::{Logger, LoggerBackend, LoggerFormat};
+use nomos_mempool::{backend::priority::PriorityPoolSettings, validator::TxValidatorSettings};
 use nomos_network::backends::libp2p::Libp2p;
 use nomos_network::NetworkService;
 use overwatch_rs::services::ServiceData;
@@ -108,6 +109,8 @@ pub struct Config {
     pub network: <NetworkService<Libp2p> as ServiceData>::Settings,
     pub http: <HttpService<AxumBackend> as ServiceData>::Settings,
     pub consensus: <Carnot as ServiceData>::Settings,
+    pub cl_mempool: PriorityPoolSettings,
+    pub cl_mempool_validator: TxValidatorSettings,
     #[cfg(feature = "metrics")]
     pub metrics: <MetricsService<MapMetricsBackend<MetricsData>> as ServiceData>::Settings,
     pub da: <DataAvailability as ServiceData>::Settings,
@@ -1,5 +1,4 @@
 mod config;
-mod tx;
 
 use color_eyre::eyre::Result;
 use consensus_engine::overlay::{RandomBeaconState, RoundRobin, TreeOverlay};
@@ -26,8 +25,9 @@ use nomos_http::http::HttpService;
 use nomos_log::Logger;
 use nomos_mempool::network::adapters::libp2p::Libp2pAdapter as MempoolLibp2pAdapter;
 use nomos_mempool::{
-    backend::mockpool::MockPool, Certificate as CertDiscriminant, MempoolService,
-    Transaction as TxDiscriminant,
+    backend::{mockpool::MockPool, priority::PriorityPool},
+    validator::TxValidator,
+    Certificate as CertDiscriminant, MempoolService, Transaction as TxDiscriminant,
 };
 use nomos_network::backends::libp2p::Libp2p;
 use nomos_storage::{
@@ -40,20 +40,20 @@ use overwatch_derive::*;
 use overwatch_rs::services::handle::ServiceHandle;
 
 pub use config::{Config, ConsensusArgs, HttpArgs, LogArgs, NetworkArgs, OverlayArgs};
+pub use nomos_core::tx::carnot::Tx;
 use nomos_core::{
     da::certificate::select::FillSize as FillSizeWithBlobsCertificate,
     tx::select::FillSize as FillSizeWithTx,
 };
 use serde::{de::DeserializeOwned, Serialize};
-pub use tx::Tx;
 
 pub const CL_TOPIC: &str = "cl";
 pub const DA_TOPIC: &str = "da";
 const MB16: usize = 1024 * 1024 * 16;
 
 pub type Carnot = CarnotConsensus<
-    ConsensusLibp2pAdapter,
-    MockPool<Tx, <Tx as Transaction>::Hash>,
+    ConsensusLibp2pAdapter<Tx, Certificate>,
+    PriorityPool<Tx, <Tx as Transaction>::Hash>,
     MempoolLibp2pAdapter<Tx, <Tx as Transaction>::Hash>,
     MockPool<Certificate, <<Certificate as certificate::Certificate>::Blob as blob::Blob>::Hash>,
     MempoolLibp2pAdapter<
@@ -72,13 +72,20 @@ pub type DataAvailability = DataAvailabilityService<
     DaLibp2pAdapter<Blob, Attestation>,
 >;
 
+pub type ClMempool = MempoolService<
+    MempoolLibp2pAdapter<Tx, <Tx as Transaction>::Hash>,
+    PriorityPool<Tx, <Tx as Transaction>::Hash>,
+    TxDiscriminant,
+    TxValidator,
+>;
+
 type Mempool<K, V, D> = MempoolService<MempoolLibp2pAdapter<K, V>, MockPool<K, V>, D>;
 
 #[derive(Services)]
 pub struct Nomos {
     logging: ServiceHandle<Logger>,
     network: ServiceHandle<NetworkService<Libp2p>>,
-    cl_mempool: ServiceHandle<Mempool<Tx, <Tx as Transaction>::Hash, TxDiscriminant>>,
+    cl_mempool: ServiceHandle<ClMempool>,
     da_mempool: ServiceHandle<
         Mempool<
             Certificate,
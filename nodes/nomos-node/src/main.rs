@@ -60,6 +60,7 @@ fn main() -> Result<()> {
 
     let bridges: Vec<HttpBridge> = vec![
         Arc::new(Box::new(bridges::carnot_info_bridge)),
+        Arc::new(Box::new(bridges::carnot_evidence_bridge)),
         // Due to a limitation in the current api system, we can't connect a single endopint to multiple services
         // which means we need two different paths for complete mempool metrics.
         Arc::new(Box::new(bridges::cl_mempool_metrics_bridge)),
@@ -85,11 +86,12 @@ fn main() -> Result<()> {
             logging: config.log,
             http: config.http,
             cl_mempool: nomos_mempool::Settings {
-                backend: (),
+                backend: config.cl_mempool,
                 network: AdapterSettings {
                     topic: String::from(nomos_node::CL_TOPIC),
                     id: <Tx as Transaction>::hash,
                 },
+                validator: config.cl_mempool_validator,
             },
             da_mempool: nomos_mempool::Settings {
                 backend: (),
@@ -97,6 +99,7 @@ fn main() -> Result<()> {
                     topic: String::from(nomos_node::DA_TOPIC),
                     id: cert_id,
                 },
+                validator: (),
             },
             consensus: config.consensus,
             bridges: HttpBridgeSettings { bridges },
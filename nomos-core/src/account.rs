@@ -1,4 +1,35 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct AccountId;
+use crate::crypto::PublicKey;
+
+/// Accounts are identified by the hash of the ed25519 public key controlling them
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AccountId([u8; 32]);
+
+impl AccountId {
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        use blake2::{
+            digest::{consts::U32, Digest},
+            Blake2b,
+        };
+        let mut hasher = Blake2b::<U32>::new();
+        hasher.update(public_key);
+        Self(hasher.finalize().into())
+    }
+}
+
+impl From<[u8; 32]> for AccountId {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl AsRef<[u8]> for AccountId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
@@ -7,6 +7,7 @@ use serde::Serialize;
 use crate::block::Block;
 use crate::da::certificate::BlobCertificateSelect;
 use crate::da::certificate::Certificate;
+use crate::ledger::StateRoot;
 use crate::tx::{Transaction, TxSelect};
 use consensus_engine::overlay::RandomBeaconState;
 use consensus_engine::{NodeId, Qc, View};
@@ -24,6 +25,7 @@ use consensus_engine::{NodeId, Qc, View};
 ///         .with_parent_qc(qc)
 ///         .with_proposer(proposer)
 ///         .with_beacon_state(beacon)
+///         .with_state_root(state_root)
 ///         .with_transactions([tx1].into_iter())
 ///         .with_blobs([blob1].into_iter())
 /// };
@@ -36,6 +38,7 @@ pub struct BlockBuilder<Tx, Blob, TxSelector, BlobSelector> {
     parent_qc: Option<Qc>,
     proposer: Option<NodeId>,
     beacon: Option<RandomBeaconState>,
+    state_root: Option<StateRoot>,
     txs: Option<Box<dyn Iterator<Item = Tx>>>,
     blobs: Option<Box<dyn Iterator<Item = Blob>>>,
 }
@@ -55,6 +58,7 @@ where
             parent_qc: None,
             proposer: None,
             beacon: None,
+            state_root: None,
             txs: None,
             blobs: None,
         }
@@ -84,6 +88,12 @@ where
         self
     }
 
+    #[must_use]
+    pub fn with_state_root(mut self, state_root: StateRoot) -> Self {
+        self.state_root = Some(state_root);
+        self
+    }
+
     #[must_use]
     pub fn with_transactions(mut self, txs: impl Iterator<Item = Tx> + 'static) -> Self {
         self.txs = Some(Box::new(txs));
@@ -108,6 +118,7 @@ where
             parent_qc: Some(parent_qc),
             proposer: Some(proposer),
             beacon: Some(beacon),
+            state_root: Some(state_root),
             txs: Some(txs),
             blobs: Some(blobs),
         } = self
@@ -119,6 +130,7 @@ where
                 blob_selector.select_blob_from(blobs),
                 proposer,
                 beacon,
+                state_root,
             ))
         } else {
             Err(self)
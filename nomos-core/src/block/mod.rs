@@ -5,6 +5,7 @@ use indexmap::IndexSet;
 // std
 use core::hash::Hash;
 // crates
+use crate::ledger::StateRoot;
 use crate::wire;
 use bytes::Bytes;
 pub use consensus_engine::BlockId;
@@ -15,6 +16,9 @@ use serde::{Deserialize, Serialize};
 
 pub type TxHash = [u8; 32];
 
+/// Maximum size in bytes of an encoded block
+pub const MAX_BLOCK_SIZE: u64 = 8 << 20;
+
 /// A block
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Block<Tx: Clone + Eq + Hash, BlobCertificate: Clone + Eq + Hash> {
@@ -36,6 +40,7 @@ impl<
         blobs: impl Iterator<Item = BlobCertificate>,
         proposer: NodeId,
         beacon: RandomBeaconState,
+        state_root: StateRoot,
     ) -> Self {
         let transactions = txs.collect();
         let blobs = blobs.collect();
@@ -43,6 +48,7 @@ impl<
             id: BlockId::zeros(),
             view,
             parent_qc,
+            state_root,
             leader_proof: LeaderProof::LeaderId {
                 leader_id: proposer,
             },
@@ -75,8 +81,17 @@ impl<Tx: Clone + Eq + Hash, BlobCertificate: Clone + Eq + Hash> Block<Tx, BlobCe
     pub fn beacon(&self) -> &RandomBeaconState {
         &self.beacon
     }
+
+    /// Root of the ledger state after executing the block transactions
+    pub fn state_root(&self) -> &StateRoot {
+        &self.header.state_root
+    }
 }
 
+/// Id of a block, as the hash of its canonical encoding (see [`wire`]).
+///
+/// The id only depends on the block content and the wire format version, so it is the same
+/// across releases and implementations sharing that version.
 pub fn block_id_from_wire_content<
     Tx: Clone + Eq + Hash + Serialize + DeserializeOwned,
     BlobCertificate: Clone + Eq + Hash + Serialize + DeserializeOwned,
@@ -97,13 +112,108 @@ impl<
     > Block<Tx, BlobCertificate>
 {
     /// Encode block into bytes
+    ///
+    /// Blocks are decoded within [`MAX_BLOCK_SIZE`] and proposals are filled with
+    /// [`fit_in_block`], so only blocks built by hand with too many contents can fail here.
     pub fn as_bytes(&self) -> Bytes {
-        wire::serialize(self).unwrap().into()
+        wire::serialize_with_limit(self, MAX_BLOCK_SIZE)
+            .expect("block should fit in MAX_BLOCK_SIZE")
+            .into()
+    }
+
+    /// Size of the encoded block
+    pub fn encoded_size(&self) -> Result<u64, wire::Error> {
+        wire::serialized_size(self)
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Self {
-        let mut result: Self = wire::deserialize(bytes).unwrap();
+    /// Decode a block, failing on malformed data or invalid transactions
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, wire::Error> {
+        let mut result: Self = wire::deserialize_with_limit(bytes, MAX_BLOCK_SIZE)?;
         result.header.id = block_id_from_wire_content(&result);
-        result
+        Ok(result)
+    }
+}
+
+/// Keep `items`, in order, as long as their encoding fits in the `budget` bytes left in a block,
+/// deducting their size from it.
+///
+/// A block grows by exactly the encoded size of each transaction or blob certificate it carries,
+/// so filling the budget left by the same block without contents keeps it within
+/// [`MAX_BLOCK_SIZE`].
+pub fn fit_in_block<T: Serialize>(items: impl IntoIterator<Item = T>, budget: &mut u64) -> Vec<T> {
+    items
+        .into_iter()
+        .map_while(|item| {
+            let size = wire::serialized_size(&item).ok()?;
+            (size <= *budget).then(|| {
+                *budget -= size;
+                item
+            })
+        })
+        .collect()
+}
+
+impl<
+        Tx: Clone + Eq + Hash + Serialize + DeserializeOwned,
+        BlobCertificate: Clone + Eq + Hash + Serialize + DeserializeOwned,
+    > wire::WireMessage for Block<Tx, BlobCertificate>
+{
+    const TAG: u16 = wire::tag::BLOCK;
+    const MAX_SIZE: u64 = MAX_BLOCK_SIZE;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consensus_engine::StandardQc;
+
+    // Block ids are part of the protocol, changing them requires a new wire format version
+    #[test]
+    fn stable_block_id() {
+        let block: Block<u64, ()> = Block::new(
+            View::new(1),
+            Qc::Standard(StandardQc::genesis()),
+            [1, 2, 3].into_iter(),
+            std::iter::empty(),
+            NodeId::new([0; 32]),
+            RandomBeaconState::initial_sad_from_entropy([0; 32]),
+            [0; 32],
+        );
+        assert_eq!(
+            Block::<u64, ()>::from_bytes(&block.as_bytes())
+                .unwrap()
+                .header()
+                .id,
+            block.header().id
+        );
+        assert!(Block::<u64, ()>::from_bytes(&[0; 16]).is_err());
+        assert_eq!(
+            block.header().id,
+            BlockId::new([
+                93, 120, 87, 88, 200, 3, 32, 205, 32, 197, 139, 228, 223, 166, 98, 4, 238, 65, 118,
+                255, 183, 118, 61, 179, 199, 29, 201, 37, 160, 131, 153, 202
+            ])
+        );
+    }
+
+    #[test]
+    fn fill_block() {
+        let block = |txs: Vec<u64>| {
+            Block::<u64, ()>::new(
+                View::new(1),
+                Qc::Standard(StandardQc::genesis()),
+                txs.into_iter(),
+                std::iter::empty(),
+                NodeId::new([0; 32]),
+                RandomBeaconState::initial_sad_from_entropy([0; 32]),
+                [0; 32],
+            )
+        };
+        let empty = block(vec![]).encoded_size().unwrap();
+        let mut budget = 20;
+        let txs = fit_in_block([1, 2, 3], &mut budget);
+        assert_eq!(txs, vec![1, 2]);
+        assert_eq!(budget, 4);
+        assert_eq!(block(txs).encoded_size().unwrap(), empty + 16);
     }
 }
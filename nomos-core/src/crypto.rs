@@ -1,3 +1,3 @@
 pub type PublicKey = [u8; 32];
 pub type PrivateKey = [u8; 32];
-pub type Signature = [u8; 32];
+pub type Signature = [u8; 64];
@@ -27,7 +27,8 @@ pub trait DaProtocol {
     /// If the protocol is not yet ready to return the data, return None.
     fn extract(&mut self) -> Option<Bytes>;
     /// Attest that we have received and stored a blob.
-    fn attest(&self, blob: &Self::Blob) -> Self::Attestation;
+    /// Return None if the blob is not valid, in which case it should not be stored.
+    fn attest(&self, blob: &Self::Blob) -> Option<Self::Attestation>;
     /// Validate that an attestation is valid for a blob.
     fn validate_attestation(&self, blob: &Self::Blob, attestation: &Self::Attestation) -> bool;
     /// Buffer attestations to produce a certificate of correct dispersal.
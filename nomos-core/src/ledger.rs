@@ -0,0 +1,304 @@
+// std
+use std::collections::BTreeMap;
+use std::hash::Hash;
+// crates
+use serde::{Deserialize, Serialize};
+// internal
+use crate::account::AccountId;
+use crate::block::{Block, BlockId};
+use crate::tx::carnot::{TransferTransaction, Tx};
+use crate::wire;
+
+pub type StateRoot = [u8; 32];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LedgerError {
+    #[error("Block {block:?} does not extend the ledger tip {tip:?}")]
+    NotExtending { block: BlockId, tip: BlockId },
+    #[error("Invalid nonce for account {account:?}: expected {expected}, got {got}")]
+    InvalidNonce {
+        account: AccountId,
+        expected: u64,
+        got: u64,
+    },
+    #[error("Account {account:?} can't transfer {value} with a balance of {balance}")]
+    InsufficientBalance {
+        account: AccountId,
+        balance: u64,
+        value: u64,
+    },
+    #[error("Balance of account {0:?} overflows")]
+    Overflow(AccountId),
+    #[error("Block commits to state root {committed:?} but execution results in {computed:?}")]
+    StateRootMismatch {
+        committed: StateRoot,
+        computed: StateRoot,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    pub balance: u64,
+    /// Number of transactions executed on behalf of this account
+    pub nonce: u64,
+}
+
+/// Transactions with an effect on the ledger
+pub trait LedgerTransaction {
+    /// Transfer performed by the transaction, if any
+    fn transfer(&self) -> Option<&TransferTransaction>;
+}
+
+impl LedgerTransaction for Tx {
+    fn transfer(&self) -> Option<&TransferTransaction> {
+        let Tx::Transfer(tx) = self;
+        Some(tx)
+    }
+}
+
+/// Balances and nonces of every account, as of the last executed block.
+///
+/// Blocks are applied following the chain order, proposals being executed on a copy of the
+/// state at their parent until they are committed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ledger {
+    tip: BlockId,
+    accounts: BTreeMap<AccountId, Account>,
+}
+
+impl Ledger {
+    /// Ledger state at the genesis block, with the initial balance of each account
+    pub fn genesis(balances: impl IntoIterator<Item = (AccountId, u64)>) -> Self {
+        Self {
+            tip: BlockId::zeros(),
+            accounts: balances
+                .into_iter()
+                .map(|(id, balance)| (id, Account { balance, nonce: 0 }))
+                .collect(),
+        }
+    }
+
+    /// Id of the last block applied to the ledger
+    pub fn tip(&self) -> BlockId {
+        self.tip
+    }
+
+    pub fn account(&self, id: &AccountId) -> Account {
+        self.accounts.get(id).copied().unwrap_or_default()
+    }
+
+    /// Commitment to the current state of every account
+    pub fn state_root(&self) -> StateRoot {
+        state_root(&self.accounts)
+    }
+
+    /// State root resulting from executing `txs` on top of the current state.
+    /// Used by leaders to fill in the root of the block they propose.
+    pub fn execute<'a, T: LedgerTransaction + 'a>(
+        &self,
+        txs: impl IntoIterator<Item = &'a T>,
+    ) -> Result<StateRoot, LedgerError> {
+        let mut state = self.clone();
+        for tx in txs {
+            state.apply_tx(tx)?;
+        }
+        Ok(state.state_root())
+    }
+
+    /// Execute a single transaction, leaving the ledger untouched if it fails
+    pub fn apply_tx<T: LedgerTransaction>(&mut self, tx: &T) -> Result<(), LedgerError> {
+        match tx.transfer() {
+            Some(tx) => transfer(&mut self.accounts, tx),
+            None => Ok(()),
+        }
+    }
+
+    /// Execute the transactions of a block on top of its parent, which must be the ledger tip.
+    ///
+    /// The block is rejected as a whole, leaving the ledger untouched, if any of its transactions
+    /// fails or if the resulting state does not match the root committed in the block.
+    pub fn apply_block<T: LedgerTransaction + Clone + Eq + Hash, C: Clone + Eq + Hash>(
+        &mut self,
+        block: &Block<T, C>,
+    ) -> Result<(), LedgerError> {
+        let header = block.header();
+        if header.parent() != self.tip {
+            return Err(LedgerError::NotExtending {
+                block: header.id,
+                tip: self.tip,
+            });
+        }
+        let mut state = self.clone();
+        for tx in block.transactions() {
+            state.apply_tx(tx)?;
+        }
+        let computed = state.state_root();
+        if computed != *block.state_root() {
+            return Err(LedgerError::StateRootMismatch {
+                committed: *block.state_root(),
+                computed,
+            });
+        }
+        state.tip = header.id;
+        *self = state;
+        Ok(())
+    }
+}
+
+// Both accounts are only updated once the transfer is known to succeed
+fn transfer(
+    accounts: &mut BTreeMap<AccountId, Account>,
+    tx: &TransferTransaction,
+) -> Result<(), LedgerError> {
+    let (from, to, value) = (*tx.from(), *tx.to(), tx.value());
+    let sender = accounts.get(&from).copied().unwrap_or_default();
+    if sender.nonce != tx.nonce() {
+        return Err(LedgerError::InvalidNonce {
+            account: from,
+            expected: sender.nonce,
+            got: tx.nonce(),
+        });
+    }
+    let sender = Account {
+        balance: sender
+            .balance
+            .checked_sub(value)
+            .ok_or(LedgerError::InsufficientBalance {
+                account: from,
+                balance: sender.balance,
+                value,
+            })?,
+        nonce: sender.nonce + 1,
+    };
+    let receiver = if to == from {
+        sender
+    } else {
+        accounts.get(&to).copied().unwrap_or_default()
+    };
+    let receiver = Account {
+        balance: receiver
+            .balance
+            .checked_add(value)
+            .ok_or(LedgerError::Overflow(to))?,
+        ..receiver
+    };
+    accounts.insert(from, sender);
+    accounts.insert(to, receiver);
+    Ok(())
+}
+
+// Accounts are kept sorted, so hashing their encoding gives the same root on every node
+fn state_root(accounts: &BTreeMap<AccountId, Account>) -> StateRoot {
+    use blake2::{
+        digest::{consts::U32, Digest},
+        Blake2b,
+    };
+    let mut hasher = Blake2b::<U32>::new();
+    hasher.update(wire::serialize(accounts).expect("accounts should always be serializable"));
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::PrivateKey;
+    use consensus_engine::overlay::RandomBeaconState;
+    use consensus_engine::{NodeId, Qc, StandardQc, View};
+
+    const ALICE: PrivateKey = [1; 32];
+    const BOB: PrivateKey = [2; 32];
+
+    fn account(private_key: &PrivateKey) -> AccountId {
+        *TransferTransaction::new(private_key, AccountId::new([0; 32]), 0, 0).from()
+    }
+
+    fn block(parent: BlockId, view: View, txs: Vec<Tx>, state_root: StateRoot) -> Block<Tx, ()> {
+        Block::new(
+            view,
+            Qc::Standard(StandardQc {
+                view: view.prev(),
+                id: parent,
+                signature: None,
+            }),
+            txs.into_iter(),
+            std::iter::empty(),
+            NodeId::new([0; 32]),
+            RandomBeaconState::initial_sad_from_entropy([0; 32]),
+            state_root,
+        )
+    }
+
+    #[test]
+    fn apply_blocks() {
+        let (alice, bob) = (account(&ALICE), account(&BOB));
+        let mut ledger = Ledger::genesis([(alice, 100)]);
+        let txs = vec![
+            Tx::Transfer(TransferTransaction::new(&ALICE, bob, 30, 0)),
+            Tx::Transfer(TransferTransaction::new(&BOB, alice, 10, 0)),
+        ];
+        let root = ledger.execute(&txs).unwrap();
+        let first = block(ledger.tip(), View::new(1), txs, root);
+        ledger.apply_block(&first).unwrap();
+        assert_eq!(ledger.tip(), first.header().id);
+        assert_eq!(ledger.state_root(), root);
+        assert_eq!(
+            ledger.account(&alice),
+            Account {
+                balance: 80,
+                nonce: 1
+            }
+        );
+        assert_eq!(
+            ledger.account(&bob),
+            Account {
+                balance: 20,
+                nonce: 1
+            }
+        );
+
+        // blocks must be applied in order
+        let orphan = block(BlockId::new([42; 32]), View::new(2), vec![], root);
+        assert!(matches!(
+            ledger.apply_block(&orphan),
+            Err(LedgerError::NotExtending { .. })
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_blocks() {
+        let (alice, bob) = (account(&ALICE), account(&BOB));
+        let mut ledger = Ledger::genesis([(alice, 100)]);
+        let genesis = ledger.clone();
+
+        let replay = vec![
+            Tx::Transfer(TransferTransaction::new(&ALICE, bob, 30, 0)),
+            Tx::Transfer(TransferTransaction::new(&ALICE, bob, 30, 0)),
+        ];
+        assert_eq!(
+            ledger.execute(&replay),
+            Err(LedgerError::InvalidNonce {
+                account: alice,
+                expected: 1,
+                got: 0
+            })
+        );
+
+        let overspend = vec![Tx::Transfer(TransferTransaction::new(&ALICE, bob, 101, 0))];
+        let invalid = block(ledger.tip(), View::new(1), overspend, ledger.state_root());
+        assert!(matches!(
+            ledger.apply_block(&invalid),
+            Err(LedgerError::InsufficientBalance { .. })
+        ));
+
+        let txs = vec![Tx::Transfer(TransferTransaction::new(&ALICE, bob, 30, 0))];
+        let wrong_root = block(ledger.tip(), View::new(1), txs, ledger.state_root());
+        assert!(matches!(
+            ledger.apply_block(&wrong_root),
+            Err(LedgerError::StateRootMismatch { .. })
+        ));
+
+        let self_transfer = Tx::Transfer(TransferTransaction::new(&ALICE, alice, 101, 0));
+        assert!(ledger.apply_tx(&self_transfer).is_err());
+        assert_eq!(ledger, genesis);
+    }
+}
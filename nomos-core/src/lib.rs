@@ -2,6 +2,7 @@ pub mod account;
 pub mod block;
 pub mod crypto;
 pub mod da;
+pub mod ledger;
 pub mod staking;
 pub mod tx;
 pub mod utils;
@@ -1 +1 @@
-pub type Stake = u64;
+pub use consensus_engine::{Stake, StakeRegistry};
@@ -5,10 +5,11 @@ use serde::{Deserialize, Serialize};
 // internal
 pub use crate::tx::carnot::transaction::TransferTransaction;
 use crate::tx::{Transaction, TransactionHasher};
+use crate::wire;
 
 mod transaction;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Tx {
     Transfer(TransferTransaction),
 }
@@ -30,6 +31,8 @@ impl Transaction for Tx {
     type Hash = [u8; 32];
 
     fn as_bytes(&self) -> Bytes {
-        [].to_vec().into()
+        wire::serialize(self)
+            .expect("transactions should always be serializable")
+            .into()
     }
 }
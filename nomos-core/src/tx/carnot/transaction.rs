@@ -1,36 +1,125 @@
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+
 use crate::account::AccountId;
-use crate::crypto::Signature;
+use crate::crypto::{PrivateKey, PublicKey, Signature};
+
+const TRANSFER_TAG: &[u8] = b"NOMOS_CARNOT_TRANSFER";
 
 /// Verified transactions
 ///
 /// Can only be constructed if the signature is valid,
 /// but does not imply that it can be successfully applied
 /// to the ledger.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TransferTransaction {
-    pub from: AccountId,
-    pub to: AccountId,
-    pub value: u64,
+    from: AccountId,
+    to: AccountId,
+    value: u64,
+    nonce: u64,
     // TODO: here for the moment because I still want to retain the ability
     // to go from `Transaction` to wire format. We could otherwise
     // save the id and rely on some storage
-    _signature: Signature,
+    public_key: PublicKey,
+    signature: Signature,
+}
+
+impl TransferTransaction {
+    /// Transfer `value` to `to` from the account controlled by `private_key`.
+    ///
+    /// `nonce` must match the number of transactions already executed for that account.
+    pub fn new(private_key: &PrivateKey, to: AccountId, value: u64, nonce: u64) -> Self {
+        let signing_key = SigningKey::from_bytes(private_key);
+        let public_key = signing_key.verifying_key().to_bytes();
+        let signature = signing_key
+            .sign(&signed_bytes(&public_key, &to, value, nonce))
+            .to_bytes();
+        Self {
+            from: AccountId::from_public_key(&public_key),
+            to,
+            value,
+            nonce,
+            public_key,
+            signature,
+        }
+    }
+
+    pub fn from(&self) -> &AccountId {
+        &self.from
+    }
+
+    pub fn to(&self) -> &AccountId {
+        &self.to
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Whether the transfer is signed by the key of the sender account
+    pub fn verify_signature(&self) -> bool {
+        self.from == AccountId::from_public_key(&self.public_key)
+            && verify(
+                &self.public_key,
+                &self.to,
+                self.value,
+                self.nonce,
+                &self.signature,
+            )
+    }
+}
+
+/// Bytes signed by the sender of a transfer
+fn signed_bytes(public_key: &PublicKey, to: &AccountId, value: u64, nonce: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(TRANSFER_TAG.len() + 32 + 32 + 8 + 8);
+    bytes.extend_from_slice(TRANSFER_TAG);
+    bytes.extend_from_slice(public_key);
+    bytes.extend_from_slice(to.as_ref());
+    bytes.extend_from_slice(&value.to_be_bytes());
+    bytes.extend_from_slice(&nonce.to_be_bytes());
+    bytes
+}
+
+fn verify(
+    public_key: &PublicKey,
+    to: &AccountId,
+    value: u64,
+    nonce: u64,
+    signature: &Signature,
+) -> bool {
+    VerifyingKey::from_bytes(public_key)
+        .and_then(|key| {
+            key.verify_strict(
+                &signed_bytes(public_key, to, value, nonce),
+                &ed25519_dalek::Signature::from_bytes(signature),
+            )
+        })
+        .is_ok()
 }
 
 mod serde {
     use super::*;
-    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use ::serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
     // We have this additional definition so that we can automatically derive
     // Serialize/Deserialize for the type while still being able to check
     // the signature while deserializing.
     // This would also allow to control ser/de independently from the Rust
     // representation.
+    // The sender account is not part of the wire format as it is derived from its public key.
     #[derive(Serialize, Deserialize)]
     struct WireTransferTransaction {
-        from: AccountId,
+        from: PublicKey,
         to: AccountId,
         value: u64,
-        signature: Signature,
+        nonce: u64,
+        signature: ed25519_dalek::Signature,
     }
 
     impl<'de> Deserialize<'de> for TransferTransaction {
@@ -42,14 +131,20 @@ mod serde {
                 from,
                 to,
                 value,
+                nonce,
                 signature,
             } = WireTransferTransaction::deserialize(deserializer)?;
-            //TODO: check signature
+            let signature = signature.to_bytes();
+            if !verify(&from, &to, value, nonce, &signature) {
+                return Err(D::Error::custom("invalid transaction signature"));
+            }
             Ok(TransferTransaction {
-                from,
+                from: AccountId::from_public_key(&from),
                 to,
                 value,
-                _signature: signature,
+                nonce,
+                public_key: from,
+                signature,
             })
         }
     }
@@ -60,12 +155,37 @@ mod serde {
             S: Serializer,
         {
             WireTransferTransaction {
-                from: self.from.clone(),
-                to: self.to.clone(),
+                from: self.public_key,
+                to: self.to,
                 value: self.value,
-                signature: self._signature,
+                nonce: self.nonce,
+                signature: ed25519_dalek::Signature::from_bytes(&self.signature),
             }
             .serialize(serializer)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire;
+
+    #[test]
+    fn signature_checked_on_deserialization() {
+        let tx = TransferTransaction::new(&[1; 32], AccountId::new([2; 32]), 10, 0);
+        assert_eq!(tx.from(), &AccountId::from_public_key(tx.public_key()));
+        assert!(tx.verify_signature());
+        let bytes = wire::serialize(&tx).unwrap();
+        assert_eq!(
+            wire::deserialize::<TransferTransaction>(&bytes).unwrap(),
+            tx
+        );
+
+        let mut tampered = tx;
+        tampered.value = 1000;
+        assert!(!tampered.verify_signature());
+        let bytes = wire::serialize(&tampered).unwrap();
+        assert!(wire::deserialize::<TransferTransaction>(&bytes).is_err());
+    }
+}
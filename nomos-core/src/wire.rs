@@ -1,7 +1,26 @@
 //! Serializer and Deserializer for wire formats.
+//!
+//! # Canonical encoding
+//!
+//! Every item is encoded with the same fixed set of rules, so that the same value always
+//! results in the same bytes, which other implementations can reproduce. This is what makes
+//! hashes over encoded data (e.g. block ids) stable:
+//! * integers are encoded with a fixed width in little endian order, `bool`s as a single byte
+//! * sequences, strings and maps are prefixed by their length as a `u64`
+//! * `Option`s are prefixed by a single byte, `0` for `None` and `1` for `Some`
+//! * enum variants are prefixed by their index as a `u32`
+//! * struct and tuple fields are encoded in order, without any additional framing
+//! * trailing bytes are rejected when decoding
+//!
+//! Types relying on this property must only contain collections with a deterministic iteration
+//! order (e.g. `BTreeMap` or `IndexSet`). Any change to these rules requires bumping [`VERSION`].
+//!
+//! # Envelope
+//!
+//! Messages exchanged between nodes are framed by [`encode`] and [`decode`] with a header made of
+//! [`MAGIC`], the format [`VERSION`] and the [`WireMessage::TAG`] of the message type, followed
+//! by the canonical encoding of the message, bounded by [`WireMessage::MAX_SIZE`].
 
-// TODO: we're using bincode for now, but might need strong guarantees about
-// the underlying format in the future for standardization.
 use bincode::{
     config::{
         Bounded, DefaultOptions, FixintEncoding, LittleEndian, RejectTrailing, WithOtherEndian,
@@ -10,11 +29,50 @@ use bincode::{
     de::read::SliceReader,
     Options,
 };
-use once_cell::sync::Lazy;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-pub type Error = bincode::Error;
+/// Bytes identifying a nomos wire message
+pub const MAGIC: [u8; 4] = *b"NOMO";
+/// Version of the wire format, covering both the envelope and the canonical encoding
+pub const VERSION: u8 = 2;
+/// Size of the envelope header: magic, version and message tag
+pub const HEADER_SIZE: usize = MAGIC.len() + 1 + 2;
+/// Size limit for types not specifying their own
+pub const DEFAULT_DATA_LIMIT: u64 = 1 << 20;
+
+/// Tags of the message types exchanged over the wire, they must never be reused
+pub mod tag {
+    pub const BLOCK: u16 = 0x0001;
+    pub const CONSENSUS: u16 = 0x0002;
+    pub const SYNC_REQUEST: u16 = 0x0003;
+    pub const SYNC_RESPONSE: u16 = 0x0004;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Encoding(#[from] bincode::Error),
+    #[error("Encoded data takes {size} bytes, over the limit of {limit}")]
+    TooLarge { size: usize, limit: u64 },
+    #[error("Message is too short to contain a header: {0} bytes")]
+    Truncated(usize),
+    #[error("Not a nomos message, unexpected magic bytes {0:?}")]
+    InvalidMagic([u8; 4]),
+    #[error("Unsupported wire format version {0}, expected {VERSION}")]
+    UnsupportedVersion(u8),
+    #[error("Unexpected message type {found:#06x}, expected {expected:#06x}")]
+    UnexpectedTag { expected: u16, found: u16 },
+}
+
+/// A type sent over the wire inside a versioned envelope
+pub trait WireMessage: Serialize + DeserializeOwned {
+    /// Identifies the message type, see [`tag`]
+    const TAG: u16;
+    /// Maximum size in bytes of the encoded message, excluding the envelope header
+    const MAX_SIZE: u64 = DEFAULT_DATA_LIMIT;
+}
+
 // type composition is cool but also makes naming types a bit akward
 type BincodeOptions = WithOtherTrailing<
     WithOtherIntEncoding<
@@ -24,14 +82,13 @@ type BincodeOptions = WithOtherTrailing<
     RejectTrailing,
 >;
 
-const DATA_LIMIT: u64 = 2048; // Do not serialize/deserialize more than 2Kb
-static OPTIONS: Lazy<BincodeOptions> = Lazy::new(|| {
+fn options(limit: u64) -> BincodeOptions {
     bincode::DefaultOptions::new()
         .with_little_endian()
-        .with_limit(DATA_LIMIT)
+        .with_limit(limit)
         .with_fixint_encoding()
         .reject_trailing_bytes()
-});
+}
 
 type BincodeDeserializer<'de> = bincode::Deserializer<SliceReader<'de>, BincodeOptions>;
 type BincodeSerializer<T> = bincode::Serializer<T, BincodeOptions>;
@@ -50,7 +107,7 @@ impl<'de> Deserializer<'de> {
     }
 
     pub fn deserialize<T: Deserialize<'de>>(&mut self) -> Result<T, Error> {
-        <T>::deserialize(&mut self.inner)
+        Ok(<T>::deserialize(&mut self.inner)?)
     }
 }
 
@@ -63,7 +120,7 @@ impl<T: std::io::Write> Serializer<T> {
         &mut self,
         item: &U,
     ) -> Result<<&mut BincodeSerializer<T> as serde::Serializer>::Ok, Error> {
-        item.serialize(&mut self.inner)
+        Ok(item.serialize(&mut self.inner)?)
     }
 }
 
@@ -73,7 +130,7 @@ impl<T: std::io::Write> Serializer<T> {
 /// any underlying protocol. See https://sans-io.readthedocs.io/how-to-sans-io.html
 pub fn deserializer(data: &[u8]) -> Deserializer<'_> {
     Deserializer {
-        inner: bincode::de::Deserializer::from_slice(data, *OPTIONS),
+        inner: bincode::de::Deserializer::from_slice(data, options(DEFAULT_DATA_LIMIT)),
     }
 }
 
@@ -83,7 +140,7 @@ pub fn deserializer(data: &[u8]) -> Deserializer<'_> {
 /// any underlying protocol. See https://sans-io.readthedocs.io/how-to-sans-io.html
 pub fn serializer(buffer: &mut Vec<u8>) -> Serializer<&'_ mut Vec<u8>> {
     Serializer {
-        inner: bincode::Serializer::new(buffer, *OPTIONS),
+        inner: bincode::Serializer::new(buffer, options(DEFAULT_DATA_LIMIT)),
     }
 }
 
@@ -94,21 +151,74 @@ pub fn serializer(buffer: &mut Vec<u8>) -> Serializer<&'_ mut Vec<u8>> {
 /// any underlying protocol. See https://sans-io.readthedocs.io/how-to-sans-io.html
 pub fn serializer_into_buffer(buffer: &mut [u8]) -> Serializer<&'_ mut [u8]> {
     Serializer {
-        inner: bincode::Serializer::new(buffer, *OPTIONS),
+        inner: bincode::Serializer::new(buffer, options(DEFAULT_DATA_LIMIT)),
     }
 }
 
 /// Serialize an object directly into a vec
 pub fn serialize<T: Serialize>(item: &T) -> Result<Vec<u8>, Error> {
-    let size = OPTIONS.serialized_size(item)?;
-    let mut buf = Vec::with_capacity(size as usize);
-    serializer(&mut buf).serialize_into(item)?;
-    Ok(buf)
+    serialize_with_limit(item, DEFAULT_DATA_LIMIT)
 }
 
 /// Deserialize an object directly
 pub fn deserialize<T: DeserializeOwned>(item: &[u8]) -> Result<T, Error> {
-    deserializer(item).deserialize()
+    deserialize_with_limit(item, DEFAULT_DATA_LIMIT)
+}
+
+/// Serialize an object directly into a vec, failing if it takes more than `limit` bytes
+pub fn serialize_with_limit<T: Serialize>(item: &T, limit: u64) -> Result<Vec<u8>, Error> {
+    Ok(options(limit).serialize(item)?)
+}
+
+/// Size of the canonical encoding of an object
+pub fn serialized_size<T: Serialize>(item: &T) -> Result<u64, Error> {
+    Ok(options(u64::MAX).serialized_size(item)?)
+}
+
+/// Deserialize an object directly, failing if it takes more than `limit` bytes
+pub fn deserialize_with_limit<T: DeserializeOwned>(item: &[u8], limit: u64) -> Result<T, Error> {
+    // checked upfront as single bytes are not accounted for by bincode
+    if item.len() as u64 > limit {
+        return Err(Error::TooLarge {
+            size: item.len(),
+            limit,
+        });
+    }
+    Ok(options(limit).deserialize(item)?)
+}
+
+/// Serialize a message within the versioned envelope
+pub fn encode<T: WireMessage>(message: &T) -> Result<Vec<u8>, Error> {
+    let size = options(T::MAX_SIZE).serialized_size(message)?;
+    let mut buf = Vec::with_capacity(HEADER_SIZE + size as usize);
+    buf.extend_from_slice(&MAGIC);
+    buf.push(VERSION);
+    buf.extend_from_slice(&T::TAG.to_be_bytes());
+    options(T::MAX_SIZE).serialize_into(&mut buf, message)?;
+    Ok(buf)
+}
+
+/// Deserialize a message after checking its envelope
+pub fn decode<T: WireMessage>(data: &[u8]) -> Result<T, Error> {
+    if data.len() < HEADER_SIZE {
+        return Err(Error::Truncated(data.len()));
+    }
+    let (header, payload) = data.split_at(HEADER_SIZE);
+    let magic: [u8; 4] = header[..4].try_into().unwrap();
+    if magic != MAGIC {
+        return Err(Error::InvalidMagic(magic));
+    }
+    if header[4] != VERSION {
+        return Err(Error::UnsupportedVersion(header[4]));
+    }
+    let tag = u16::from_be_bytes([header[5], header[6]]);
+    if tag != T::TAG {
+        return Err(Error::UnexpectedTag {
+            expected: T::TAG,
+            found: tag,
+        });
+    }
+    deserialize_with_limit(payload, T::MAX_SIZE)
 }
 
 #[cfg(test)]
@@ -153,4 +263,75 @@ mod tests {
         let deserialized = <String>::deserialize(deserializer.get_deserializer()).unwrap();
         assert_eq!(tmp, deserialized);
     }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Ping {
+        nonce: u32,
+        payload: Option<Vec<u8>>,
+    }
+
+    impl WireMessage for Ping {
+        const TAG: u16 = 0xffff;
+        const MAX_SIZE: u64 = 32;
+    }
+
+    #[test]
+    fn canonical_encoding() {
+        let ping = Ping {
+            nonce: 1,
+            payload: Some(vec![0xaa]),
+        };
+        assert_eq!(
+            serialize(&ping).unwrap(),
+            [1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0xaa]
+        );
+    }
+
+    #[test]
+    fn envelope() {
+        let ping = Ping {
+            nonce: 1,
+            payload: None,
+        };
+        let encoded = encode(&ping).unwrap();
+        assert_eq!(&encoded[..HEADER_SIZE], b"NOMO\x02\xff\xff");
+        assert_eq!(decode::<Ping>(&encoded).unwrap(), ping);
+
+        let mut wrong_version = encoded.clone();
+        wrong_version[4] = VERSION + 1;
+        assert!(matches!(
+            decode::<Ping>(&wrong_version),
+            Err(Error::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            decode::<Ping>(&encoded[HEADER_SIZE..]),
+            Err(Error::Truncated(_))
+        ));
+        assert!(matches!(
+            decode::<Ping>(&serialize(&(MAGIC, VERSION, 0u16, 0u32)).unwrap()),
+            Err(Error::UnexpectedTag { .. })
+        ));
+    }
+
+    #[test]
+    fn size_limit() {
+        let ping = Ping {
+            nonce: 1,
+            payload: Some(vec![0; 32]),
+        };
+        assert!(encode(&ping).is_err());
+        let oversized = [
+            &encoded_header()[..],
+            &serialize_with_limit(&ping, 64).unwrap(),
+        ]
+        .concat();
+        assert!(matches!(
+            decode::<Ping>(&oversized),
+            Err(Error::TooLarge { .. })
+        ));
+    }
+
+    fn encoded_header() -> Vec<u8> {
+        [&MAGIC[..], &[VERSION], &Ping::TAG.to_be_bytes()].concat()
+    }
 }
@@ -0,0 +1,683 @@
+// internal
+use nomos_core::da::{
+    attestation::{self, Attestation as _},
+    blob::{self, BlobHasher},
+    certificate, DaProtocol,
+};
+// std
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+// crates
+use blake2::{
+    digest::{Update, VariableOutput},
+    Blake2bVar,
+};
+use bytes::Bytes;
+use nomos_core::wire;
+use serde::{Deserialize, Serialize};
+
+/// Number of reconstructed data remembered, so that chunks arriving late do not produce it again
+const DECODED_HISTORY: usize = 1024;
+/// Number of dispersals whose chunks are kept until they can be reconstructed, the oldest one
+/// being dropped to make room for a new one
+const MAX_PENDING: usize = 1024;
+
+/// Disperses data as `total_chunks` erasure coded chunks, one per node, any `data_chunks` of
+/// which are enough to reconstruct it.
+///
+/// The data is split into stripes of `data_chunks` chunks of `chunk_size` bytes, the last one
+/// padded with zeros. Each stripe is Reed Solomon encoded into `total_chunks` chunks, and the
+/// blob of a node carries the chunk of the same index of every stripe.
+///
+/// Every blob carries the hashes of the chunks of all blobs, which the dispersal is identified
+/// by, so that chunks can be authenticated on their own.
+#[derive(Debug, Clone)]
+pub struct ErasureCoding<CertificateStrategy> {
+    settings: SettingsConfig,
+    certificate_strategy: CertificateStrategy,
+    pending_chunks: HashMap<[u8; 32], PendingChunks>,
+    pending_order: VecDeque<[u8; 32]>,
+    decoded: VecDeque<[u8; 32]>,
+    output_buffer: Vec<Bytes>,
+    attestations: Vec<Attestation>,
+    output_certificate_buf: Vec<Certificate>,
+}
+
+impl<S> ErasureCoding<S> {
+    pub fn new(settings: Settings, strategy: S) -> Self {
+        Self {
+            settings: settings.config,
+            certificate_strategy: strategy,
+            pending_chunks: HashMap::new(),
+            pending_order: VecDeque::new(),
+            decoded: VecDeque::new(),
+            output_buffer: Vec::new(),
+            attestations: Vec::new(),
+            output_certificate_buf: Vec::new(),
+        }
+    }
+
+    // number of bytes of every blob carrying `data_len` bytes of data
+    fn blob_size(&self, data_len: u64) -> usize {
+        let stripe_size = self.settings.chunk_size * self.settings.data_chunks;
+        (data_len as usize).div_ceil(stripe_size).max(1) * self.settings.chunk_size
+    }
+
+    /// Whether the blob has the expected size and its chunks are the ones the dispersal
+    /// commits to
+    fn is_authentic(&self, blob: &Blob) -> bool {
+        blob.chunk_hashes.len() == self.settings.total_chunks
+            && (blob.index as usize) < self.settings.total_chunks
+            && blob.chunks.len() == self.blob_size(blob.data_len)
+            && hash(&blob.chunks) == blob.chunk_hashes[blob.index as usize]
+            && commitment(blob.content_hash, blob.data_len, &blob.chunk_hashes) == blob.data_hash
+    }
+
+    fn encode_data(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        let SettingsConfig {
+            chunk_size,
+            data_chunks,
+            total_chunks,
+            ..
+        } = self.settings;
+        let blob_size = self.blob_size(data.len() as u64);
+        let mut padded = data.to_vec();
+        padded.resize(blob_size * data_chunks, 0);
+        let mut shards = vec![Vec::with_capacity(blob_size); data_chunks];
+        for (i, chunk) in padded.chunks(chunk_size).enumerate() {
+            shards[i % data_chunks].extend_from_slice(chunk);
+        }
+        reed_solomon::encode_shards(shards, total_chunks - data_chunks)
+            .expect("settings are checked on creation")
+    }
+
+    /// Reconstruct the data from the chunks of the blobs at `indexes`
+    fn decode_data(&self, blobs: &[Option<Blob>], indexes: &[usize]) -> Option<Bytes> {
+        let SettingsConfig {
+            chunk_size,
+            data_chunks,
+            total_chunks,
+            ..
+        } = self.settings;
+        let any = blobs[*indexes.first()?].as_ref()?;
+        let (content_hash, data_len) = (any.content_hash, any.data_len);
+        let mut shards = vec![None; total_chunks];
+        for &index in indexes {
+            shards[index] = blobs[index].as_ref().map(|blob| blob.chunks.to_vec());
+        }
+        let shards = reed_solomon::decode_shards(data_chunks, shards).ok()?;
+
+        let blob_size = self.blob_size(data_len);
+        let mut data = Vec::with_capacity(blob_size * data_chunks);
+        for offset in (0..blob_size).step_by(chunk_size) {
+            for shard in &shards {
+                data.extend_from_slice(&shard[offset..offset + chunk_size]);
+            }
+        }
+        data.truncate(data_len as usize);
+        // the disperser may have committed to chunks that do not encode the same data
+        (hash(&data) == content_hash).then(|| data.into())
+    }
+
+    fn remember_decoded(&mut self, data_hash: [u8; 32]) {
+        self.decoded.push_back(data_hash);
+        if self.decoded.len() > DECODED_HISTORY {
+            self.decoded.pop_front();
+        }
+    }
+}
+
+/// Authenticated chunks of a dispersal that was not reconstructed yet
+#[derive(Debug, Clone)]
+struct PendingChunks {
+    blobs: Vec<Option<Blob>>,
+    /// Indexes of the received blobs, in the order they arrived
+    received: Vec<usize>,
+}
+
+/// Reed Solomon over 8 bits elements
+const MAX_CHUNKS: usize = 256;
+
+// TODO: maybe abstract in a general library?
+trait CertificateStrategy {
+    type Attestation: attestation::Attestation;
+    type Certificate: certificate::Certificate;
+
+    fn can_build(&self, attestations: &[Self::Attestation]) -> bool;
+    fn build(&self, attestations: Vec<Self::Attestation>) -> Certificate;
+}
+
+#[derive(Debug, Clone)]
+pub struct AbsoluteNumber<A, C> {
+    num_attestations: usize,
+    _a: std::marker::PhantomData<A>,
+    _c: std::marker::PhantomData<C>,
+}
+
+impl<A, C> AbsoluteNumber<A, C> {
+    pub fn new(num_attestations: usize) -> Self {
+        Self {
+            num_attestations,
+            _a: std::marker::PhantomData,
+            _c: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Settings as written in the node config, see [`Settings`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsConfig {
+    /// Size in bytes of the chunks every stripe of data is split into
+    pub chunk_size: usize,
+    /// Number of chunks needed to reconstruct the data
+    pub data_chunks: usize,
+    /// Number of chunks the data is dispersed as, one per node
+    pub total_chunks: usize,
+    /// Number of nodes attesting their chunk needed to certify a dispersal.
+    /// At least `data_chunks` are always required, so that certified data can be reconstructed.
+    pub num_attestations: usize,
+    /// Key this node is known by, put in the attestations it makes
+    pub voter: [u8; 32],
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("Chunks should have at least one byte")]
+    EmptyChunks,
+    #[error("There should be at least one data chunk and one parity chunk, not {data_chunks} data chunks out of {total_chunks}")]
+    InvalidDataChunks {
+        data_chunks: usize,
+        total_chunks: usize,
+    },
+    #[error("Reed Solomon encoding supports up to {MAX_CHUNKS} chunks, not {0}")]
+    TooManyChunks(usize),
+}
+
+/// Checked settings, so that a bad config is reported while loading it rather than when the
+/// protocol is built.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "SettingsConfig", into = "SettingsConfig")]
+pub struct Settings {
+    config: SettingsConfig,
+}
+
+impl Settings {
+    pub fn new(config: SettingsConfig) -> Result<Self, SettingsError> {
+        let SettingsConfig {
+            chunk_size,
+            data_chunks,
+            total_chunks,
+            ..
+        } = config;
+        if chunk_size == 0 {
+            return Err(SettingsError::EmptyChunks);
+        }
+        if data_chunks == 0 || data_chunks >= total_chunks {
+            return Err(SettingsError::InvalidDataChunks {
+                data_chunks,
+                total_chunks,
+            });
+        }
+        if total_chunks > MAX_CHUNKS {
+            return Err(SettingsError::TooManyChunks(total_chunks));
+        }
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &SettingsConfig {
+        &self.config
+    }
+}
+
+impl TryFrom<SettingsConfig> for Settings {
+    type Error = SettingsError;
+
+    fn try_from(config: SettingsConfig) -> Result<Self, Self::Error> {
+        Self::new(config)
+    }
+}
+
+impl From<Settings> for SettingsConfig {
+    fn from(settings: Settings) -> Self {
+        settings.config
+    }
+}
+
+impl CertificateStrategy for AbsoluteNumber<Attestation, Certificate> {
+    type Attestation = Attestation;
+    type Certificate = Certificate;
+
+    // every attestation should be about a different chunk of the same data
+    fn can_build(&self, attestations: &[Self::Attestation]) -> bool {
+        attestations
+            .iter()
+            .map(|a| &a.data)
+            .collect::<HashSet<_>>()
+            .len()
+            == 1
+            && attestations
+                .iter()
+                .map(|a| &a.blob)
+                .collect::<HashSet<_>>()
+                .len()
+                >= self.num_attestations
+    }
+
+    fn build(&self, attestations: Vec<Self::Attestation>) -> Certificate {
+        assert!(self.can_build(&attestations));
+        Certificate { attestations }
+    }
+}
+
+/// A chunk of every stripe of some data, dispersed to a single node
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, Hash, PartialEq)]
+pub struct Blob {
+    /// Commitment to the content, size and chunks of the data, shared by the blobs of the same
+    /// dispersal
+    data_hash: [u8; 32],
+    /// Hash of the whole data
+    content_hash: [u8; 32],
+    /// Size of the data, without padding
+    data_len: u64,
+    /// Hash of the chunks of every blob of the dispersal, in order
+    chunk_hashes: Vec<[u8; 32]>,
+    /// Index of the node the blob is for, among `total_chunks`
+    index: u16,
+    chunks: Bytes,
+}
+
+impl Blob {
+    pub fn data_hash(&self) -> [u8; 32] {
+        self.data_hash
+    }
+
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+fn hasher(blob: &Blob) -> [u8; 32] {
+    let mut hasher = Blake2bVar::new(32).unwrap();
+    // the data hash commits to every other field but the chunks, which are authenticated by it
+    hasher.update(&blob.data_hash);
+    hasher.update(&blob.index.to_be_bytes());
+    hasher.update(&blob.chunks);
+    let mut output = [0; 32];
+    hasher.finalize_variable(&mut output).unwrap();
+    output
+}
+
+impl blob::Blob for Blob {
+    const HASHER: BlobHasher<Self> = hasher as BlobHasher<Self>;
+    type Hash = [u8; 32];
+
+    fn as_bytes(&self) -> bytes::Bytes {
+        wire::serialize(self)
+            .expect("Blob shouldn't fail to be serialized")
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Attestation {
+    blob: [u8; 32],
+    data: [u8; 32],
+    voter: [u8; 32],
+}
+
+impl attestation::Attestation for Attestation {
+    type Blob = Blob;
+    fn blob(&self) -> [u8; 32] {
+        self.blob
+    }
+
+    fn hash(&self) -> <Self::Blob as blob::Blob>::Hash {
+        hash([self.blob, self.data, self.voter].concat())
+    }
+
+    fn as_bytes(&self) -> Bytes {
+        wire::serialize(self)
+            .expect("Attestation shouldn't fail to be serialized")
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Certificate {
+    attestations: Vec<Attestation>,
+}
+
+impl Hash for Certificate {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(certificate::Certificate::as_bytes(self).as_ref());
+    }
+}
+
+impl certificate::Certificate for Certificate {
+    type Blob = Blob;
+
+    /// Commitment to the dispersed data, since every attestation is about a different blob
+    fn blob(&self) -> <Self::Blob as blob::Blob>::Hash {
+        let Attestation { data, .. } = self.attestations[0];
+        data
+    }
+
+    fn hash(&self) -> <Self::Blob as blob::Blob>::Hash {
+        let mut input = self
+            .attestations
+            .iter()
+            .map(|a| a.hash())
+            .collect::<Vec<_>>();
+        // sort to make the hash deterministic
+        input.sort();
+        hash(input.concat())
+    }
+
+    fn as_bytes(&self) -> Bytes {
+        wire::serialize(self)
+            .expect("Certificate shouldn't fail to be serialized")
+            .into()
+    }
+}
+
+// TODO: add generic impl when the trait for Certificate is expanded
+impl DaProtocol for ErasureCoding<AbsoluteNumber<Attestation, Certificate>> {
+    type Blob = Blob;
+    type Attestation = Attestation;
+    type Certificate = Certificate;
+    type Settings = Settings;
+
+    fn new(settings: Self::Settings) -> Self {
+        let config = settings.config();
+        let num_attestations = config.num_attestations.max(config.data_chunks);
+        Self::new(settings, AbsoluteNumber::new(num_attestations))
+    }
+
+    /// Returns the blob of every node, in order
+    fn encode<T: AsRef<[u8]>>(&self, data: T) -> Vec<Self::Blob> {
+        let data = data.as_ref();
+        let content_hash = hash(data);
+        let data_len = data.len() as u64;
+        let encoded = self.encode_data(data);
+        let chunk_hashes: Vec<_> = encoded.iter().map(hash).collect();
+        let data_hash = commitment(content_hash, data_len, &chunk_hashes);
+        encoded
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunks)| Blob {
+                data_hash,
+                content_hash,
+                data_len,
+                chunk_hashes: chunk_hashes.clone(),
+                index: index as u16,
+                chunks: chunks.into(),
+            })
+            .collect()
+    }
+
+    fn recv_blob(&mut self, blob: Self::Blob) {
+        if !self.is_authentic(&blob) || self.decoded.contains(&blob.data_hash) {
+            return;
+        }
+        let index = blob.index as usize;
+        let data_hash = blob.data_hash;
+        if !self.pending_chunks.contains_key(&data_hash) {
+            if self.pending_order.len() >= MAX_PENDING {
+                let oldest = self
+                    .pending_order
+                    .pop_front()
+                    .expect("pending is not empty");
+                self.pending_chunks.remove(&oldest);
+            }
+            self.pending_order.push_back(data_hash);
+            self.pending_chunks.insert(
+                data_hash,
+                PendingChunks {
+                    blobs: vec![None; self.settings.total_chunks],
+                    received: Vec::new(),
+                },
+            );
+        }
+        let pending = self
+            .pending_chunks
+            .get_mut(&data_hash)
+            .expect("pending chunks were just inserted");
+        if pending.blobs[index].is_some() {
+            return;
+        }
+        pending.blobs[index] = Some(blob);
+        pending.received.push(index);
+        let data_chunks = self.settings.data_chunks;
+        if pending.received.len() < data_chunks {
+            return;
+        }
+
+        // Chunks are authenticated, but may not encode the same data if the disperser is
+        // faulty. Every new chunk is tried along with the ones received just before it, so
+        // that chunks which do not match the others eventually stop being used.
+        let pending = &self.pending_chunks[&data_hash];
+        let latest = &pending.received[pending.received.len() - data_chunks..];
+        if let Some(data) = self.decode_data(&pending.blobs, latest) {
+            self.pending_chunks.remove(&data_hash);
+            self.pending_order.retain(|hash| *hash != data_hash);
+            self.output_buffer.push(data);
+            self.remember_decoded(data_hash);
+        }
+    }
+
+    fn extract(&mut self) -> Option<Bytes> {
+        self.output_buffer.pop()
+    }
+
+    fn attest(&self, blob: &Self::Blob) -> Option<Self::Attestation> {
+        self.is_authentic(blob).then(|| Attestation {
+            blob: hasher(blob),
+            data: blob.data_hash,
+            voter: self.settings.voter,
+        })
+    }
+
+    fn validate_attestation(&self, blob: &Self::Blob, attestation: &Self::Attestation) -> bool {
+        hasher(blob) == attestation.blob && blob.data_hash == attestation.data
+    }
+
+    fn recv_attestation(&mut self, attestation: Self::Attestation) {
+        self.attestations.push(attestation);
+        if self.certificate_strategy.can_build(&self.attestations) {
+            self.output_certificate_buf.push(
+                self.certificate_strategy
+                    .build(std::mem::take(&mut self.attestations)),
+            );
+        }
+    }
+
+    fn certify_dispersal(&mut self) -> Option<Self::Certificate> {
+        self.output_certificate_buf.pop()
+    }
+
+    fn validate_certificate(&self, certificate: &Self::Certificate) -> bool {
+        self.certificate_strategy
+            .can_build(&certificate.attestations)
+    }
+}
+
+/// Identifies a dispersal by its content, size and the chunks it was encoded into
+fn commitment(content_hash: [u8; 32], data_len: u64, chunk_hashes: &[[u8; 32]]) -> [u8; 32] {
+    let mut hasher = Blake2bVar::new(32).unwrap();
+    hasher.update(&content_hash);
+    hasher.update(&data_len.to_be_bytes());
+    for chunk_hash in chunk_hashes {
+        hasher.update(chunk_hash);
+    }
+    let mut output = [0; 32];
+    hasher.finalize_variable(&mut output).unwrap();
+    output
+}
+
+fn hash(item: impl AsRef<[u8]>) -> [u8; 32] {
+    let mut hasher = Blake2bVar::new(32).unwrap();
+    hasher.update(item.as_ref());
+    let mut output = [0; 32];
+    hasher.finalize_variable(&mut output).unwrap();
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nomos_core::da::certificate::Certificate as _;
+
+    type Protocol = ErasureCoding<AbsoluteNumber<Attestation, Certificate>>;
+
+    fn config() -> SettingsConfig {
+        SettingsConfig {
+            chunk_size: 4,
+            data_chunks: 3,
+            total_chunks: 5,
+            num_attestations: 1,
+            voter: [1; 32],
+        }
+    }
+
+    fn protocol() -> Protocol {
+        <Protocol as DaProtocol>::new(Settings::new(config()).unwrap())
+    }
+
+    #[test]
+    fn reject_invalid_settings() {
+        for (config, valid) in [
+            (config(), true),
+            (
+                SettingsConfig {
+                    chunk_size: 0,
+                    ..config()
+                },
+                false,
+            ),
+            (
+                SettingsConfig {
+                    data_chunks: 0,
+                    ..config()
+                },
+                false,
+            ),
+            // no parity chunk
+            (
+                SettingsConfig {
+                    data_chunks: 5,
+                    ..config()
+                },
+                false,
+            ),
+            (
+                SettingsConfig {
+                    total_chunks: MAX_CHUNKS + 1,
+                    ..config()
+                },
+                false,
+            ),
+        ] {
+            assert_eq!(Settings::new(config).is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn reconstruct_from_any_data_chunks() {
+        let data: Vec<u8> = (0..30).collect();
+        let blobs = protocol().encode(&data);
+        assert_eq!(blobs.len(), 5);
+        // 30 bytes take 3 stripes of 3 chunks of 4 bytes
+        assert!(blobs.iter().all(|blob| blob.chunks.len() == 12));
+
+        let mut receiver = protocol();
+        for blob in blobs.iter().skip(1).step_by(2) {
+            receiver.recv_blob(blob.clone());
+        }
+        assert_eq!(receiver.extract(), None);
+        receiver.recv_blob(blobs[4].clone());
+        assert_eq!(receiver.extract(), Some(Bytes::from(data)));
+
+        // late chunks do not reconstruct the same data again
+        receiver.recv_blob(blobs[0].clone());
+        receiver.recv_blob(blobs[2].clone());
+        receiver.recv_blob(blobs[1].clone());
+        assert_eq!(receiver.extract(), None);
+    }
+
+    #[test]
+    fn reject_tampered_chunks() {
+        let data = b"some data to disperse";
+        let blobs = protocol().encode(data);
+        let mut tampered = blobs[0].clone();
+        let mut chunks = tampered.chunks.to_vec();
+        chunks[0] ^= 1;
+        tampered.chunks = chunks.into();
+        let mut wrong_len = blobs[1].clone();
+        wrong_len.data_len += 1;
+        assert!(protocol().attest(&tampered).is_none());
+
+        let mut receiver = protocol();
+        receiver.recv_blob(tampered);
+        receiver.recv_blob(wrong_len);
+        receiver.recv_blob(blobs[2].clone());
+        receiver.recv_blob(blobs[3].clone());
+        assert_eq!(receiver.extract(), None);
+        // the honest chunks are kept
+        receiver.recv_blob(blobs[4].clone());
+        assert_eq!(receiver.extract(), Some(Bytes::from_static(data)));
+    }
+
+    #[test]
+    fn skip_chunks_not_encoding_the_data() {
+        let data = b"some data to disperse";
+        let mut blobs = protocol().encode(data);
+        // a faulty disperser commits to a chunk that does not encode the data
+        let mut chunks = blobs[0].chunks.to_vec();
+        chunks[0] ^= 1;
+        blobs[0].chunks = chunks.into();
+        let chunk_hashes: Vec<_> = blobs.iter().map(|blob| hash(&blob.chunks)).collect();
+        let data_hash = commitment(hash(data), data.len() as u64, &chunk_hashes);
+        for blob in &mut blobs {
+            blob.chunk_hashes = chunk_hashes.clone();
+            blob.data_hash = data_hash;
+        }
+
+        let mut receiver = protocol();
+        for blob in &blobs[..3] {
+            receiver.recv_blob(blob.clone());
+        }
+        assert_eq!(receiver.extract(), None);
+        receiver.recv_blob(blobs[3].clone());
+        assert_eq!(receiver.extract(), Some(Bytes::from_static(data)));
+    }
+
+    #[test]
+    fn bounded_pending_chunks() {
+        let mut receiver = protocol();
+        for i in 0..MAX_PENDING as u32 + 1 {
+            receiver.recv_blob(protocol().encode(i.to_be_bytes()).swap_remove(0));
+        }
+        assert_eq!(receiver.pending_chunks.len(), MAX_PENDING);
+        assert_eq!(receiver.pending_order.len(), MAX_PENDING);
+    }
+
+    #[test]
+    fn certify_with_enough_chunks() {
+        let blobs = protocol().encode(b"data");
+        let mut disperser = protocol();
+        for blob in &blobs[..2] {
+            let attestation = protocol().attest(blob).unwrap();
+            assert!(disperser.validate_attestation(blob, &attestation));
+            disperser.recv_attestation(attestation);
+        }
+        // not enough to reconstruct the data yet
+        assert!(disperser.certify_dispersal().is_none());
+
+        let attestation = protocol().attest(&blobs[2]).unwrap();
+        assert_eq!(attestation.voter, config().voter);
+        disperser.recv_attestation(attestation);
+        let certificate = disperser.certify_dispersal().unwrap();
+        assert_eq!(certificate.blob(), blobs[0].data_hash());
+        assert!(disperser.validate_certificate(&certificate));
+    }
+}
@@ -193,12 +193,12 @@ impl DaProtocol for FullReplication<AbsoluteNumber<Attestation, Certificate>> {
         self.output_buffer.pop()
     }
 
-    fn attest(&self, blob: &Self::Blob) -> Self::Attestation {
-        Attestation {
+    fn attest(&self, blob: &Self::Blob) -> Option<Self::Attestation> {
+        Some(Attestation {
             blob: hasher(blob),
             // TODO: voter id?
             voter: [0; 32],
-        }
+        })
     }
 
     fn validate_attestation(&self, blob: &Self::Blob, attestation: &Self::Attestation) -> bool {
@@ -0,0 +1,578 @@
+// internal
+use nomos_core::da::{
+    attestation,
+    blob::{self, BlobHasher},
+    certificate, DaProtocol,
+};
+use nomos_kzg::{Commitment, KzgSettings, Proof};
+// std
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Arc;
+// crates
+use blake2::{
+    digest::{Update, VariableOutput},
+    Blake2bVar,
+};
+use bls_signatures::{PrivateKey, PublicKey, Serialize as _, Signature};
+use bytes::Bytes;
+use nomos_core::wire;
+use serde::{Deserialize, Serialize};
+
+const ATTESTATION_TAG: &[u8] = b"NOMOS_DA_ATTESTATION";
+/// Data bytes carried by a field element, the most significant byte being left zeroed so that
+/// every element is smaller than the field modulus
+const BYTES_PER_ELEMENT: usize = 31;
+/// Largest blob the kzg challenge is computed for
+const MAX_CHUNK_ELEMENTS: usize = 4096;
+
+/// Replicates data to every node along with kzg commitments to its chunks.
+///
+/// Nodes only attest data whose chunks match their commitments, signing attestations with
+/// their key, and certificates aggregate the signatures of enough attesters.
+#[derive(Clone)]
+pub struct KzgReplication<CertificateStrategy> {
+    certificate_strategy: CertificateStrategy,
+    chunk_elements: usize,
+    kzg_settings: Arc<KzgSettings>,
+    private_key: PrivateKey,
+    attesters: HashSet<Box<[u8]>>,
+    output_buffer: Vec<Bytes>,
+    attestations: Vec<Attestation>,
+    output_certificate_buf: Vec<Certificate>,
+}
+
+impl<S> KzgReplication<S> {
+    pub fn new(settings: Settings, strategy: S) -> Self {
+        let Settings {
+            config,
+            kzg_settings,
+        } = settings;
+        Self {
+            certificate_strategy: strategy,
+            chunk_elements: config.chunk_elements,
+            kzg_settings,
+            private_key: PrivateKey::new(config.private_key),
+            attesters: config.attesters.into_iter().collect(),
+            output_buffer: Vec::new(),
+            attestations: Vec::new(),
+            output_certificate_buf: Vec::new(),
+        }
+    }
+
+    /// Splits data into chunks of field elements, the last one padded with zeros
+    fn chunks(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        let chunk_size = self.chunk_elements * BYTES_PER_ELEMENT;
+        let mut chunks: Vec<_> = data.chunks(chunk_size).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        chunks
+            .into_iter()
+            .map(|chunk| {
+                let mut chunk = chunk.to_vec();
+                chunk.resize(chunk_size, 0);
+                chunk
+                    .chunks(BYTES_PER_ELEMENT)
+                    .flat_map(|element| std::iter::once(&0).chain(element))
+                    .copied()
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Checks that every chunk of the blob data matches its commitment
+    fn verify_blob(&self, blob: &Blob) -> bool {
+        let chunks = self.chunks(&blob.data);
+        chunks.len() == blob.chunks.len()
+            && chunks.iter().zip(&blob.chunks).all(|(data, chunk)| {
+                let (Ok(commitment), Ok(proof)) = (
+                    Commitment::from_bytes(&chunk.commitment),
+                    Proof::from_bytes(&chunk.proof),
+                ) else {
+                    return false;
+                };
+                nomos_kzg::verify_blob(data, &proof, &commitment, &self.kzg_settings)
+                    .unwrap_or(false)
+            })
+    }
+
+    /// Checks that the attestation was signed by one of the attesters
+    fn verify_signature(&self, attestation: &Attestation) -> bool {
+        if !self.attesters.contains(&attestation.voter) {
+            return false;
+        }
+        let (Ok(public_key), Ok(signature)) = (
+            PublicKey::from_bytes(&attestation.voter),
+            Signature::from_bytes(&attestation.signature),
+        ) else {
+            return false;
+        };
+        public_key.verify(
+            signature,
+            attestation_payload(&attestation.blob, &attestation.voter),
+        )
+    }
+}
+
+/// Bytes signed by a node when attesting a blob.
+///
+/// The voter is part of the payload so that every aggregated message is distinct.
+fn attestation_payload(blob: &[u8; 32], voter: &[u8]) -> Vec<u8> {
+    [ATTESTATION_TAG, blob, voter].concat()
+}
+
+// TODO: maybe abstract in a general library?
+trait CertificateStrategy {
+    type Attestation: attestation::Attestation;
+    type Certificate: certificate::Certificate;
+
+    fn can_build(&self, attestations: &[Self::Attestation]) -> bool;
+    fn build(&self, attestations: Vec<Self::Attestation>) -> Certificate;
+}
+
+#[derive(Debug, Clone)]
+pub struct AbsoluteNumber<A, C> {
+    num_attestations: usize,
+    _a: std::marker::PhantomData<A>,
+    _c: std::marker::PhantomData<C>,
+}
+
+impl<A, C> AbsoluteNumber<A, C> {
+    pub fn new(num_attestations: usize) -> Self {
+        Self {
+            num_attestations,
+            _a: std::marker::PhantomData,
+            _c: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Settings as written in the node config, see [`Settings`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsConfig {
+    pub num_attestations: usize,
+    /// Number of field elements of every committed chunk, a power of two of at most 4096
+    pub chunk_elements: usize,
+    /// File holding the public trusted setup, see [`KzgSettings::from_trusted_setup_file`]
+    pub trusted_setup: PathBuf,
+    /// Seed of the key this node signs its attestations with
+    pub private_key: [u8; 32],
+    /// Public keys of the nodes whose attestations count towards certificates
+    pub attesters: Vec<Box<[u8]>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("Chunks should have a power of two of at most {MAX_CHUNK_ELEMENTS} elements, not {0}")]
+    InvalidChunkElements(usize),
+    #[error("Could not load the trusted setup from {path:?}: {message}")]
+    TrustedSetup { path: PathBuf, message: String },
+}
+
+/// Checked settings, along with the trusted setup they point to.
+///
+/// The trusted setup is loaded when the settings are deserialized, so that a bad config is
+/// reported while loading it rather than when the protocol is built.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "SettingsConfig", into = "SettingsConfig")]
+pub struct Settings {
+    config: SettingsConfig,
+    kzg_settings: Arc<KzgSettings>,
+}
+
+impl Settings {
+    /// Settings using kzg settings that were already loaded instead of the trusted setup file
+    pub fn with_kzg_settings(
+        config: SettingsConfig,
+        kzg_settings: KzgSettings,
+    ) -> Result<Self, SettingsError> {
+        check_chunk_elements(config.chunk_elements)?;
+        Ok(Self {
+            config,
+            kzg_settings: Arc::new(kzg_settings),
+        })
+    }
+
+    pub fn config(&self) -> &SettingsConfig {
+        &self.config
+    }
+}
+
+fn check_chunk_elements(chunk_elements: usize) -> Result<(), SettingsError> {
+    if !chunk_elements.is_power_of_two() || chunk_elements > MAX_CHUNK_ELEMENTS {
+        return Err(SettingsError::InvalidChunkElements(chunk_elements));
+    }
+    Ok(())
+}
+
+impl TryFrom<SettingsConfig> for Settings {
+    type Error = SettingsError;
+
+    fn try_from(config: SettingsConfig) -> Result<Self, Self::Error> {
+        check_chunk_elements(config.chunk_elements)?;
+        let kzg_settings =
+            KzgSettings::from_trusted_setup_file(&config.trusted_setup, config.chunk_elements)
+                .map_err(|e| SettingsError::TrustedSetup {
+                    path: config.trusted_setup.clone(),
+                    message: e.to_string(),
+                })?;
+        Self::with_kzg_settings(config, kzg_settings)
+    }
+}
+
+impl From<Settings> for SettingsConfig {
+    fn from(settings: Settings) -> Self {
+        settings.config
+    }
+}
+
+impl std::fmt::Debug for Settings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.config.fmt(f)
+    }
+}
+
+impl CertificateStrategy for AbsoluteNumber<Attestation, Certificate> {
+    type Attestation = Attestation;
+    type Certificate = Certificate;
+
+    fn can_build(&self, attestations: &[Self::Attestation]) -> bool {
+        attestations
+            .iter()
+            .map(|a| &a.blob)
+            .collect::<HashSet<_>>()
+            .len()
+            == 1
+            && attestations
+                .iter()
+                .map(|a| &a.voter)
+                .collect::<HashSet<_>>()
+                .len()
+                >= self.num_attestations
+    }
+
+    fn build(&self, mut attestations: Vec<Self::Attestation>) -> Certificate {
+        assert!(self.can_build(&attestations));
+        attestations.sort_by(|a, b| a.voter.cmp(&b.voter));
+        attestations.dedup_by(|a, b| a.voter == b.voter);
+        let signatures = attestations
+            .iter()
+            .map(|a| {
+                Signature::from_bytes(&a.signature)
+                    .expect("attestations are verified when received")
+            })
+            .collect::<Vec<_>>();
+        let signature = bls_signatures::aggregate(&signatures)
+            .expect("there should be at least one attestation");
+        Certificate {
+            blob: attestations[0].blob,
+            attesters: attestations.into_iter().map(|a| a.voter).collect(),
+            signature: signature.as_bytes().into_boxed_slice(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, Hash, PartialEq)]
+pub struct Blob {
+    data: Bytes,
+    chunks: Vec<Chunk>,
+}
+
+/// Commitment to a chunk of the blob data, and the proof that the data matches it
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, Hash, PartialEq)]
+struct Chunk {
+    commitment: Box<[u8]>,
+    proof: Box<[u8]>,
+}
+
+fn hasher(blob: &Blob) -> [u8; 32] {
+    let mut hasher = Blake2bVar::new(32).unwrap();
+    hasher.update(&blob.data);
+    for chunk in &blob.chunks {
+        hasher.update(&chunk.commitment);
+    }
+    let mut output = [0; 32];
+    hasher.finalize_variable(&mut output).unwrap();
+    output
+}
+
+impl blob::Blob for Blob {
+    const HASHER: BlobHasher<Self> = hasher as BlobHasher<Self>;
+    type Hash = [u8; 32];
+
+    fn as_bytes(&self) -> bytes::Bytes {
+        wire::serialize(self)
+            .expect("Blob shouldn't fail to be serialized")
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Attestation {
+    blob: [u8; 32],
+    /// Public key of the attester
+    voter: Box<[u8]>,
+    signature: Box<[u8]>,
+}
+
+impl attestation::Attestation for Attestation {
+    type Blob = Blob;
+    fn blob(&self) -> [u8; 32] {
+        self.blob
+    }
+
+    fn hash(&self) -> <Self::Blob as blob::Blob>::Hash {
+        hash([&self.blob[..], &self.voter].concat())
+    }
+
+    fn as_bytes(&self) -> Bytes {
+        wire::serialize(self)
+            .expect("Attestation shouldn't fail to be serialized")
+            .into()
+    }
+}
+
+/// Attestations of a blob, as the aggregated signature of the attesters
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Certificate {
+    blob: [u8; 32],
+    /// Public keys of the attesters, sorted
+    attesters: Vec<Box<[u8]>>,
+    signature: Box<[u8]>,
+}
+
+impl Hash for Certificate {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(certificate::Certificate::as_bytes(self).as_ref());
+    }
+}
+
+impl certificate::Certificate for Certificate {
+    type Blob = Blob;
+
+    fn blob(&self) -> <Self::Blob as blob::Blob>::Hash {
+        self.blob
+    }
+
+    fn hash(&self) -> <Self::Blob as blob::Blob>::Hash {
+        hash(certificate::Certificate::as_bytes(self))
+    }
+
+    fn as_bytes(&self) -> Bytes {
+        wire::serialize(self)
+            .expect("Certificate shouldn't fail to be serialized")
+            .into()
+    }
+}
+
+// TODO: add generic impl when the trait for Certificate is expanded
+impl DaProtocol for KzgReplication<AbsoluteNumber<Attestation, Certificate>> {
+    type Blob = Blob;
+    type Attestation = Attestation;
+    type Certificate = Certificate;
+    type Settings = Settings;
+
+    fn new(settings: Self::Settings) -> Self {
+        let num_attestations = settings.config().num_attestations;
+        Self::new(settings, AbsoluteNumber::new(num_attestations))
+    }
+
+    fn encode<T: AsRef<[u8]>>(&self, data: T) -> Vec<Self::Blob> {
+        let chunks = self
+            .chunks(data.as_ref())
+            .iter()
+            .map(|chunk| {
+                let commitment = nomos_kzg::compute_commitment(chunk, &self.kzg_settings)
+                    .expect("chunks are made of valid field elements");
+                let proof = nomos_kzg::compute_blob_proof(chunk, &commitment, &self.kzg_settings)
+                    .expect("commitment was just computed");
+                Chunk {
+                    commitment: commitment.as_bytes_owned().into(),
+                    proof: proof.as_bytes_owned().into(),
+                }
+            })
+            .collect();
+        vec![Blob {
+            data: Bytes::copy_from_slice(data.as_ref()),
+            chunks,
+        }]
+    }
+
+    fn recv_blob(&mut self, blob: Self::Blob) {
+        if self.verify_blob(&blob) {
+            self.output_buffer.push(blob.data);
+        }
+    }
+
+    fn extract(&mut self) -> Option<Bytes> {
+        self.output_buffer.pop()
+    }
+
+    fn attest(&self, blob: &Self::Blob) -> Option<Self::Attestation> {
+        if !self.verify_blob(blob) {
+            return None;
+        }
+        let blob = hasher(blob);
+        let voter = self.private_key.public_key().as_bytes().into_boxed_slice();
+        let signature = self.private_key.sign(attestation_payload(&blob, &voter));
+        Some(Attestation {
+            blob,
+            voter,
+            signature: signature.as_bytes().into_boxed_slice(),
+        })
+    }
+
+    fn validate_attestation(&self, blob: &Self::Blob, attestation: &Self::Attestation) -> bool {
+        hasher(blob) == attestation.blob && self.verify_signature(attestation)
+    }
+
+    fn recv_attestation(&mut self, attestation: Self::Attestation) {
+        if !self.verify_signature(&attestation) {
+            return;
+        }
+        self.attestations.push(attestation);
+        if self.certificate_strategy.can_build(&self.attestations) {
+            self.output_certificate_buf.push(
+                self.certificate_strategy
+                    .build(std::mem::take(&mut self.attestations)),
+            );
+        }
+    }
+
+    fn certify_dispersal(&mut self) -> Option<Self::Certificate> {
+        self.output_certificate_buf.pop()
+    }
+
+    fn validate_certificate(&self, certificate: &Self::Certificate) -> bool {
+        let attesters = &certificate.attesters;
+        // sorted without duplicates
+        if attesters.len() < self.certificate_strategy.num_attestations
+            || attesters.windows(2).any(|pair| pair[0] >= pair[1])
+            || !attesters.iter().all(|a| self.attesters.contains(a))
+        {
+            return false;
+        }
+        let Ok(signature) = Signature::from_bytes(&certificate.signature) else {
+            return false;
+        };
+        let Ok(public_keys) = attesters
+            .iter()
+            .map(|a| PublicKey::from_bytes(a))
+            .collect::<Result<Vec<_>, _>>()
+        else {
+            return false;
+        };
+        let messages = attesters
+            .iter()
+            .map(|a| attestation_payload(&certificate.blob, a))
+            .collect::<Vec<_>>();
+        let messages = messages.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        bls_signatures::verify_messages(&signature, &messages, &public_keys)
+    }
+}
+
+fn hash(item: impl AsRef<[u8]>) -> [u8; 32] {
+    let mut hasher = Blake2bVar::new(32).unwrap();
+    hasher.update(item.as_ref());
+    let mut output = [0; 32];
+    hasher.finalize_variable(&mut output).unwrap();
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Protocol = KzgReplication<AbsoluteNumber<Attestation, Certificate>>;
+
+    fn public_key(seed: u8) -> Box<[u8]> {
+        PrivateKey::new([seed; 32])
+            .public_key()
+            .as_bytes()
+            .into_boxed_slice()
+    }
+
+    fn config(chunk_elements: usize, seed: u8) -> SettingsConfig {
+        SettingsConfig {
+            num_attestations: 2,
+            chunk_elements,
+            trusted_setup: PathBuf::new(),
+            private_key: [seed; 32],
+            attesters: (0..3).map(public_key).collect(),
+        }
+    }
+
+    fn protocol(seed: u8) -> Protocol {
+        let settings = Settings::with_kzg_settings(
+            config(16, seed),
+            KzgSettings::insecure_from_secret(16, [0; 32]).unwrap(),
+        )
+        .unwrap();
+        Protocol::new(settings, AbsoluteNumber::new(2))
+    }
+
+    #[test]
+    fn invalid_settings() {
+        for chunk_elements in [0, 12, 2 * MAX_CHUNK_ELEMENTS] {
+            assert!(matches!(
+                Settings::with_kzg_settings(
+                    config(chunk_elements, 0),
+                    KzgSettings::insecure_from_secret(16, [0; 32]).unwrap(),
+                ),
+                Err(SettingsError::InvalidChunkElements(_))
+            ));
+        }
+        assert!(matches!(
+            Settings::try_from(config(16, 0)),
+            Err(SettingsError::TrustedSetup { .. })
+        ));
+    }
+
+    #[test]
+    fn attest_valid_blobs_only() {
+        let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let mut blobs = protocol(0).encode(&data);
+        assert_eq!(blobs.len(), 1);
+        // 16 elements of 31 bytes per chunk
+        assert_eq!(blobs[0].chunks.len(), 3);
+
+        let mut node = protocol(1);
+        let attestation = node.attest(&blobs[0]).unwrap();
+        assert!(protocol(0).validate_attestation(&blobs[0], &attestation));
+        node.recv_blob(blobs[0].clone());
+        assert_eq!(node.extract(), Some(Bytes::from(data)));
+
+        let mut tampered = blobs[0].data.to_vec();
+        tampered[500] ^= 1;
+        blobs[0].data = tampered.into();
+        assert!(node.attest(&blobs[0]).is_none());
+    }
+
+    #[test]
+    fn aggregate_attestations() {
+        let blob = protocol(0).encode(b"data").remove(0);
+        let mut disperser = protocol(0);
+
+        // not an attester
+        disperser.recv_attestation(protocol(7).attest(&blob).unwrap());
+        // signed by someone else
+        let mut forged = protocol(1).attest(&blob).unwrap();
+        forged.voter = public_key(2);
+        disperser.recv_attestation(forged);
+        disperser.recv_attestation(protocol(1).attest(&blob).unwrap());
+        assert!(disperser.certify_dispersal().is_none());
+
+        disperser.recv_attestation(protocol(2).attest(&blob).unwrap());
+        let mut certificate = disperser.certify_dispersal().unwrap();
+        assert_eq!(
+            certificate::Certificate::blob(&certificate),
+            blob::Blob::hash(&blob)
+        );
+        assert!(protocol(1).validate_certificate(&certificate));
+
+        certificate.attesters.pop();
+        assert!(!protocol(1).validate_certificate(&certificate));
+    }
+}
@@ -42,6 +42,18 @@ pub fn compute_proofs(
     Ok(res)
 }
 
+/// Compute a single kzg proof for the given data, taken as a whole blob.
+/// It works for arbitrary data, but the data must be a multiple of **32 bytes**.
+/// The data is interpreted as a sequence of field elements. Each consisting of **32 bytes**.
+pub fn compute_blob_proof(
+    data: &[u8],
+    commitment: &Commitment,
+    settings: &KzgSettings,
+) -> Result<Proof, Box<dyn Error>> {
+    let blob = Blob::from_bytes(data, settings)?;
+    Ok(Proof(compute_blob_kzg_proof(&blob, commitment, settings)?))
+}
+
 /// Verify a kzg proof for the given blob.
 /// It works for arbitrary data, but the data must be a multiple of **32 bytes**.
 /// The data is interpreted as a sequence of field elements. Each consisting of **32 bytes**.
@@ -79,4 +91,53 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_compute_and_verify_blob_proof() -> Result<(), Box<dyn Error>> {
+        let kzg_settings = KzgSettings::insecure_from_secret(16, [1; 32])?;
+        // keep the first byte of every element zeroed, so that it is in the field
+        let blob: Vec<u8> = (0..16 * 32)
+            .map(|i| if i % 32 == 0 { 0 } else { i as u8 })
+            .collect();
+        let commitment = compute_commitment(&blob, &kzg_settings)?;
+        let proof = compute_blob_proof(&blob, &commitment, &kzg_settings)?;
+        assert!(verify_blob(&blob, &proof, &commitment, &kzg_settings)?);
+
+        let mut tampered = blob.clone();
+        tampered[1] ^= 1;
+        assert!(!verify_blob(&tampered, &proof, &commitment, &kzg_settings)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_trusted_setup_file() -> Result<(), Box<dyn Error>> {
+        use kzg_traits::{G1, G2};
+        let (g1s, g2s) = generate_trusted_setup(32, [1; 32]);
+        let mut contents = format!("{}\n{}\n", g1s.len(), g2s.len());
+        for point in &g1s {
+            contents += &format!("{}\n", hex::encode(point.to_bytes()));
+        }
+        for point in &g2s {
+            contents += &format!("{}\n", hex::encode(point.to_bytes()));
+        }
+        let path = std::env::temp_dir().join("nomos_kzg_trusted_setup.txt");
+        std::fs::write(&path, contents)?;
+
+        // a larger setup can be used for smaller blobs
+        let loaded = KzgSettings::from_trusted_setup_file(&path, 16)?;
+        let generated = KzgSettings::insecure_from_secret(16, [1; 32])?;
+        let blob: Vec<u8> = (0..16 * 32)
+            .map(|i| if i % 32 == 0 { 0 } else { i as u8 })
+            .collect();
+        let commitment = compute_commitment(&blob, &loaded)?;
+        assert_eq!(
+            commitment.as_bytes_owned(),
+            compute_commitment(&blob, &generated)?.as_bytes_owned()
+        );
+        let proof = compute_blob_proof(&blob, &commitment, &loaded)?;
+        assert!(verify_blob(&blob, &proof, &commitment, &generated)?);
+
+        assert!(KzgSettings::from_trusted_setup_file(&path, 64).is_err());
+        Ok(())
+    }
 }
@@ -1,11 +1,15 @@
 use crate::{BYTES_PER_COMMITMENT, BYTES_PER_PROOF};
+use kzg::types::fft_settings::FsFFTSettings;
 use kzg::types::fr::FsFr;
 use kzg::types::g1::FsG1;
+use kzg::types::g2::FsG2;
 use kzg::types::kzg_settings::FsKZGSettings;
-use kzg_traits::{Fr, G1};
+use kzg_traits::{FFTSettings, Fr, KZGSettings, FFTG1, G1, G2};
 use std::error::Error;
+use std::path::Path;
 
 /// A wrapper around the KZG settings that also stores the number of bytes per field element.
+#[derive(Clone)]
 pub struct KzgSettings {
     pub settings: FsKZGSettings,
     pub bytes_per_field_element: usize,
@@ -22,16 +26,96 @@ pub struct Blob {
     pub(crate) inner: Vec<FsFr>,
 }
 
+impl KzgSettings {
+    /// Settings for blobs of `field_elements` elements (a power of two), with the public trusted
+    /// setup read from the file at `path`.
+    ///
+    /// The file lists the number of G1 points and the number of G2 points on its first two
+    /// lines, followed by one hex encoded compressed point per line, G1 points first.
+    /// Points are powers of the setup secret in monomial form, starting from the generator, and
+    /// there should be at least `field_elements` G1 points.
+    pub fn from_trusted_setup_file(
+        path: impl AsRef<Path>,
+        field_elements: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+        let mut count = || -> Result<usize, Box<dyn Error>> {
+            Ok(lines.next().ok_or("Missing number of points")?.parse()?)
+        };
+        let (g1_count, g2_count) = (count()?, count()?);
+        if g1_count < field_elements {
+            return Err(format!(
+                "The trusted setup has {g1_count} G1 points, {field_elements} are needed"
+            )
+            .into());
+        }
+        let mut point = || -> Result<Vec<u8>, Box<dyn Error>> {
+            Ok(hex::decode(lines.next().ok_or("Missing point")?)?)
+        };
+        let g1s = (0..g1_count)
+            .map(|_| Ok(FsG1::from_bytes(&point()?)?))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        let g2s = (0..g2_count)
+            .map(|_| Ok(FsG2::from_bytes(&point()?)?))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        Self::from_setup(field_elements, &g1s[..field_elements], &g2s)
+    }
+
+    /// Settings for blobs of `field_elements` elements (a power of two), with a trusted setup
+    /// generated from `secret`.
+    /// Anyone knowing the secret can forge proofs, so this is only meant for testing.
+    #[cfg(any(test, feature = "insecure"))]
+    pub fn insecure_from_secret(
+        field_elements: usize,
+        secret: [u8; 32],
+    ) -> Result<Self, Box<dyn Error>> {
+        let (g1s, g2s) = kzg::utils::generate_trusted_setup(field_elements, secret);
+        Self::from_setup(field_elements, &g1s, &g2s)
+    }
+
+    fn from_setup(
+        field_elements: usize,
+        g1s: &[FsG1],
+        g2s: &[FsG2],
+    ) -> Result<Self, Box<dyn Error>> {
+        if field_elements < 2 || !field_elements.is_power_of_two() {
+            return Err("The number of field elements should be a power of two".into());
+        }
+        let scale = field_elements.trailing_zeros();
+        let fft_settings = FsFFTSettings::new(scale as usize)?;
+        // Blobs are in evaluation form over the roots of unity, in bit reversed order,
+        // so commitments are computed with the matching lagrange basis.
+        let lagrange = fft_settings.fft_g1(g1s, true)?;
+        let lagrange: Vec<FsG1> = (0..field_elements)
+            .map(|i| lagrange[i.reverse_bits() >> (usize::BITS - scale)])
+            .collect();
+        let settings = FsKZGSettings::new(&lagrange, g2s, field_elements, &fft_settings)?;
+        Ok(Self {
+            settings,
+            bytes_per_field_element: 32,
+        })
+    }
+}
+
 impl Commitment {
     pub fn as_bytes_owned(&self) -> [u8; BYTES_PER_COMMITMENT] {
         self.0.to_bytes()
     }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Ok(Self(FsG1::from_bytes(bytes)?))
+    }
 }
 
 impl Proof {
     pub fn as_bytes_owned(&self) -> [u8; BYTES_PER_PROOF] {
         self.0.to_bytes()
     }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Ok(Self(FsG1::from_bytes(bytes)?))
+    }
 }
 
 impl Blob {
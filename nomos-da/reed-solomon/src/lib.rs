@@ -37,6 +37,35 @@ pub fn decode_from_elements(
         .collect())
 }
 
+/// Reed Solomon encode shards of the same size, appending the parity shards
+/// # Arguments
+/// * `data` - Original shards
+/// * `parity_shards` - Number of parity shards to append
+pub fn encode_shards(mut data: Vec<Vec<u8>>, parity_shards: usize) -> Result<Vec<Vec<u8>>, Error> {
+    let encoder = ReedSolomon::new(data.len(), parity_shards)?;
+    let shard_size = data.first().map(Vec::len).unwrap_or_default();
+    data.resize(data.len() + parity_shards, vec![0; shard_size]);
+    encoder.encode(&mut data)?;
+    Ok(data)
+}
+
+/// Reed Solomon decode the original shards from any `data_shards` of the encoded ones
+/// # Arguments
+/// * `data_shards` - Number of original shards (must be the same as the one used for encoding)
+/// * `shards` - Encoded shards, missing ones being `None`
+pub fn decode_shards(
+    data_shards: usize,
+    mut shards: Vec<Option<Vec<u8>>>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let decoder = ReedSolomon::new(data_shards, shards.len().saturating_sub(data_shards))?;
+    decoder.reconstruct_data(&mut shards)?;
+    Ok(shards
+        .into_iter()
+        .take(data_shards)
+        .map(|shard| shard.expect("data shards are reconstructed"))
+        .collect())
+}
+
 #[cfg(test)]
 mod test {
     use reed_solomon_erasure::Error;
@@ -70,4 +99,23 @@ mod test {
         let decoded = super::decode_from_elements(8, 1, &encoded);
         assert!(matches!(decoded, Err(Error::TooFewShardsPresent)));
     }
+
+    #[test]
+    fn decode_shards_from_any_subset() {
+        let data = vec![vec![1, 2], vec![3, 4], vec![5, 6]];
+        let encoded = super::encode_shards(data.clone(), 2).unwrap();
+        assert_eq!(encoded.len(), 5);
+        assert_eq!(&encoded[..3], &data);
+
+        let mut shards: Vec<_> = encoded.into_iter().map(Some).collect();
+        shards[0] = None;
+        shards[2] = None;
+        assert_eq!(super::decode_shards(3, shards.clone()).unwrap(), data);
+
+        shards[4] = None;
+        assert!(matches!(
+            super::decode_shards(3, shards),
+            Err(Error::TooFewShardsPresent)
+        ));
+    }
 }
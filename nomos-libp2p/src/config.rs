@@ -1,20 +1,44 @@
 use std::time::Duration;
 
 use libp2p::{gossipsub, identity::secp256k1};
+use multiaddr::{multiaddr, Multiaddr};
 use serde::{Deserialize, Serialize};
 
+/// Transport used to connect to other peers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportKind {
+    /// TCP connections secured with Noise and multiplexed with Yamux
+    #[default]
+    Tcp,
+    /// QUIC connections, secured with TLS 1.3 and natively multiplexed
+    Quic,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwarmConfig {
     // Listening IPv4 address
     pub host: std::net::Ipv4Addr,
-    // TCP listening port. Use 0 for random
+    // TCP (or UDP for QUIC) listening port. Use 0 for random
     pub port: u16,
+    // Transport used both to listen and dial. Default TCP
+    #[serde(default)]
+    pub transport: TransportKind,
     // Secp256k1 private key in Hex format (`0x123...abc`). Default random
     #[serde(with = "secret_key_serde", default = "secp256k1::SecretKey::generate")]
     pub node_key: secp256k1::SecretKey,
     // Gossipsub config
     #[serde(with = "GossipsubConfigDef", default = "gossipsub::Config::default")]
     pub gossipsub_config: gossipsub::Config,
+    // Gossipsub peer scoring
+    #[serde(default)]
+    pub peer_score: PeerScoreConfig,
+    // Interval between Kademlia bootstraps, refreshing the routing table
+    #[serde(default = "SwarmConfig::default_bootstrap_interval")]
+    pub bootstrap_interval: Duration,
+    // Interval between pings checking that connected peers are alive
+    #[serde(default = "SwarmConfig::default_ping_interval")]
+    pub ping_interval: Duration,
 }
 
 impl Default for SwarmConfig {
@@ -22,8 +46,111 @@ impl Default for SwarmConfig {
         Self {
             host: std::net::Ipv4Addr::new(0, 0, 0, 0),
             port: 60000,
+            transport: TransportKind::default(),
             node_key: secp256k1::SecretKey::generate(),
             gossipsub_config: gossipsub::Config::default(),
+            peer_score: PeerScoreConfig::default(),
+            bootstrap_interval: Self::default_bootstrap_interval(),
+            ping_interval: Self::default_ping_interval(),
+        }
+    }
+}
+
+impl SwarmConfig {
+    fn default_bootstrap_interval() -> Duration {
+        Duration::from_secs(5 * 60)
+    }
+
+    fn default_ping_interval() -> Duration {
+        Duration::from_secs(15)
+    }
+
+    /// Address the swarm listens on, according to the chosen transport
+    pub fn listen_address(&self) -> Multiaddr {
+        match self.transport {
+            TransportKind::Tcp => multiaddr!(Ip4(self.host), Tcp(self.port)),
+            TransportKind::Quic => multiaddr!(Ip4(self.host), Udp(self.port), QuicV1),
+        }
+    }
+}
+
+/// Gossipsub peer scoring, penalising peers relaying messages that fail validation.
+///
+/// Peers with a negative score are pruned from the mesh of every topic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerScoreConfig {
+    // Penalty applied to the square of the number of invalid messages relayed by a peer
+    pub invalid_message_weight: f64,
+    // Factor applied to the number of invalid messages of each peer every `decay_interval`
+    pub invalid_message_decay: f64,
+    pub decay_interval: Duration,
+    // Below this score, no gossip is exchanged with a peer
+    pub gossip_threshold: f64,
+    // Below this score, no message is published to a peer
+    pub publish_threshold: f64,
+    // Below this score, every message from a peer is ignored
+    pub graylist_threshold: f64,
+    // Added to the score of a peer every time it answers a request with invalid data
+    #[serde(default = "PeerScoreConfig::default_invalid_response_penalty")]
+    pub invalid_response_penalty: f64,
+    // Factor applied to the invalid response penalties of each peer every `decay_interval`
+    #[serde(default = "PeerScoreConfig::default_invalid_response_decay")]
+    pub invalid_response_decay: f64,
+}
+
+impl Default for PeerScoreConfig {
+    fn default() -> Self {
+        Self {
+            invalid_message_weight: -10.0,
+            invalid_message_decay: 0.99,
+            decay_interval: Duration::from_secs(1),
+            gossip_threshold: -10.0,
+            publish_threshold: -50.0,
+            graylist_threshold: -80.0,
+            invalid_response_penalty: Self::default_invalid_response_penalty(),
+            invalid_response_decay: Self::default_invalid_response_decay(),
+        }
+    }
+}
+
+impl PeerScoreConfig {
+    const fn default_invalid_response_penalty() -> f64 {
+        -20.0
+    }
+
+    const fn default_invalid_response_decay() -> f64 {
+        0.99
+    }
+
+    pub fn params(&self) -> gossipsub::PeerScoreParams {
+        gossipsub::PeerScoreParams {
+            decay_interval: self.decay_interval,
+            // penalties from `Swarm::penalize` are applied as is, and decayed by the swarm
+            app_specific_weight: 1.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn thresholds(&self) -> gossipsub::PeerScoreThresholds {
+        gossipsub::PeerScoreThresholds {
+            gossip_threshold: self.gossip_threshold,
+            publish_threshold: self.publish_threshold,
+            graylist_threshold: self.graylist_threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Scoring of every subscribed topic
+    pub fn topic_params(&self) -> gossipsub::TopicScoreParams {
+        gossipsub::TopicScoreParams {
+            topic_weight: 1.0,
+            invalid_message_deliveries_weight: self.invalid_message_weight,
+            invalid_message_deliveries_decay: self.invalid_message_decay,
+            // some topics only see a few messages per view, peers can't be expected to
+            // deliver them at a steady rate
+            mesh_message_deliveries_weight: 0.0,
+            mesh_failure_penalty_weight: 0.0,
+            ..Default::default()
         }
     }
 }
@@ -180,6 +307,20 @@ mod tests {
         let deserialized: SwarmConfig = serde_json::from_str(serialized.as_str()).unwrap();
         assert_eq!(deserialized.host, config.host);
         assert_eq!(deserialized.port, config.port);
+        assert_eq!(deserialized.transport, config.transport);
         assert_eq!(deserialized.node_key.to_bytes(), config.node_key.to_bytes());
     }
+
+    #[test]
+    fn listen_address() {
+        let config = SwarmConfig {
+            port: 3000,
+            transport: TransportKind::Quic,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.listen_address().to_string(),
+            "/ip4/0.0.0.0/udp/3000/quic-v1"
+        );
+    }
 }
@@ -1,39 +1,57 @@
 mod config;
+mod request;
 
+use std::collections::HashMap;
 use std::error::Error;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use std::time::Duration;
 
-pub use config::SwarmConfig;
+pub use config::{PeerScoreConfig, SwarmConfig, TransportKind};
 pub use libp2p;
+pub use request::{Codec, Request, MAX_MESSAGE_SIZE, REQUEST_RESPONSE_PROTOCOL};
 
 use blake2::digest::{consts::U32, Digest};
 use blake2::Blake2b;
-use libp2p::gossipsub::{Message, MessageId, TopicHash};
+pub use libp2p::gossipsub::{Message, MessageAcceptance, MessageId, TopicHash};
 #[allow(deprecated)]
 pub use libp2p::{
     core::upgrade,
     dns,
     gossipsub::{self, PublishError, SubscriptionError},
+    identify,
     identity::{self, secp256k1},
-    plaintext::Config as PlainText2Config,
+    kad, noise, ping, quic, request_response,
     swarm::{dial_opts::DialOpts, DialError, NetworkBehaviour, SwarmEvent, THandlerErr},
     tcp, yamux, PeerId, SwarmBuilder, Transport,
 };
-use libp2p::{swarm::ConnectionId, tcp::tokio::Tcp};
+use libp2p::{
+    core::{muxing::StreamMuxerBox, transport::Boxed},
+    swarm::ConnectionId,
+    tcp::tokio::Tcp,
+    StreamProtocol,
+};
 pub use multiaddr::{multiaddr, Multiaddr, Protocol};
 
 /// Wraps [`libp2p::Swarm`], and config it for use within Nomos.
 pub struct Swarm {
     // A core libp2p swarm
     swarm: libp2p::Swarm<Behaviour>,
+    topic_score_params: gossipsub::TopicScoreParams,
+    invalid_response_penalty: f64,
+    invalid_response_decay: f64,
+    // scores given to peers by the application, on top of the gossipsub ones
+    application_scores: HashMap<PeerId, f64>,
 }
 
 #[derive(NetworkBehaviour)]
 pub struct Behaviour {
     gossipsub: gossipsub::Behaviour,
+    kademlia: kad::Behaviour<kad::store::MemoryStore>,
+    identify: identify::Behaviour,
+    ping: ping::Behaviour,
+    request_response: request_response::Behaviour<Codec>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -45,6 +63,12 @@ pub enum SwarmError {
 /// A timeout for the setup and protocol upgrade process for all in/outbound connections
 const TRANSPORT_TIMEOUT: Duration = Duration::from_secs(20);
 
+// Nomos peers only talk to each other, keep them apart from other libp2p networks
+const KADEMLIA_PROTOCOL: StreamProtocol = StreamProtocol::new("/nomos/kad/1.0.0");
+const IDENTIFY_PROTOCOL_VERSION: &str = "/nomos/id/1.0.0";
+// Application scores closer to zero than this are forgotten, as gossipsub does for its counters
+const DECAY_TO_ZERO: f64 = 0.1;
+
 impl Swarm {
     /// Builds a [`Swarm`] configured for use with Nomos on top of a tokio executor.
     //
@@ -54,38 +78,74 @@ impl Swarm {
         let local_peer_id = PeerId::from(id_keys.public());
         log::info!("libp2p peer_id:{}", local_peer_id);
 
-        // TODO: consider using noise authentication
-        let tcp_transport = tcp::Transport::<Tcp>::new(tcp::Config::default().nodelay(true))
-            .upgrade(upgrade::Version::V1Lazy)
-            .authenticate(PlainText2Config::new(&id_keys))
-            .multiplex(yamux::Config::default())
-            .timeout(TRANSPORT_TIMEOUT)
-            .boxed();
-
-        // Wrapping TCP transport into DNS transport to resolve hostnames.
-        let tcp_transport = dns::tokio::Transport::system(tcp_transport)?.boxed();
-
-        // TODO: consider using Signed or Anonymous.
-        //       For Anonymous, a custom `message_id` function need to be set
-        //       to prevent all messages from a peer being filtered as duplicates.
-        let gossipsub = gossipsub::Behaviour::new(
-            gossipsub::MessageAuthenticity::Author(local_peer_id),
+        let transport = match config.transport {
+            TransportKind::Tcp => tcp_transport(&id_keys)?,
+            TransportKind::Quic => quic_transport(&id_keys),
+        };
+
+        // Messages are signed so that their origin can be checked, and are only propagated
+        // once the subscribing service has validated them (see [`Swarm::report_validation`])
+        let mut gossipsub = gossipsub::Behaviour::new(
+            gossipsub::MessageAuthenticity::Signed(id_keys.clone()),
             gossipsub::ConfigBuilder::from(config.gossipsub_config.clone())
-                .validation_mode(gossipsub::ValidationMode::None)
+                .validation_mode(gossipsub::ValidationMode::Strict)
+                .validate_messages()
                 .message_id_fn(compute_message_id)
                 .build()?,
         )?;
+        gossipsub.with_peer_score(config.peer_score.params(), config.peer_score.thresholds())?;
+
+        let mut kademlia_config = kad::Config::default();
+        kademlia_config.set_protocol_names(vec![KADEMLIA_PROTOCOL]);
+        let mut kademlia = kad::Behaviour::with_config(
+            local_peer_id,
+            kad::store::MemoryStore::new(local_peer_id),
+            kademlia_config,
+        );
+        // answer queries even before an external address is confirmed, as peers are
+        // usually reached at the address they were configured with
+        kademlia.set_mode(Some(kad::Mode::Server));
+
+        let identify = identify::Behaviour::new(identify::Config::new(
+            IDENTIFY_PROTOCOL_VERSION.into(),
+            id_keys.public(),
+        ));
+
+        let ping = ping::Behaviour::new(ping::Config::new().with_interval(config.ping_interval));
+
+        let mut request_response_config = request_response::Config::default();
+        request_response_config.set_request_timeout(TRANSPORT_TIMEOUT);
+        let request_response = request_response::Behaviour::with_codec(
+            Codec,
+            [(
+                REQUEST_RESPONSE_PROTOCOL,
+                request_response::ProtocolSupport::Full,
+            )],
+            request_response_config,
+        );
 
         let mut swarm = libp2p::Swarm::new(
-            tcp_transport,
-            Behaviour { gossipsub },
+            transport,
+            Behaviour {
+                gossipsub,
+                kademlia,
+                identify,
+                ping,
+                request_response,
+            },
             local_peer_id,
             libp2p::swarm::Config::with_tokio_executor(),
         );
 
-        swarm.listen_on(multiaddr!(Ip4(config.host), Tcp(config.port)))?;
+        swarm.listen_on(config.listen_address())?;
 
-        Ok(Swarm { swarm })
+        Ok(Swarm {
+            swarm,
+            topic_score_params: config.peer_score.topic_params(),
+            invalid_response_penalty: config.peer_score.invalid_response_penalty,
+            invalid_response_decay: config.peer_score.invalid_response_decay,
+            application_scores: HashMap::new(),
+        })
     }
 
     /// Initiates a connection attempt to a peer
@@ -102,10 +162,66 @@ impl Swarm {
     ///
     /// Returns true if the topic is newly subscribed or false if already subscribed.
     pub fn subscribe(&mut self, topic: &str) -> Result<bool, SubscriptionError> {
+        let topic = gossipsub::IdentTopic::new(topic);
+        let gossipsub = &mut self.swarm.behaviour_mut().gossipsub;
+        if let Err(e) = gossipsub.set_topic_params(topic.clone(), self.topic_score_params.clone()) {
+            tracing::error!("failed to set score parameters of topic {topic}: {e}");
+        }
+        gossipsub.subscribe(&topic)
+    }
+
+    /// Reports the validation result of a received message.
+    ///
+    /// Accepted messages are propagated to other peers, rejected ones penalise the peer that
+    /// relayed them. Returns false if the message is not in the cache anymore.
+    pub fn report_validation(
+        &mut self,
+        message_id: &MessageId,
+        propagation_source: &PeerId,
+        acceptance: MessageAcceptance,
+    ) -> Result<bool, PublishError> {
+        self.swarm
+            .behaviour_mut()
+            .gossipsub
+            .report_message_validation_result(message_id, propagation_source, acceptance)
+    }
+
+    /// Lowers the score of a peer that answered a request with invalid data.
+    ///
+    /// Penalties add up, and peers below the score thresholds are treated by gossipsub as
+    /// peers relaying invalid messages are, until enough penalties decay
+    /// (see [`Swarm::decay_application_scores`]).
+    pub fn penalize(&mut self, peer_id: &PeerId) {
+        let score = self.application_scores.entry(*peer_id).or_default();
+        *score += self.invalid_response_penalty;
         self.swarm
             .behaviour_mut()
             .gossipsub
-            .subscribe(&gossipsub::IdentTopic::new(topic))
+            .set_application_score(peer_id, *score);
+    }
+
+    /// Decays the penalties of every peer, to be called every
+    /// [`PeerScoreConfig::decay_interval`] so that peers eventually recover from them.
+    pub fn decay_application_scores(&mut self) {
+        let gossipsub = &mut self.swarm.behaviour_mut().gossipsub;
+        let decay = self.invalid_response_decay;
+        self.application_scores.retain(|peer_id, score| {
+            *score *= decay;
+            let forgotten = score.abs() < DECAY_TO_ZERO;
+            if forgotten {
+                *score = 0.0;
+            }
+            gossipsub.set_application_score(peer_id, *score);
+            !forgotten
+        });
+    }
+
+    /// Score given to a peer by the application, 0 if it was never penalized
+    pub fn application_score(&self, peer_id: &PeerId) -> f64 {
+        self.application_scores
+            .get(peer_id)
+            .copied()
+            .unwrap_or_default()
     }
 
     pub fn broadcast(
@@ -129,6 +245,79 @@ impl Swarm {
             .unsubscribe(&gossipsub::IdentTopic::new(topic))
     }
 
+    /// Sends a request directly to a peer, without going through gossipsub
+    ///
+    /// The response, or the failure, is reported as an event with the returned id.
+    pub fn send_request(
+        &mut self,
+        peer_id: &PeerId,
+        request: Request,
+    ) -> request_response::RequestId {
+        self.swarm
+            .behaviour_mut()
+            .request_response
+            .send_request(peer_id, request)
+    }
+
+    /// Answers a request received from a peer
+    ///
+    /// Fails, returning the response, if the connection or the request timed out.
+    pub fn send_response(
+        &mut self,
+        channel: request_response::ResponseChannel<Vec<u8>>,
+        response: Vec<u8>,
+    ) -> Result<(), Vec<u8>> {
+        self.swarm
+            .behaviour_mut()
+            .request_response
+            .send_response(channel, response)
+    }
+
+    /// Adds a known address of a peer to the routing table
+    pub fn add_peer_address(&mut self, peer_id: PeerId, addr: Multiaddr) {
+        self.swarm
+            .behaviour_mut()
+            .kademlia
+            .add_address(&peer_id, addr);
+    }
+
+    /// Looks up the closest peers to our own id to populate the routing table
+    ///
+    /// Fails if no peer is known yet.
+    pub fn bootstrap(&mut self) -> Result<kad::QueryId, kad::NoKnownPeers> {
+        self.swarm.behaviour_mut().kademlia.bootstrap()
+    }
+
+    /// Returns every peer of the routing table along with its known addresses
+    pub fn routing_table(&mut self) -> Vec<(PeerId, Vec<Multiaddr>)> {
+        self.swarm
+            .behaviour_mut()
+            .kademlia
+            .kbuckets()
+            .flat_map(|bucket| {
+                bucket
+                    .iter()
+                    .map(|entry| {
+                        (
+                            *entry.node.key.preimage(),
+                            entry.node.value.clone().into_vec(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    pub fn is_connected(&self, peer_id: &PeerId) -> bool {
+        self.swarm.is_connected(peer_id)
+    }
+
+    /// Closes all connections to a peer
+    pub fn disconnect(&mut self, peer_id: PeerId) {
+        // an error only means that we were not connected
+        let _ = self.swarm.disconnect_peer_id(peer_id);
+    }
+
     /// Returns a reference to the underlying [`libp2p::Swarm`]
     pub fn swarm(&self) -> &libp2p::Swarm<Behaviour> {
         &self.swarm
@@ -159,8 +348,128 @@ impl futures::Stream for Swarm {
     }
 }
 
-fn compute_message_id(message: &Message) -> MessageId {
+/// TCP connections, authenticated and encrypted with Noise (XX handshake)
+fn tcp_transport(
+    id_keys: &identity::Keypair,
+) -> Result<Boxed<(PeerId, StreamMuxerBox)>, Box<dyn Error>> {
+    let transport = tcp::Transport::<Tcp>::new(tcp::Config::default().nodelay(true))
+        .upgrade(upgrade::Version::V1Lazy)
+        .authenticate(noise::Config::new(id_keys)?)
+        .multiplex(yamux::Config::default())
+        .timeout(TRANSPORT_TIMEOUT)
+        .boxed();
+
+    // Wrapping TCP transport into DNS transport to resolve hostnames.
+    Ok(dns::tokio::Transport::system(transport)?.boxed())
+}
+
+/// QUIC connections, which are always authenticated and encrypted with TLS
+fn quic_transport(id_keys: &identity::Keypair) -> Boxed<(PeerId, StreamMuxerBox)> {
+    let mut config = quic::Config::new(id_keys);
+    config.handshake_timeout = TRANSPORT_TIMEOUT;
+    quic::tokio::Transport::new(config)
+        .map(|(peer_id, connection), _| (peer_id, StreamMuxerBox::new(connection)))
+        .boxed()
+}
+
+/// Id of a gossipsub message, which only depends on its content
+pub fn compute_message_id(message: &Message) -> MessageId {
     let mut hasher = Blake2b::<U32>::new();
     hasher.update(&message.data);
     MessageId::from(hasher.finalize().to_vec())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn swarm() -> Swarm {
+        Swarm::build(&SwarmConfig {
+            host: std::net::Ipv4Addr::LOCALHOST,
+            port: 0,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn request_response_between_peers() {
+        let mut responder = swarm();
+        let mut requester = swarm();
+        let responder_id = *responder.swarm().local_peer_id();
+        let address = loop {
+            if let Some(SwarmEvent::NewListenAddr { address, .. }) = responder.next().await {
+                break address;
+            }
+        };
+        requester.connect(address).unwrap();
+
+        let request = Request {
+            protocol: "sync".into(),
+            data: vec![1, 2, 3],
+        };
+        let response = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                tokio::select! {
+                    Some(event) = requester.next() => match event {
+                        SwarmEvent::ConnectionEstablished { peer_id, .. } if peer_id == responder_id => {
+                            requester.send_request(&responder_id, request.clone());
+                        }
+                        SwarmEvent::Behaviour(BehaviourEvent::RequestResponse(
+                            request_response::Event::Message {
+                                message: request_response::Message::Response { response, .. },
+                                ..
+                            },
+                        )) => break response,
+                        _ => {}
+                    },
+                    Some(event) = responder.next() => {
+                        if let SwarmEvent::Behaviour(BehaviourEvent::RequestResponse(
+                            request_response::Event::Message {
+                                message: request_response::Message::Request { request: received, channel, .. },
+                                ..
+                            },
+                        )) = event
+                        {
+                            assert_eq!(received, request);
+                            let response = received.data.into_iter().rev().collect();
+                            responder.send_response(channel, response).unwrap();
+                        }
+                    }
+                }
+            }
+        })
+        .await
+        .expect("no response before the timeout");
+        assert_eq!(response, vec![3, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn penalized_peer_recovers() {
+        let mut swarm = Swarm::build(&SwarmConfig {
+            host: std::net::Ipv4Addr::LOCALHOST,
+            port: 0,
+            peer_score: PeerScoreConfig {
+                invalid_response_penalty: -20.0,
+                invalid_response_decay: 0.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let peer_id = PeerId::random();
+
+        swarm.penalize(&peer_id);
+        swarm.penalize(&peer_id);
+        assert_eq!(swarm.application_score(&peer_id), -40.0);
+        swarm.decay_application_scores();
+        assert_eq!(swarm.application_score(&peer_id), -20.0);
+
+        for _ in 0..10 {
+            swarm.decay_application_scores();
+        }
+        assert_eq!(swarm.application_score(&peer_id), 0.0);
+        assert!(swarm.application_scores.is_empty());
+    }
+}
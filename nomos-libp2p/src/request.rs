@@ -0,0 +1,121 @@
+use std::io;
+
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::StreamProtocol;
+
+/// Protocol used for every direct request, the application protocol is part of the [`Request`]
+pub const REQUEST_RESPONSE_PROTOCOL: StreamProtocol = StreamProtocol::new("/nomos/req/1.0.0");
+/// Do not read requests or responses bigger than this
+pub const MAX_MESSAGE_SIZE: u64 = 16 << 20;
+
+/// A request sent directly to a single peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// Application protocol, used by the receiving node to route the request (e.g. `sync`)
+    pub protocol: String,
+    pub data: Vec<u8>,
+}
+
+/// Codec for [`Request`]s and their raw responses.
+///
+/// A request is framed as the length of its protocol (`u16`, big endian), the protocol
+/// and the data. Since each request and response has its own stream, data is read until
+/// the remote closes it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Codec;
+
+#[async_trait::async_trait]
+impl libp2p::request_response::Codec for Codec {
+    type Protocol = StreamProtocol;
+    type Request = Request;
+    type Response = Vec<u8>;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let mut len = [0; 2];
+        io.read_exact(&mut len).await?;
+        let mut protocol = vec![0; u16::from_be_bytes(len) as usize];
+        io.read_exact(&mut protocol).await?;
+        let protocol = String::from_utf8(protocol)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let data = read_to_end(io).await?;
+        Ok(Request { protocol, data })
+    }
+
+    async fn read_response<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Vec<u8>>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_to_end(io).await
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        request: Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let len = u16::try_from(request.protocol.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        io.write_all(&len.to_be_bytes()).await?;
+        io.write_all(request.protocol.as_bytes()).await?;
+        io.write_all(&request.data).await?;
+        io.close().await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        response: Vec<u8>,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        io.write_all(&response).await?;
+        io.close().await
+    }
+}
+
+async fn read_to_end<T: AsyncRead + Unpin + Send>(io: &mut T) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    io.take(MAX_MESSAGE_SIZE + 1).read_to_end(&mut data).await?;
+    if data.len() as u64 > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message exceeds the maximum size",
+        ));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::Cursor;
+    use libp2p::request_response::Codec as _;
+
+    #[tokio::test]
+    async fn request_roundtrip() {
+        let request = Request {
+            protocol: "sync".into(),
+            data: vec![1, 2, 3],
+        };
+        let mut buf = Cursor::new(Vec::new());
+        Codec
+            .write_request(&REQUEST_RESPONSE_PROTOCOL, &mut buf, request.clone())
+            .await
+            .unwrap();
+        buf.set_position(0);
+        let read = Codec
+            .read_request(&REQUEST_RESPONSE_PROTOCOL, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(read, request);
+    }
+}
@@ -8,8 +8,9 @@ use std::hash::Hash;
 // internal
 use consensus_engine::overlay::{
     CommitteeMembership, Error as RandomBeaconError, FreezeMembership, RandomBeaconState,
+    StakeWeighted,
 };
-use consensus_engine::TimeoutQc;
+use consensus_engine::{LeaderProof, TimeoutQc};
 use nomos_core::block::Block;
 
 pub trait UpdateableCommitteeMembership: CommitteeMembership {
@@ -44,10 +45,32 @@ impl UpdateableCommitteeMembership for RandomBeaconState {
         &self,
         block: &Block<Tx, Blob>,
     ) -> Result<Self, Self::Error> {
-        self.check_advance_happy(block.beacon().clone(), block.header().parent_qc.view())
+        let LeaderProof::LeaderId { leader_id } = block.header().leader_proof;
+        self.check_advance_happy(
+            block.beacon().clone(),
+            block.header().parent_qc.view(),
+            leader_id,
+        )
     }
 
     fn on_timeout_qc_received(&self, qc: &TimeoutQc) -> Result<Self, Self::Error> {
         Ok(Self::generate_sad(qc.view(), self))
     }
 }
+
+impl UpdateableCommitteeMembership for StakeWeighted {
+    type Error = RandomBeaconError;
+
+    fn on_new_block_received<Tx: Hash + Clone + Eq, Blob: Clone + Eq + Hash>(
+        &self,
+        block: &Block<Tx, Blob>,
+    ) -> Result<Self, Self::Error> {
+        UpdateableCommitteeMembership::on_new_block_received(self.beacon(), block)
+            .map(|beacon| self.with_beacon(beacon))
+    }
+
+    fn on_timeout_qc_received(&self, qc: &TimeoutQc) -> Result<Self, Self::Error> {
+        UpdateableCommitteeMembership::on_timeout_qc_received(self.beacon(), qc)
+            .map(|beacon| self.with_beacon(beacon))
+    }
+}
@@ -0,0 +1,205 @@
+// std
+use std::sync::{Arc, Mutex};
+// crates
+use bls_signatures::Serialize as _;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+// internal
+use crate::network::messages::{ProposalMsg, VoteMsg};
+use crate::PublicKeys;
+use consensus_engine::{Claim, Equivocation, EquivocationDetector, Evidence, NodeId, View};
+
+/// Watches the signed proposals and votes received by the service for conflicting ones,
+/// whatever network adapter they came from.
+///
+/// Only messages signed by consensus participants are watched, anyone else can sign as many
+/// conflicting messages as they want without it meaning anything.
+#[derive(Clone)]
+pub struct EquivocationMonitor {
+    detector: Arc<Mutex<EquivocationDetector>>,
+    public_keys: Arc<PublicKeys>,
+    evidence: UnboundedSender<Evidence>,
+}
+
+impl EquivocationMonitor {
+    /// Returns the monitor of the nodes in `public_keys` along with the stream of the evidence
+    /// it finds
+    pub fn new(public_keys: Arc<PublicKeys>) -> (Self, UnboundedReceiverStream<Evidence>) {
+        let (evidence, receiver) = mpsc::unbounded_channel();
+        let monitor = Self {
+            detector: Default::default(),
+            public_keys,
+            evidence,
+        };
+        (monitor, UnboundedReceiverStream::new(receiver))
+    }
+
+    /// Check that `evidence` is against a consensus participant, using the key it is known by
+    pub fn is_known_offender(&self, evidence: &Evidence) -> bool {
+        self.is_participant(&evidence.offender, &evidence.public_key)
+    }
+
+    pub fn observe_proposal(&self, msg: &ProposalMsg) {
+        let claim = Claim {
+            block: msg.proposal,
+            signature: msg.signature.clone(),
+        };
+        self.observe(
+            Equivocation::Proposal,
+            msg.view,
+            msg.leader,
+            &msg.public_key,
+            claim,
+        );
+    }
+
+    pub fn observe_vote(&self, msg: &VoteMsg) {
+        let claim = Claim {
+            block: msg.vote.block,
+            signature: msg.signature.clone(),
+        };
+        self.observe(
+            Equivocation::Vote,
+            msg.vote.view,
+            msg.voter,
+            &msg.public_key,
+            claim,
+        );
+    }
+
+    /// Forget about messages for views older than `view`
+    pub fn prune(&self, view: View) {
+        self.detector.lock().unwrap().prune(view);
+    }
+
+    fn is_participant(&self, id: &NodeId, public_key: &[u8]) -> bool {
+        self.public_keys
+            .get(id)
+            .is_some_and(|known| known.as_bytes() == public_key)
+    }
+
+    fn observe(
+        &self,
+        equivocation: Equivocation,
+        view: View,
+        signer: NodeId,
+        public_key: &[u8],
+        claim: Claim,
+    ) {
+        if !self.is_participant(&signer, public_key) {
+            return;
+        }
+        let evidence =
+            self.detector
+                .lock()
+                .unwrap()
+                .observe(equivocation, view, signer, public_key, claim);
+        if let Some(evidence) = evidence {
+            // an error only means the service is shutting down
+            let _ = self.evidence.send(evidence);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls_signatures::PrivateKey;
+    use consensus_engine::{BlockId, Vote};
+    use futures::StreamExt;
+
+    fn public_keys(private_keys: &[&PrivateKey]) -> Arc<PublicKeys> {
+        Arc::new(
+            private_keys
+                .iter()
+                .map(|key| {
+                    let public_key = key.public_key();
+                    (NodeId::from_public_key(&public_key), public_key)
+                })
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn conflicting_proposals() {
+        let private_key = PrivateKey::new([1; 32]);
+        let view = View::new(1);
+        let proposal = |block: BlockId| ProposalMsg::new(Box::new([]), block, view, &private_key);
+        let (monitor, mut found) = EquivocationMonitor::new(public_keys(&[&private_key]));
+
+        monitor.observe_proposal(&proposal(BlockId::new([1; 32])));
+        monitor.observe_proposal(&proposal(BlockId::new([1; 32])));
+        monitor.observe_proposal(&proposal(BlockId::new([2; 32])));
+        drop(monitor);
+
+        let evidence = found.next().await.unwrap();
+        assert_eq!(evidence.equivocation, Equivocation::Proposal);
+        evidence.verify().unwrap();
+        assert!(found.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn conflicting_votes() {
+        let private_key = PrivateKey::new([1; 32]);
+        let vote =
+            |view: View, block: BlockId| VoteMsg::new(Vote { view, block }, None, &private_key);
+        let (monitor, mut found) = EquivocationMonitor::new(public_keys(&[&private_key]));
+
+        monitor.observe_vote(&vote(View::new(1), BlockId::new([1; 32])));
+        // voting for another block in a later view is fine
+        monitor.observe_vote(&vote(View::new(2), BlockId::new([2; 32])));
+        monitor.observe_vote(&vote(View::new(1), BlockId::new([2; 32])));
+        drop(monitor);
+
+        let evidence = found.next().await.unwrap();
+        assert_eq!(evidence.equivocation, Equivocation::Vote);
+        assert_eq!(evidence.view, View::new(1));
+        evidence.verify().unwrap();
+        assert!(found.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn conflicting_votes_after_skipping_views() {
+        let private_key = PrivateKey::new([1; 32]);
+        let vote =
+            |view: View, block: BlockId| VoteMsg::new(Vote { view, block }, None, &private_key);
+        let (monitor, mut found) = EquivocationMonitor::new(public_keys(&[&private_key]));
+
+        // jump from view 1 to view 5 and prune as the service does on a view change
+        monitor.observe_vote(&vote(View::new(1), BlockId::new([1; 32])));
+        let current_view = View::new(5);
+        monitor.prune(current_view.prev());
+        monitor.observe_vote(&vote(current_view, BlockId::new([1; 32])));
+        monitor.observe_vote(&vote(current_view, BlockId::new([2; 32])));
+        drop(monitor);
+
+        let evidence = found.next().await.unwrap();
+        assert_eq!(evidence.equivocation, Equivocation::Vote);
+        assert_eq!(evidence.view, current_view);
+        evidence.verify().unwrap();
+        assert!(found.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn unknown_signers() {
+        let private_key = PrivateKey::new([1; 32]);
+        let outsider = PrivateKey::new([2; 32]);
+        let vote = |private_key: &PrivateKey, block: BlockId| {
+            VoteMsg::new(
+                Vote {
+                    view: View::new(1),
+                    block,
+                },
+                None,
+                private_key,
+            )
+        };
+        let (monitor, mut found) = EquivocationMonitor::new(public_keys(&[&private_key]));
+
+        monitor.observe_vote(&vote(&outsider, BlockId::new([1; 32])));
+        monitor.observe_vote(&vote(&outsider, BlockId::new([2; 32])));
+        drop(monitor);
+
+        assert!(found.next().await.is_none());
+    }
+}
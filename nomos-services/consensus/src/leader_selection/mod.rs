@@ -1,7 +1,7 @@
 use consensus_engine::overlay::RoundRobin;
 use consensus_engine::{
-    overlay::{Error as RandomBeaconError, LeaderSelection, RandomBeaconState},
-    TimeoutQc,
+    overlay::{Error as RandomBeaconError, LeaderSelection, RandomBeaconState, StakeWeighted},
+    LeaderProof, TimeoutQc,
 };
 use nomos_core::block::Block;
 use std::{convert::Infallible, error::Error, hash::Hash};
@@ -38,11 +38,33 @@ impl UpdateableLeaderSelection for RandomBeaconState {
         &self,
         block: &Block<Tx, Blob>,
     ) -> Result<Self, Self::Error> {
-        self.check_advance_happy(block.beacon().clone(), block.header().parent_qc.view())
-        // TODO: check random beacon public keys is leader id
+        let LeaderProof::LeaderId { leader_id } = block.header().leader_proof;
+        self.check_advance_happy(
+            block.beacon().clone(),
+            block.header().parent_qc.view(),
+            leader_id,
+        )
     }
 
     fn on_timeout_qc_received(&self, qc: &TimeoutQc) -> Result<Self, Self::Error> {
         Ok(Self::generate_sad(qc.view(), self))
     }
 }
+
+/// Stake weighted draws are re-seeded by the beacon of every view
+impl UpdateableLeaderSelection for StakeWeighted {
+    type Error = RandomBeaconError;
+
+    fn on_new_block_received<Tx: Hash + Clone + Eq, Blob: Clone + Eq + Hash>(
+        &self,
+        block: &Block<Tx, Blob>,
+    ) -> Result<Self, Self::Error> {
+        UpdateableLeaderSelection::on_new_block_received(self.beacon(), block)
+            .map(|beacon| self.with_beacon(beacon))
+    }
+
+    fn on_timeout_qc_received(&self, qc: &TimeoutQc) -> Result<Self, Self::Error> {
+        UpdateableLeaderSelection::on_timeout_qc_received(self.beacon(), qc)
+            .map(|beacon| self.with_beacon(beacon))
+    }
+}
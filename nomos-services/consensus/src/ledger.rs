@@ -0,0 +1,157 @@
+// std
+use std::collections::HashMap;
+use std::hash::Hash;
+// crates
+// internal
+use consensus_engine::{BlockId, View};
+use nomos_core::block::Block;
+use nomos_core::ledger::{Ledger, LedgerError, LedgerTransaction, StateRoot};
+
+/// Ledger state of the committed chain, along with the state after each validated block that
+/// extends it but is not committed yet.
+#[derive(Clone, Debug)]
+pub(crate) struct LedgerState {
+    committed: Ledger,
+    pending: HashMap<BlockId, (View, Ledger)>,
+}
+
+impl LedgerState {
+    pub fn new(committed: Ledger) -> Self {
+        Self {
+            committed,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn committed(&self) -> &Ledger {
+        &self.committed
+    }
+
+    /// Keep the transactions that can be executed on top of `parent`, in order, along with the
+    /// resulting state root.
+    /// Returns `None` if the state at `parent` is unknown.
+    pub fn execute<T: LedgerTransaction>(
+        &self,
+        parent: BlockId,
+        txs: impl IntoIterator<Item = T>,
+    ) -> Option<(Vec<T>, StateRoot)> {
+        let mut state = self.state_after(parent)?.clone();
+        let txs = txs
+            .into_iter()
+            .filter(|tx| state.apply_tx(tx).is_ok())
+            .collect();
+        Some((txs, state.state_root()))
+    }
+
+    /// Execute a proposal on top of the state at its parent, checking the root it commits to.
+    ///
+    /// The resulting state is returned rather than kept, as it must only be built upon once
+    /// consensus accepted the block, see [`LedgerState::accept`].
+    pub fn validate<T, C>(&self, block: &Block<T, C>) -> Result<Ledger, LedgerError>
+    where
+        T: LedgerTransaction + Clone + Eq + Hash,
+        C: Clone + Eq + Hash,
+    {
+        let header = block.header();
+        let mut state = self
+            .state_after(header.parent())
+            .ok_or(LedgerError::NotExtending {
+                block: header.id,
+                tip: self.committed.tip(),
+            })?
+            .clone();
+        state.apply_block(block)?;
+        Ok(state)
+    }
+
+    /// Keep the `state` after an accepted `block`, as returned by [`LedgerState::validate`]
+    pub fn accept(&mut self, block: &consensus_engine::Block, state: Ledger) {
+        self.pending.insert(block.id, (block.view, state));
+    }
+
+    /// Apply a newly committed block, forgetting about the pending states it makes obsolete
+    pub fn commit<T, C>(&mut self, block: &Block<T, C>) -> Result<(), LedgerError>
+    where
+        T: LedgerTransaction + Clone + Eq + Hash,
+        C: Clone + Eq + Hash,
+    {
+        self.committed.apply_block(block)?;
+        let view = block.header().view;
+        self.pending
+            .retain(|_, (pending_view, _)| *pending_view > view);
+        Ok(())
+    }
+
+    fn state_after(&self, block: BlockId) -> Option<&Ledger> {
+        if block == self.committed.tip() {
+            return Some(&self.committed);
+        }
+        self.pending.get(&block).map(|(_, state)| state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consensus_engine::overlay::RandomBeaconState;
+    use consensus_engine::{NodeId, Qc, StandardQc};
+    use nomos_core::account::AccountId;
+    use nomos_core::tx::carnot::{TransferTransaction, Tx};
+
+    const ALICE: [u8; 32] = [1; 32];
+
+    fn block(parent: BlockId, view: View, txs: Vec<Tx>, state_root: StateRoot) -> Block<Tx, ()> {
+        Block::new(
+            view,
+            Qc::Standard(StandardQc {
+                view: view.prev(),
+                id: parent,
+                signature: None,
+            }),
+            txs.into_iter(),
+            std::iter::empty(),
+            NodeId::new([0; 32]),
+            RandomBeaconState::initial_sad_from_entropy([0; 32]),
+            state_root,
+        )
+    }
+
+    #[test]
+    fn validate_chain_of_proposals() {
+        let alice = *TransferTransaction::new(&ALICE, AccountId::new([0; 32]), 0, 0).from();
+        let bob = AccountId::new([2; 32]);
+        let transfer = |nonce| Tx::Transfer(TransferTransaction::new(&ALICE, bob, 60, nonce));
+        let mut ledger = LedgerState::new(Ledger::genesis([(alice, 100)]));
+
+        // the second transfer overspends and is left out of the proposal
+        let (txs, root) = ledger
+            .execute(BlockId::zeros(), [transfer(0), transfer(1)])
+            .unwrap();
+        assert_eq!(txs, vec![transfer(0)]);
+        let first = block(BlockId::zeros(), View::new(1), txs, root);
+        let state = ledger.validate(&first).unwrap();
+        // blocks are only built upon once accepted
+        assert!(ledger.execute::<Tx>(first.header().id, []).is_none());
+        ledger.accept(first.header(), state);
+
+        // proposals extend the state of their uncommitted parent
+        let (txs, root) = ledger.execute(first.header().id, [transfer(1)]).unwrap();
+        assert!(txs.is_empty());
+        let second = block(first.header().id, View::new(2), txs, root);
+        let state = ledger.validate(&second).unwrap();
+        ledger.accept(second.header(), state);
+
+        let wrong_root = block(first.header().id, View::new(2), vec![], [0; 32]);
+        assert!(matches!(
+            ledger.validate(&wrong_root),
+            Err(LedgerError::StateRootMismatch { .. })
+        ));
+        let unknown_parent = block(BlockId::new([42; 32]), View::new(2), vec![], root);
+        assert!(ledger.validate(&unknown_parent).is_err());
+
+        ledger.commit(&first).unwrap();
+        assert_eq!(ledger.committed().tip(), first.header().id);
+        assert_eq!(ledger.committed().account(&bob).balance, 60);
+        assert!(ledger.execute::<Tx>(second.header().id, []).is_some());
+    }
+}
@@ -1,19 +1,24 @@
 pub mod committee_membership;
+pub mod equivocation;
 pub mod leader_selection;
+mod ledger;
 pub mod network;
+mod sync;
 mod tally;
 mod task_manager;
+pub mod timeout;
 
 // std
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::time::Duration;
 // crates
-use bls_signatures::PrivateKey;
+use bls_signatures::{PrivateKey, PublicKey, Serialize as _};
 pub use consensus_engine::NodeId;
-use futures::{Stream, StreamExt};
+use futures::{future::OptionFuture, Stream, StreamExt};
 use leader_selection::UpdateableLeaderSelection;
 use serde::Deserialize;
 use serde::{de::DeserializeOwned, Serialize};
@@ -22,22 +27,30 @@ use tokio::sync::oneshot::Sender;
 use tracing::{error, instrument};
 // internal
 use crate::network::messages::{
-    NetworkMessage, NewViewMsg, ProposalMsg, TimeoutMsg, TimeoutQcMsg, VoteMsg,
+    NetworkMessage, NewViewMsg, ProposalMsg, SyncRequest, TimeoutMsg, TimeoutQcMsg, VoteMsg,
 };
-use crate::network::NetworkAdapter;
+use crate::network::{IncomingSyncRequest, NetworkAdapter, TimeoutQcVerifier};
+use crate::sync::MAX_SYNC_BLOCKS;
 use crate::tally::{
     happy::CarnotTally, timeout::TimeoutTally, unhappy::NewViewTally, CarnotTallySettings,
 };
 use consensus_engine::{
-    overlay::RandomBeaconState, AggregateQc, BlockId, Carnot, Committee, LeaderProof, NewView,
-    Overlay, Payload, Qc, StandardQc, Timeout, TimeoutQc, View, Vote,
+    overlay::{Error as RandomBeaconError, RandomBeaconState},
+    AggregateQc, AggregateSignature, BlockId, Carnot, Checkpoint, Committee, EpochSchedule,
+    Evidence, LeaderProof, NewView, Overlay, Payload, Qc, StandardQc, Timeout, TimeoutQc, View,
+    Vote,
 };
 use task_manager::TaskManager;
+use timeout::{AdaptiveTimeoutSettings, ViewTimeout};
 
 use crate::committee_membership::UpdateableCommitteeMembership;
+use crate::equivocation::EquivocationMonitor;
+use crate::ledger::LedgerState;
+use nomos_core::account::AccountId;
 use nomos_core::block::builder::BlockBuilder;
-use nomos_core::block::Block;
+use nomos_core::block::{fit_in_block, Block, MAX_BLOCK_SIZE};
 use nomos_core::da::certificate::{BlobCertificateSelect, Certificate};
+use nomos_core::ledger::{Ledger, LedgerTransaction};
 use nomos_core::tx::{Transaction, TxSelect};
 use nomos_core::vote::Tally;
 use nomos_mempool::{
@@ -45,7 +58,10 @@ use nomos_mempool::{
     MempoolMsg, MempoolService, Transaction as TxDiscriminant,
 };
 use nomos_network::NetworkService;
-use nomos_storage::{backends::StorageBackend, StorageMsg, StorageService};
+use nomos_storage::{
+    backends::{Column, StorageBackend, WriteBatch},
+    StorageMsg, StorageService,
+};
 use overwatch_rs::services::life_cycle::LifecycleMessage;
 use overwatch_rs::services::relay::{OutboundRelay, Relay, RelayMessage};
 use overwatch_rs::services::{
@@ -55,6 +71,13 @@ use overwatch_rs::services::{
 };
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Storage key of the consensus engine checkpoint, in the metadata column
+const CHECKPOINT_KEY: &[u8] = b"carnot/checkpoint";
+/// Storage key of the ledger state as of the latest committed block, in the metadata column.
+/// It is written along with the checkpoint so that both describe the same committed block.
+const LEDGER_KEY: &[u8] = b"carnot/ledger";
+/// Maximum number of evidence kept around, the oldest is dropped first
+const MAX_EVIDENCE: usize = 1024;
 
 fn default_timeout() -> Duration {
     DEFAULT_TIMEOUT
@@ -63,16 +86,44 @@ fn default_timeout() -> Duration {
 // Random seed for each round provided by the protocol
 pub type Seed = [u8; 32];
 
+/// BLS public keys of the consensus participants, indexed by their node id
+pub type PublicKeys = HashMap<NodeId, PublicKey>;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CarnotSettings<O: Overlay, Ts, Bs> {
     pub private_key: [u8; 32],
     pub overlay_settings: O::Settings,
     #[serde(default = "default_timeout")]
     pub timeout: Duration,
+    /// Back off after timeout qcs and decay after commits, starting from `timeout`.
+    /// The timeout stays fixed if not set.
+    #[serde(default)]
+    pub adaptive_timeout: Option<AdaptiveTimeoutSettings>,
     #[serde(default)]
     pub transaction_selector_settings: Ts,
     #[serde(default)]
     pub blob_selector_settings: Bs,
+    /// Compressed BLS public keys of the other participants, used to verify the aggregated
+    /// signatures of quorum certificates.
+    #[serde(default)]
+    pub public_keys: Vec<Box<[u8]>>,
+    /// Validator set changes at epoch boundaries.
+    /// The keys of the nodes joining must be part of `public_keys`.
+    #[serde(default)]
+    pub epochs: Option<EpochSchedule>,
+    /// Fetch the contents of the next proposal from the mempools while the votes for the
+    /// current block are gathered, so that leaders propose as soon as the quorum certificate
+    /// is formed.
+    ///
+    /// Proposals are still built on top of a quorum certificate, as the parent of a block is
+    /// the one certified by its `parent_qc`: only the mempool round trip is taken off the
+    /// critical path. Proposing ahead of the votes on an optimistic certificate is not
+    /// supported, since the engine only accepts blocks extending a certified parent.
+    #[serde(default)]
+    pub pipelined: bool,
+    /// Initial balance of the accounts in the ledger
+    #[serde(default)]
+    pub genesis_balances: Vec<(AccountId, u64)>,
 }
 
 impl<O: Overlay, Ts: Clone, Bs: Clone> Clone for CarnotSettings<O, Ts, Bs> {
@@ -81,27 +132,43 @@ impl<O: Overlay, Ts: Clone, Bs: Clone> Clone for CarnotSettings<O, Ts, Bs> {
             private_key: self.private_key,
             overlay_settings: self.overlay_settings.clone(),
             timeout: self.timeout,
+            adaptive_timeout: self.adaptive_timeout,
             transaction_selector_settings: self.transaction_selector_settings.clone(),
             blob_selector_settings: self.blob_selector_settings.clone(),
+            public_keys: self.public_keys.clone(),
+            epochs: self.epochs.clone(),
+            pipelined: self.pipelined,
+            genesis_balances: self.genesis_balances.clone(),
         }
     }
 }
 
 impl<O: Overlay, Ts, Bs> CarnotSettings<O, Ts, Bs> {
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub const fn new(
         private_key: [u8; 32],
         overlay_settings: O::Settings,
         transaction_selector_settings: Ts,
         blob_selector_settings: Bs,
         timeout: Duration,
+        adaptive_timeout: Option<AdaptiveTimeoutSettings>,
+        public_keys: Vec<Box<[u8]>>,
+        epochs: Option<EpochSchedule>,
+        pipelined: bool,
+        genesis_balances: Vec<(AccountId, u64)>,
     ) -> Self {
         Self {
             private_key,
             overlay_settings,
             timeout,
+            adaptive_timeout,
             transaction_selector_settings,
             blob_selector_settings,
+            public_keys,
+            epochs,
+            pipelined,
+            genesis_balances,
         }
     }
 }
@@ -167,6 +234,7 @@ where
     DaPool: MemPool + Send + Sync + 'static,
     DaPool::Settings: Send + Sync + 'static,
     ClPool::Item: Transaction
+        + LedgerTransaction
         + Debug
         + Clone
         + Eq
@@ -190,7 +258,7 @@ where
     DaPool::Key: Debug + Send + Sync,
     ClPoolAdapter: MempoolAdapter<Item = ClPool::Item, Key = ClPool::Key> + Send + Sync + 'static,
     DaPoolAdapter: MempoolAdapter<Item = DaPool::Item, Key = DaPool::Key> + Send + Sync + 'static,
-    O: Overlay + Debug + Send + Sync + 'static,
+    O: Overlay + Debug + Serialize + DeserializeOwned + Send + Sync + 'static,
     O::LeaderSelection: UpdateableLeaderSelection,
     O::CommitteeMembership: UpdateableCommitteeMembership,
     TxS: TxSelect<Tx = ClPool::Item> + Clone + Send + Sync + 'static,
@@ -243,75 +311,140 @@ where
             private_key,
             overlay_settings,
             timeout,
+            adaptive_timeout,
             transaction_selector_settings,
             blob_selector_settings,
+            public_keys,
+            epochs,
+            pipelined,
+            genesis_balances,
         } = self.service_state.settings_reader.get_updated_settings();
 
-        let overlay = O::new(overlay_settings);
+        let private_key = PrivateKey::new(private_key);
+        let public_key = private_key.public_key();
+        let public_keys = public_keys
+            .iter()
+            .map(|bytes| PublicKey::from_bytes(bytes))
+            .chain(std::iter::once(Ok(public_key)))
+            .map(|public_key| public_key.map(|pk| (NodeId::from_public_key(&pk), pk)))
+            .collect::<Result<PublicKeys, _>>()?;
+        let public_keys = Arc::new(public_keys);
+
+        let id = NodeId::from_public_key(&public_key);
         let genesis = consensus_engine::Block {
             id: BlockId::zeros(),
             view: View::new(0),
             parent_qc: Qc::Standard(StandardQc::genesis()),
+            state_root: [0; 32],
             leader_proof: LeaderProof::LeaderId {
                 leader_id: NodeId::new([0; 32]),
             },
         };
-        let mut carnot = Carnot::from_genesis(NodeId::new(private_key), genesis, overlay);
+        let resumed = Self::resume(id, &storage_relay).await;
         let adapter = A::new(network_relay).await;
-        let private_key = PrivateKey::new(private_key);
-        let self_committee = carnot.self_committee();
-        let leader_committee = [carnot.id()].into_iter().collect::<Committee>();
-        let tally_settings = CarnotTallySettings {
-            threshold: carnot.super_majority_threshold(),
-            participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
-        };
-        let leader_tally_settings = CarnotTallySettings {
-            threshold: carnot.leader_super_majority_threshold(),
-            participating_nodes: carnot.root_committee(),
-        };
 
         let tx_selector = TxS::new(transaction_selector_settings);
         let blob_selector = BS::new(blob_selector_settings);
 
         let mut task_manager = TaskManager::new();
-
-        let genesis_block = carnot.genesis_block();
-        Self::process_view_change(
-            carnot.clone(),
-            genesis_block.view.prev(),
-            &mut task_manager,
-            adapter.clone(),
-            timeout,
-        )
-        .await;
-        // we already have the genesis block, no need to wait for it
-        task_manager.push(
-            genesis_block.view,
-            Self::gather_votes(
+        let mut timeout = ViewTimeout::new(timeout, adaptive_timeout);
+        let (monitor, local_evidence) = EquivocationMonitor::new(public_keys.clone());
+        adapter.set_equivocation_monitor(monitor.clone()).await;
+
+        let epochs = epochs.map(|mut epochs| {
+            // qcs of the views before the first change are signed by the initial validators,
+            // which a resumed overlay may not be built with anymore
+            epochs.set_validators(0, O::new(overlay_settings.clone()).nodes().to_vec());
+            epochs
+        });
+        let with_epochs = |carnot: Carnot<O>| match epochs {
+            Some(epochs) => carnot.with_epochs(epochs),
+            None => carnot,
+        };
+        let was_resumed = resumed.is_some();
+        let mut carnot = if let Some(carnot) = resumed {
+            let carnot = with_epochs(carnot);
+            tracing::info!("resuming from view {}", carnot.current_view());
+            // votes for views up to the persisted `highest_voted_view` might have been sent
+            // already, only the tasks for the current view are restarted
+            Self::process_view_change(
+                carnot.clone(),
+                carnot.current_view().prev(),
+                &mut task_manager,
                 adapter.clone(),
-                self_committee.clone(),
-                genesis_block.clone(),
-                tally_settings.clone(),
-            ),
-        );
-
-        if carnot.is_next_leader() {
-            let network_adapter = adapter.clone();
-            task_manager.push(genesis_block.view.next(), async move {
-                let Event::Approve { qc, .. } = Self::gather_votes(
-                    network_adapter,
-                    leader_committee.clone(),
-                    genesis_block,
-                    leader_tally_settings.clone(),
-                )
-                .await
-                else {
-                    tracing::debug!("Failed to gather initial votes");
-                    return Event::None;
+                timeout.get(),
+                public_keys.clone(),
+                &monitor,
+            )
+            .await;
+            carnot
+        } else {
+            let overlay = O::new(overlay_settings);
+            let carnot = with_epochs(Carnot::from_genesis(id, genesis, overlay));
+            let leader_committee = [carnot.id()].into_iter().collect::<Committee>();
+            let leader_tally_settings = CarnotTallySettings {
+                threshold: carnot.leader_super_majority_threshold(),
+                participating_nodes: carnot.root_committee(),
+            };
+
+            let genesis_block = carnot.genesis_block();
+            Self::process_view_change(
+                carnot.clone(),
+                genesis_block.view.prev(),
+                &mut task_manager,
+                adapter.clone(),
+                timeout.get(),
+                public_keys.clone(),
+                &monitor,
+            )
+            .await;
+            // we already have the genesis block, no need to wait for it
+            if carnot.is_validator() {
+                let tally_settings = CarnotTallySettings {
+                    threshold: carnot.super_majority_threshold(),
+                    participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
                 };
-                Event::ProposeBlock { qc }
-            });
-        }
+                task_manager.push(
+                    genesis_block.view,
+                    Self::gather_votes(
+                        adapter.clone(),
+                        carnot.self_committee(),
+                        genesis_block.clone(),
+                        tally_settings,
+                    ),
+                );
+            }
+
+            if carnot.is_next_leader() {
+                let network_adapter = adapter.clone();
+                task_manager.push(genesis_block.view.next(), async move {
+                    let Event::Approve { qc, .. } = Self::gather_votes(
+                        network_adapter,
+                        leader_committee.clone(),
+                        genesis_block,
+                        leader_tally_settings.clone(),
+                    )
+                    .await
+                    else {
+                        tracing::debug!("Failed to gather initial votes");
+                        return Event::None;
+                    };
+                    Event::ProposeBlock { qc, contents: None }
+                });
+            }
+            carnot
+        };
+        let genesis_ledger = Ledger::genesis(genesis_balances);
+        let mut ledger = if was_resumed {
+            Self::resume_ledger(&carnot, genesis_ledger, &storage_relay).await
+        } else {
+            LedgerState::new(genesis_ledger)
+        };
+        let mut sync_requests = adapter.sync_request_stream().await;
+        // evidence found locally is handled like the one gossiped by peers
+        let mut evidence_stream =
+            futures::stream::select(adapter.evidence_stream().await, local_evidence);
+        let mut evidence = VecDeque::new();
         let mut lifecycle_stream = self.service_state.lifecycle_handle.message_stream();
         loop {
             tokio::select! {
@@ -327,12 +460,22 @@ where
                             storage_relay.clone(),
                             tx_selector.clone(),
                             blob_selector.clone(),
-                            timeout,
+                            &mut timeout,
+                            public_keys.clone(),
+                            pipelined,
+                            &mut ledger,
+                            &monitor,
                         )
                         .await
                     }
                     Some(msg) = self.service_state.inbound_relay.next() => {
-                        Self::process_message(&carnot, msg);
+                        Self::process_message(&carnot, &evidence, msg);
+                    }
+                    Some(msg) = sync_requests.next() => {
+                        Self::process_sync_request(&carnot, msg, storage_relay.clone());
+                    }
+                    Some(msg) = evidence_stream.next() => {
+                        Self::process_evidence(&mut evidence, msg, &monitor, adapter.clone()).await;
                     }
                     Some(msg) = lifecycle_stream.next() => {
                         if Self::should_stop_service(msg).await {
@@ -366,6 +509,7 @@ where
     DaPool: MemPool + Send + Sync + 'static,
     DaPool::Settings: Send + Sync + 'static,
     ClPool::Item: Transaction
+        + LedgerTransaction
         + Debug
         + Clone
         + Eq
@@ -385,7 +529,7 @@ where
         + Send
         + Sync
         + 'static,
-    O: Overlay + Debug + Send + Sync + 'static,
+    O: Overlay + Debug + Serialize + DeserializeOwned + Send + Sync + 'static,
     O::LeaderSelection: UpdateableLeaderSelection,
     O::CommitteeMembership: UpdateableCommitteeMembership,
     TxS: TxSelect<Tx = ClPool::Item> + Clone + Send + Sync + 'static,
@@ -411,7 +555,7 @@ where
         }
     }
 
-    fn process_message(carnot: &Carnot<O>, msg: ConsensusMsg) {
+    fn process_message(carnot: &Carnot<O>, evidence: &VecDeque<Evidence>, msg: ConsensusMsg) {
         match msg {
             ConsensusMsg::Info { tx } => {
                 let info = CarnotInfo {
@@ -427,9 +571,52 @@ where
                     tracing::error!("Could not send consensus info through channel: {:?}", e)
                 });
             }
+            ConsensusMsg::Evidence { tx } => {
+                tx.send(evidence.iter().cloned().collect())
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Could not send evidence through channel: {:?}", e)
+                    });
+            }
         }
     }
 
+    /// Keep track of valid evidence of misbehavior and gossip it the first time it's seen.
+    ///
+    /// A single piece of evidence is kept for each offence, whatever claims it is made of and
+    /// in which order.
+    async fn process_evidence(
+        evidence: &mut VecDeque<Evidence>,
+        new: Evidence,
+        monitor: &EquivocationMonitor,
+        adapter: A,
+    ) {
+        if evidence.iter().any(|known| {
+            (known.equivocation, known.offender, known.view)
+                == (new.equivocation, new.offender, new.view)
+        }) {
+            return;
+        }
+        if !monitor.is_known_offender(&new) {
+            tracing::debug!("evidence against unknown node {:?}", new.offender);
+            return;
+        }
+        if let Err(e) = new.verify() {
+            tracing::debug!("invalid evidence against {:?}: {e}", new.offender);
+            return;
+        }
+        tracing::warn!(
+            "{:?} equivocated in view {}: {:?}",
+            new.offender,
+            new.view,
+            new.equivocation
+        );
+        if evidence.len() == MAX_EVIDENCE {
+            evidence.pop_front();
+        }
+        evidence.push_back(new.clone());
+        adapter.broadcast(NetworkMessage::Evidence(new)).await;
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn process_carnot_event(
         mut carnot: Carnot<O>,
@@ -442,22 +629,52 @@ where
         storage_relay: OutboundRelay<StorageMsg<Storage>>,
         tx_selector: TxS,
         blobl_selector: BS,
-        timeout: Duration,
+        view_timeout: &mut ViewTimeout,
+        public_keys: Arc<PublicKeys>,
+        pipelined: bool,
+        ledger: &mut LedgerState,
+        monitor: &EquivocationMonitor,
     ) -> Carnot<O> {
         let mut output = None;
         let prev_view = carnot.current_view();
+        let prev_timeout_qc_view = carnot.last_view_timeout_qc().map(|qc| qc.view());
+        let prev_committed_view = carnot.latest_committed_view();
+        let timeout = view_timeout.get();
+        let changes_state = !matches!(event, Event::ProposeBlock { .. } | Event::None);
         match event {
             Event::Proposal { block, stream } => {
+                let prefetch =
+                    pipelined.then(|| (cl_mempool_relay.clone(), da_mempool_relay.clone()));
                 (carnot, output) = Self::process_block(
                     carnot,
                     block,
                     stream,
                     task_manager,
                     adapter.clone(),
-                    storage_relay,
+                    storage_relay.clone(),
+                    &public_keys,
+                    timeout,
+                    prefetch,
+                    ledger,
                 )
                 .await;
             }
+            Event::Synced { blocks, then } => {
+                carnot = Self::replay_blocks(
+                    carnot,
+                    blocks,
+                    then,
+                    task_manager,
+                    adapter.clone(),
+                    storage_relay.clone(),
+                    &public_keys,
+                    timeout,
+                    ledger,
+                )
+                .await;
+            }
+            // nodes that left the validator set keep following the chain without voting
+            Event::Approve { .. } | Event::NewView { .. } if !carnot.is_validator() => {}
             Event::Approve { block, .. } => {
                 tracing::debug!("approving proposal {:?}", block);
                 let (new_carnot, out) = carnot.approve_block(block);
@@ -469,6 +686,19 @@ where
                 let (new_carnot, out) = carnot.local_timeout();
                 carnot = new_carnot;
                 output = out.map(Output::Send);
+                // we might be the one lagging behind, check if peers know of newer blocks
+                task_manager.push(
+                    view,
+                    Self::sync(
+                        adapter.clone(),
+                        SyncRequest::Since {
+                            view: carnot.high_qc().view,
+                        },
+                        Vec::new(),
+                        None,
+                        timeout,
+                    ),
+                );
                 // keep timeout until the situation is resolved
                 task_manager.push(view, async move {
                     tokio::time::sleep(timeout).await;
@@ -478,6 +708,7 @@ where
             Event::NewView {
                 timeout_qc,
                 new_views,
+                ..
             } => {
                 (carnot, output) = Self::approve_new_view(
                     carnot,
@@ -485,53 +716,253 @@ where
                     new_views,
                     task_manager,
                     adapter.clone(),
+                    public_keys.clone(),
                 )
                 .await;
             }
             Event::TimeoutQc { timeout_qc } => {
-                (carnot, output) =
-                    Self::receive_timeout_qc(carnot, timeout_qc, task_manager, adapter.clone())
-                        .await;
+                (carnot, output) = Self::receive_timeout_qc(
+                    carnot,
+                    timeout_qc,
+                    task_manager,
+                    adapter.clone(),
+                    public_keys.clone(),
+                )
+                .await;
             }
-            Event::RootTimeout { timeouts } => {
-                (carnot, output) = Self::process_root_timeout(carnot, timeouts).await;
+            Event::RootTimeout {
+                timeouts,
+                signature,
+            } => {
+                (carnot, output) = Self::process_root_timeout(carnot, timeouts, signature).await;
             }
-            Event::ProposeBlock { qc } => {
+            Event::ProposeBlock { qc, contents } => {
                 output = Self::propose_block(
                     carnot.id(),
                     private_key,
                     qc,
+                    contents,
                     tx_selector.clone(),
                     blobl_selector.clone(),
                     cl_mempool_relay,
                     da_mempool_relay,
+                    ledger,
                 )
                 .await;
             }
             _ => {}
         }
 
+        if carnot.latest_committed_view() > prev_committed_view {
+            Self::commit_blocks(&carnot, ledger, &storage_relay).await;
+        }
+
         let current_view = carnot.current_view();
         if current_view != prev_view {
+            if carnot.last_view_timeout_qc().map(|qc| qc.view()) != prev_timeout_qc_view {
+                view_timeout.on_timeout_qc();
+            }
+            if carnot.latest_committed_view() > prev_committed_view {
+                view_timeout.on_commit();
+            }
             Self::process_view_change(
                 carnot.clone(),
                 prev_view,
                 task_manager,
                 adapter.clone(),
-                timeout,
+                view_timeout.get(),
+                public_keys,
+                monitor,
             )
             .await;
         }
 
+        if changes_state {
+            Self::persist_checkpoint(&carnot, ledger, &storage_relay, output.is_some()).await;
+        }
+
         if let Some(output) = output {
-            handle_output(&adapter, carnot.id(), output).await;
+            handle_output(&adapter, carnot.id(), &private_key, output).await;
         }
 
         carnot
     }
 
-    #[allow(clippy::type_complexity)]
-    #[instrument(level = "debug", skip(adapter, task_manager, stream, storage_relay))]
+    /// Persist the engine state and the committed ledger so that they can be resumed after a
+    /// restart.
+    ///
+    /// Both are written in the same batch, which backends make durable once applied.
+    /// When `wait` is set we also wait for the storage service to confirm the batch was
+    /// applied. This must be done before sending any vote, otherwise a node restarting right
+    /// after voting could vote again in the same view.
+    async fn persist_checkpoint(
+        carnot: &Carnot<O>,
+        ledger: &LedgerState,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+        wait: bool,
+    ) {
+        let mut batch = WriteBatch::new();
+        <StorageMsg<Storage>>::batch_store(
+            &mut batch,
+            Column::Metadata,
+            CHECKPOINT_KEY,
+            carnot.checkpoint(),
+        );
+        <StorageMsg<Storage>>::batch_store(
+            &mut batch,
+            Column::Metadata,
+            LEDGER_KEY,
+            ledger.committed(),
+        );
+        let (msg, reply) = <StorageMsg<_>>::new_write_batch_message(batch);
+        if let Err((e, _msg)) = storage_relay.send(msg).await {
+            tracing::error!("Could not send checkpoint to storage: {e}");
+            return;
+        }
+        if !wait {
+            return;
+        }
+        match reply.into_inner().await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!("Could not write checkpoint to storage: {e}"),
+            Err(e) => tracing::error!("Could not write checkpoint to storage: {e}"),
+        }
+    }
+
+    /// Load the engine state persisted by a previous run, if any, together with the
+    /// committed blocks that are not part of the checkpoint
+    async fn resume(
+        id: NodeId,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) -> Option<Carnot<O>> {
+        let (msg, reply) = <StorageMsg<_>>::new_load_from_message(Column::Metadata, CHECKPOINT_KEY);
+        if let Err((e, _msg)) = storage_relay.send(msg).await {
+            tracing::error!("Could not load checkpoint from storage: {e}");
+            return None;
+        }
+        let checkpoint = reply.recv::<Checkpoint<O>>().await.ok()??;
+        if checkpoint.id != id {
+            tracing::warn!("ignoring checkpoint of node {:?}", checkpoint.id);
+            return None;
+        }
+
+        let mut next = checkpoint
+            .safe_blocks
+            .values()
+            .filter(|block| block.view != View::new(0))
+            .min_by_key(|block| block.view)
+            .map(|block| block.parent());
+        let mut ancestors = Vec::new();
+        while let Some(block_id) =
+            next.filter(|block_id| !checkpoint.safe_blocks.contains_key(block_id))
+        {
+            let (msg, reply) = <StorageMsg<_>>::new_load_message(block_id);
+            if let Err((e, _msg)) = storage_relay.send(msg).await {
+                tracing::error!("Could not load block from storage: {e}");
+                break;
+            }
+            let Ok(Some(block)) = reply.recv::<Block<ClPool::Item, DaPool::Item>>().await else {
+                tracing::warn!("block {block_id:?} missing from storage");
+                break;
+            };
+            let header = block.header().clone();
+            next = Some(header.parent());
+            ancestors.push(header);
+        }
+        Some(Carnot::from_checkpoint(checkpoint, ancestors))
+    }
+
+    /// Load the ledger persisted by a previous run, then execute the blocks it is missing:
+    /// the ones committed since it was persisted and the uncommitted ones the engine keeps.
+    async fn resume_ledger(
+        carnot: &Carnot<O>,
+        genesis: Ledger,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) -> LedgerState {
+        let (msg, reply) = <StorageMsg<_>>::new_load_from_message(Column::Metadata, LEDGER_KEY);
+        let persisted = match storage_relay.send(msg).await {
+            Ok(()) => reply.recv::<Ledger>().await.ok().flatten(),
+            Err((e, _msg)) => {
+                tracing::error!("Could not load ledger from storage: {e}");
+                None
+            }
+        };
+        let mut ledger = LedgerState::new(persisted.unwrap_or(genesis));
+        Self::commit_blocks(carnot, &mut ledger, storage_relay).await;
+
+        let committed_view = carnot.latest_committed_view();
+        let mut pending = carnot
+            .safe_blocks()
+            .values()
+            .filter(|block| block.view > committed_view)
+            .collect::<Vec<_>>();
+        pending.sort_by_key(|block| block.view);
+        for header in pending {
+            let Some(block) = Self::load_block(header.id, storage_relay).await else {
+                continue;
+            };
+            // blocks kept by the engine were accepted already
+            match ledger.validate(&block) {
+                Ok(state) => ledger.accept(header, state),
+                Err(e) => tracing::warn!("could not execute block {:?}: {e}", header.id),
+            }
+        }
+        ledger
+    }
+
+    /// Apply the blocks committed since the ledger tip, from the oldest to the newest.
+    /// The resulting ledger is persisted with the next checkpoint.
+    async fn commit_blocks(
+        carnot: &Carnot<O>,
+        ledger: &mut LedgerState,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) {
+        let tip = ledger.committed().tip();
+        let committed = carnot.latest_committed_blocks();
+        let Some(new) = committed.iter().position(|id| *id == tip) else {
+            tracing::error!("ledger tip {tip:?} is not a committed block");
+            return;
+        };
+        for id in committed[..new].iter().rev() {
+            let Some(block) = Self::load_block(*id, storage_relay).await else {
+                break;
+            };
+            if let Err(e) = ledger.commit(&block) {
+                tracing::error!("could not apply committed block {id:?}: {e}");
+                break;
+            }
+        }
+    }
+
+    async fn load_block(
+        id: BlockId,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) -> Option<Block<ClPool::Item, DaPool::Item>> {
+        let (msg, reply) = <StorageMsg<_>>::new_load_message(id);
+        if let Err((e, _msg)) = storage_relay.send(msg).await {
+            tracing::error!("Could not load block from storage: {e}");
+            return None;
+        }
+        let block = reply.recv().await.ok().flatten();
+        if block.is_none() {
+            tracing::warn!("block {id:?} missing from storage");
+        }
+        block
+    }
+
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    #[instrument(
+        level = "debug",
+        skip(
+            adapter,
+            task_manager,
+            stream,
+            storage_relay,
+            public_keys,
+            prefetch,
+            ledger
+        )
+    )]
     async fn process_block(
         mut carnot: Carnot<O>,
         block: Block<ClPool::Item, DaPool::Item>,
@@ -539,6 +970,13 @@ where
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
         adapter: A,
         storage_relay: OutboundRelay<StorageMsg<Storage>>,
+        public_keys: &PublicKeys,
+        timeout: Duration,
+        prefetch: Option<(
+            OutboundRelay<MempoolMsg<ClPool::Item, ClPool::Key>>,
+            OutboundRelay<MempoolMsg<DaPool::Item, DaPool::Key>>,
+        )>,
+        ledger: &mut LedgerState,
     ) -> (Carnot<O>, Option<Output<ClPool::Item, DaPool::Item>>) {
         tracing::debug!("received proposal {:?}", block);
         if carnot.highest_voted_view() >= block.header().view {
@@ -549,20 +987,60 @@ where
         let original_block = block;
         let block = original_block.header().clone();
 
-        let self_committee = carnot.self_committee();
-        let leader_committee = [carnot.id()].into_iter().collect();
+        // the parent qc must have been signed by the root committee of the view it was built in
+        if let Err(e) = block
+            .parent_qc
+            .verify(&carnot.qc_signers(), |id| public_keys.get(id).copied())
+        {
+            tracing::debug!("invalid parent qc for block {:?}: {e}", block.id);
+            return (carnot, None);
+        }
 
-        let tally_settings = CarnotTallySettings {
-            threshold: carnot.super_majority_threshold(),
-            participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
-        };
-        let leader_tally_settings = CarnotTallySettings {
-            threshold: carnot.leader_super_majority_threshold(),
-            // TODO: add children of root committee
-            participating_nodes: carnot.root_committee(),
+        if let Err(e) = verify_beacon(&original_block) {
+            tracing::debug!("invalid random beacon for block {:?}: {e}", block.id);
+            return (carnot, None);
+        }
+
+        if !carnot.safe_blocks().contains_key(&block.parent()) {
+            tracing::debug!("missing parent for block {:?}, syncing", block.id);
+            let request = SyncRequest::Ancestors {
+                block: block.parent(),
+                depth: MAX_SYNC_BLOCKS,
+            };
+            let proposal = Event::Proposal {
+                block: original_block,
+                stream,
+            };
+            task_manager.push(
+                block.view,
+                Self::sync(
+                    adapter,
+                    request,
+                    Vec::new(),
+                    Some(Box::new(proposal)),
+                    timeout,
+                ),
+            );
+            return (carnot, None);
+        }
+
+        let state = match ledger.validate(&original_block) {
+            Ok(state) => state,
+            Err(e) => {
+                tracing::debug!("invalid state root for block {:?}: {e}", block.id);
+                return (carnot, None);
+            }
         };
 
-        match carnot.receive_block(block.clone()) {
+        let tally = carnot.is_validator().then(|| {
+            let tally_settings = CarnotTallySettings {
+                threshold: carnot.super_majority_threshold(),
+                participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
+            };
+            (carnot.self_committee(), tally_settings)
+        });
+
+        match receive_block(&carnot, ledger, &original_block, state) {
             Ok(mut new_state) => {
                 let new_view = new_state.current_view();
                 let msg = <StorageMsg<_>>::new_store_message(block.id, original_block.clone());
@@ -570,15 +1048,22 @@ where
                     tracing::error!("Could not send block to storage: {e}");
                 }
                 if new_view != carnot.current_view() {
-                    task_manager.push(
-                        block.view,
-                        Self::gather_votes(
-                            adapter.clone(),
-                            self_committee,
-                            block.clone(),
-                            tally_settings,
-                        ),
-                    );
+                    if let Some((self_committee, tally_settings)) = tally {
+                        task_manager.push(
+                            block.view,
+                            Self::gather_votes(
+                                adapter.clone(),
+                                self_committee,
+                                block.clone(),
+                                tally_settings,
+                            ),
+                        );
+                    }
+                    // keep watching the proposals of this view for conflicting ones
+                    task_manager.push(block.view, async move {
+                        while stream.next().await.is_some() {}
+                        Event::None
+                    });
                     new_state = Self::update_overlay(
                         new_state,
                         |leader_selection| leader_selection.on_new_block_received(&original_block),
@@ -601,28 +1086,267 @@ where
         }
 
         if carnot.is_next_leader() {
+            // root committee members vote once the overlay has been updated with this block,
+            // so votes are tallied against the updated overlay as well
+            let leader_committee = [carnot.id()].into_iter().collect();
+            let leader_tally_settings = CarnotTallySettings {
+                threshold: carnot.leader_super_majority_threshold(),
+                // TODO: add children of root committee
+                participating_nodes: carnot.root_committee(),
+            };
+            // in pipelined mode the contents of the proposal are fetched while the votes are
+            // gathered, as they only depend on the block being voted
+            let contents = prefetch.map(|(cl_mempool_relay, da_mempool_relay)| {
+                Self::fetch_proposal_contents(cl_mempool_relay, da_mempool_relay, block.id)
+            });
             task_manager.push(block.view, async move {
-                let Event::Approve { qc, .. } =
-                    Self::gather_votes(adapter, leader_committee, block, leader_tally_settings)
-                        .await
-                else {
-                    unreachable!()
+                let (event, contents) = futures::join!(
+                    Self::gather_votes(
+                        adapter,
+                        leader_committee,
+                        block,
+                        leader_tally_settings,
+                    ),
+                    OptionFuture::from(contents),
+                );
+                let Event::Approve { qc, .. } = event else {
+                    tracing::debug!("Failed to gather votes");
+                    return Event::None;
                 };
-                Event::ProposeBlock { qc }
+                Event::ProposeBlock {
+                    qc,
+                    contents: contents.flatten(),
+                }
             });
         }
 
         (carnot, None)
     }
 
+    /// Ask peers for the blocks described by `request` and wait for the first valid answer.
+    /// Blocks in `pending` were already fetched and must extend the requested ones, while
+    /// `then` is processed once all the blocks have been replayed.
+    #[instrument(level = "debug", skip(adapter, pending, then))]
+    async fn sync(
+        adapter: A,
+        request: SyncRequest,
+        pending: Vec<Block<ClPool::Item, DaPool::Item>>,
+        then: Option<Box<Event<ClPool::Item, DaPool::Item>>>,
+        timeout: Duration,
+    ) -> Event<ClPool::Item, DaPool::Item> {
+        let responses = adapter.sync(request).await;
+        let first_pending = pending.first().map(|block| block.header().parent());
+        let mut responses = responses.filter_map(|msg| {
+            let blocks = match msg
+                .response
+                .blocks
+                .iter()
+                .map(|data| Block::from_bytes(data))
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(blocks) => blocks,
+                Err(e) => {
+                    // honest peers only serve blocks they could decode themselves
+                    tracing::debug!("undecodable block in sync response: {e}");
+                    msg.penalize();
+                    return futures::future::ready(None);
+                }
+            };
+            if !sync::has_well_formed_qcs(&blocks) {
+                // such qcs can't be built by honest nodes, so they can't be in their chain either
+                tracing::debug!("malformed qc in sync response");
+                msg.penalize();
+                return futures::future::ready(None);
+            }
+            let tip = blocks.last().map(|block| block.header().id);
+            let valid = tip.is_some()
+                && sync::is_chain(&blocks)
+                && match request {
+                    SyncRequest::Ancestors { block, .. } => tip == Some(block),
+                    SyncRequest::Since { .. } => first_pending.is_none(),
+                };
+            futures::future::ready(valid.then_some(blocks))
+        });
+        match tokio::time::timeout(timeout, responses.next()).await {
+            Ok(Some(mut blocks)) => {
+                blocks.extend(pending);
+                Event::Synced { blocks, then }
+            }
+            _ => {
+                tracing::debug!("no valid response to sync request {request:?}");
+                Event::None
+            }
+        }
+    }
+
+    /// Feed blocks fetched from peers to the engine, from the oldest to the newest.
+    ///
+    /// Replayed blocks are not voted for, as the rest of the network already moved past them.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(
+        level = "debug",
+        skip(
+            blocks,
+            then,
+            task_manager,
+            adapter,
+            storage_relay,
+            public_keys,
+            ledger
+        )
+    )]
+    async fn replay_blocks(
+        mut carnot: Carnot<O>,
+        blocks: Vec<Block<ClPool::Item, DaPool::Item>>,
+        then: Option<Box<Event<ClPool::Item, DaPool::Item>>>,
+        task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
+        adapter: A,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+        public_keys: &PublicKeys,
+        timeout: Duration,
+        ledger: &mut LedgerState,
+    ) -> Carnot<O> {
+        let Some(first) = blocks.first().map(|block| block.header().clone()) else {
+            return carnot;
+        };
+        if !carnot.safe_blocks().contains_key(&first.parent()) {
+            // we are further behind than a single response, keep going backwards
+            let request = SyncRequest::Ancestors {
+                block: first.parent(),
+                depth: MAX_SYNC_BLOCKS,
+            };
+            task_manager.push(
+                first.view,
+                Self::sync(adapter, request, blocks, then, timeout),
+            );
+            return carnot;
+        }
+
+        for block in blocks {
+            let header = block.header().clone();
+            if carnot.safe_blocks().contains_key(&header.id) {
+                continue;
+            }
+            // the parent qc is checked by a replay copy of the engine moved to the view of the
+            // qc, so that its validator set is the one of the epoch the qc was formed in, and the
+            // copy is only kept once the qc is known to be valid
+            let Some(replay) = Self::advance_to_qc(carnot.clone(), &header.parent_qc) else {
+                tracing::debug!("malformed parent qc for synced block {:?}", header.id);
+                return carnot;
+            };
+            if let Err(e) = header
+                .parent_qc
+                .verify(&replay.qc_signers(), |id| public_keys.get(id).copied())
+            {
+                tracing::debug!("invalid parent qc for synced block {:?}: {e}", header.id);
+                return carnot;
+            }
+            if let Err(e) = verify_beacon(&block) {
+                tracing::debug!(
+                    "invalid random beacon for synced block {:?}: {e}",
+                    header.id
+                );
+                return carnot;
+            }
+            let state = match ledger.validate(&block) {
+                Ok(state) => state,
+                Err(e) => {
+                    tracing::debug!("invalid state root for synced block {:?}: {e}", header.id);
+                    return carnot;
+                }
+            };
+            match receive_block(&replay, ledger, &block, state) {
+                Ok(new_state) => {
+                    let msg = <StorageMsg<_>>::new_store_message(header.id, block.clone());
+                    if let Err((e, _msg)) = storage_relay.send(msg).await {
+                        tracing::error!("Could not send block to storage: {e}");
+                    }
+                    carnot = if new_state.current_view() != replay.current_view() {
+                        Self::update_overlay(
+                            new_state,
+                            |leader_selection| leader_selection.on_new_block_received(&block),
+                            |committee_membership| {
+                                committee_membership.on_new_block_received(&block)
+                            },
+                        )
+                    } else {
+                        new_state
+                    };
+                }
+                Err(_) => {
+                    tracing::debug!("could not replay synced block {:?}", header);
+                    return carnot;
+                }
+            }
+        }
+
+        if let Some(then) = then {
+            task_manager.push(carnot.current_view().next(), async move { *then });
+        }
+        carnot
+    }
+
+    /// Move a lagging engine to the view of `qc`, or `None` if `qc` can't have been built by
+    /// honest nodes.
+    ///
+    /// An aggregated qc claims that the rest of the network timed out while we were behind, so
+    /// the overlay goes through the same changes as for the timeout qc preceding it, switching
+    /// to the validator set of the epoch of that view if it changed. The timeout qc itself is
+    /// not known, so none is recorded by the engine. The result must only be kept once `qc`
+    /// is verified against its overlay.
+    fn advance_to_qc(carnot: Carnot<O>, qc: &Qc) -> Option<Carnot<O>> {
+        match qc {
+            Qc::Aggregated(qc) if carnot.current_view() < qc.view => {
+                let timeout_qc =
+                    TimeoutQc::try_new(qc.view.prev(), qc.high_qc.clone(), carnot.id())?;
+                Some(Self::update_overlay(
+                    carnot.catch_up_to(qc),
+                    |leader_selection| leader_selection.on_timeout_qc_received(&timeout_qc),
+                    |committee_membership| committee_membership.on_timeout_qc_received(&timeout_qc),
+                ))
+            }
+            _ => Some(carnot),
+        }
+    }
+
+    /// Serve the blocks requested by a peer that is catching up.
+    /// Peers are always answered, with no blocks if we can't help, so that they can move on.
+    fn process_sync_request(
+        carnot: &Carnot<O>,
+        msg: IncomingSyncRequest,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+    ) {
+        let request = msg.request;
+        let ids = sync::requested_blocks(carnot.safe_blocks(), &carnot.high_qc(), request);
+        tokio::spawn(async move {
+            let mut blocks = Vec::with_capacity(ids.len());
+            for id in ids {
+                let (load, reply) = <StorageMsg<_>>::new_load_message(id);
+                if let Err((e, _msg)) = storage_relay.send(load).await {
+                    tracing::error!("Could not load block from storage: {e}");
+                    blocks.clear();
+                    break;
+                }
+                match reply.recv::<Block<ClPool::Item, DaPool::Item>>().await {
+                    Ok(Some(block)) => blocks.push(block.as_bytes().to_vec().into_boxed_slice()),
+                    // only a contiguous chain is useful to the requester
+                    _ => blocks.clear(),
+                }
+            }
+            // an error only means the requester is not waiting anymore
+            let _ = msg.reply.send(sync::fit_response(request, blocks));
+        });
+    }
+
     #[allow(clippy::type_complexity)]
-    #[instrument(level = "debug", skip(task_manager, adapter))]
+    #[instrument(level = "debug", skip(task_manager, adapter, public_keys))]
     async fn approve_new_view(
         carnot: Carnot<O>,
         timeout_qc: TimeoutQc,
         new_views: HashSet<NewView>,
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
         adapter: A,
+        public_keys: Arc<PublicKeys>,
     ) -> (Carnot<O>, Option<Output<ClPool::Item, DaPool::Item>>) {
         let leader_committee = [carnot.id()].into_iter().collect();
         let leader_tally_settings = CarnotTallySettings {
@@ -630,23 +1354,25 @@ where
             // TODO: add children of root committee
             participating_nodes: carnot.root_committee(),
         };
+        let verifier = Self::timeout_qc_verifier(&carnot, public_keys);
         let (new_carnot, out) = carnot.approve_new_view(timeout_qc.clone(), new_views);
         let new_view = timeout_qc.view().next();
         if carnot.is_next_leader() {
-            let high_qc = carnot.high_qc();
             task_manager.push(new_view, async move {
-                let _votes = Self::gather_new_views(
+                let Event::NewView { qc, .. } = Self::gather_new_views(
                     adapter,
                     leader_committee,
                     timeout_qc,
                     leader_tally_settings.clone(),
+                    verifier,
                 )
-                .await;
+                .await
+                else {
+                    return Event::None;
+                };
                 Event::ProposeBlock {
-                    qc: Qc::Aggregated(AggregateQc {
-                        high_qc,
-                        view: new_view,
-                    }),
+                    qc: Qc::Aggregated(qc),
+                    contents: None,
                 }
             });
         }
@@ -655,23 +1381,32 @@ where
     }
 
     #[allow(clippy::type_complexity)]
-    #[instrument(level = "debug", skip(task_manager, adapter))]
+    #[instrument(level = "debug", skip(task_manager, adapter, public_keys))]
     async fn receive_timeout_qc(
         carnot: Carnot<O>,
         timeout_qc: TimeoutQc,
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
         adapter: A,
+        public_keys: Arc<PublicKeys>,
     ) -> (Carnot<O>, Option<Output<ClPool::Item, DaPool::Item>>) {
         let mut new_state = carnot.receive_timeout_qc(timeout_qc.clone());
-        let self_committee = carnot.self_committee();
-        let tally_settings = CarnotTallySettings {
-            threshold: carnot.super_majority_threshold(),
-            participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
-        };
-        task_manager.push(
-            timeout_qc.view().next(),
-            Self::gather_new_views(adapter, self_committee, timeout_qc.clone(), tally_settings),
-        );
+        if carnot.is_validator() {
+            let self_committee = carnot.self_committee();
+            let tally_settings = CarnotTallySettings {
+                threshold: carnot.super_majority_threshold(),
+                participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
+            };
+            task_manager.push(
+                timeout_qc.view().next(),
+                Self::gather_new_views(
+                    adapter,
+                    self_committee,
+                    timeout_qc.clone(),
+                    tally_settings,
+                    Self::timeout_qc_verifier(&carnot, public_keys),
+                ),
+            );
+        }
         if carnot.current_view() != new_state.current_view() {
             new_state = Self::update_overlay(
                 new_state,
@@ -687,6 +1422,7 @@ where
     async fn process_root_timeout(
         carnot: Carnot<O>,
         timeouts: HashSet<Timeout>,
+        signature: AggregateSignature,
     ) -> (Carnot<O>, Option<Output<ClPool::Item, DaPool::Item>>) {
         // we might have received a timeout_qc sent by some other node and advanced the view
         // already, in which case we should ignore the timeout
@@ -715,8 +1451,15 @@ where
             .clone();
         let mut output = None;
         if carnot.is_member_of_root_committee() {
-            let timeout_qc = TimeoutQc::new(carnot.current_view(), high_qc, carnot.id());
-            output = Some(Output::BroadcastTimeoutQc { timeout_qc });
+            // the high qcs come from the timeouts of other nodes
+            let Some(timeout_qc) = TimeoutQc::try_new(carnot.current_view(), high_qc, carnot.id())
+            else {
+                tracing::debug!("timeouts carry a high qc from the future");
+                return (carnot, None);
+            };
+            output = Some(Output::BroadcastTimeoutQc {
+                timeout_qc: timeout_qc.with_signature(signature),
+            });
         }
         (carnot, output)
     }
@@ -724,6 +1467,7 @@ where
     #[instrument(
         level = "debug",
         skip(
+            contents,
             cl_mempool_relay,
             da_mempool_relay,
             private_key,
@@ -731,39 +1475,86 @@ where
             blob_selector
         )
     )]
+    #[allow(clippy::too_many_arguments)]
     async fn propose_block(
         id: NodeId,
         private_key: PrivateKey,
         qc: Qc,
+        contents: Option<ProposalContents<ClPool::Item, DaPool::Item>>,
         tx_selector: TxS,
         blob_selector: BS,
         cl_mempool_relay: OutboundRelay<MempoolMsg<ClPool::Item, ClPool::Key>>,
         da_mempool_relay: OutboundRelay<MempoolMsg<DaPool::Item, DaPool::Key>>,
+        ledger: &LedgerState,
     ) -> Option<Output<ClPool::Item, DaPool::Item>> {
-        let mut output = None;
-        let cl_txs = get_mempool_contents(cl_mempool_relay);
-        let da_certs = get_mempool_contents(da_mempool_relay);
+        let contents = match contents {
+            Some(contents) => Some(contents),
+            None => {
+                Self::fetch_proposal_contents(cl_mempool_relay, da_mempool_relay, qc.block()).await
+            }
+        };
+        let (cl_txs, da_certs) = contents?;
+        let beacon = RandomBeaconState::generate_happy(qc.view(), &private_key);
+        // the proposal must fit in a block, so contents are only selected within the space
+        // left by the same block without any, transactions first
+        let empty = Block::<ClPool::Item, DaPool::Item>::new(
+            qc.view().next(),
+            qc.clone(),
+            std::iter::empty(),
+            std::iter::empty(),
+            id,
+            beacon.clone(),
+            [0; 32],
+        );
+        let mut budget = match empty.encoded_size() {
+            Ok(size) => MAX_BLOCK_SIZE.saturating_sub(size),
+            Err(e) => {
+                tracing::error!("could not encode proposal: {e}");
+                return None;
+            }
+        };
+        let selected = fit_in_block(tx_selector.select_tx_from(cl_txs.into_iter()), &mut budget);
+        let da_certs = fit_in_block(da_certs, &mut budget);
+        // transactions that can't be executed on top of the parent are left out
+        let Some((cl_txs, state_root)) = ledger.execute(qc.block(), selected) else {
+            tracing::error!("missing ledger state of block {:?}", qc.block());
+            return None;
+        };
+        let Ok(proposal) = BlockBuilder::new(tx_selector, blob_selector)
+            .with_view(qc.view().next())
+            .with_parent_qc(qc)
+            .with_proposer(id)
+            .with_beacon_state(beacon)
+            .with_state_root(state_root)
+            .with_transactions(cl_txs.into_iter())
+            .with_blobs_certificates(da_certs.into_iter())
+            .build()
+        else {
+            panic!("Proposal block should always succeed to be built")
+        };
+        Some(Output::BroadcastProposal { proposal })
+    }
+
+    /// Transactions and blob certificates not yet included in the ancestors of `parent`
+    async fn fetch_proposal_contents(
+        cl_mempool_relay: OutboundRelay<MempoolMsg<ClPool::Item, ClPool::Key>>,
+        da_mempool_relay: OutboundRelay<MempoolMsg<DaPool::Item, DaPool::Key>>,
+        parent: BlockId,
+    ) -> Option<ProposalContents<ClPool::Item, DaPool::Item>> {
+        let cl_txs = get_mempool_contents(cl_mempool_relay, parent);
+        let da_certs = get_mempool_contents(da_mempool_relay, parent);
 
         match futures::join!(cl_txs, da_certs) {
-            (Ok(cl_txs), Ok(da_certs)) => {
-                let beacon = RandomBeaconState::generate_happy(qc.view(), &private_key);
-                let Ok(proposal) = BlockBuilder::new(tx_selector, blob_selector)
-                    .with_view(qc.view().next())
-                    .with_parent_qc(qc)
-                    .with_proposer(id)
-                    .with_beacon_state(beacon)
-                    .with_transactions(cl_txs)
-                    .with_blobs_certificates(da_certs)
-                    .build()
-                else {
-                    panic!("Proposal block should always succeed to be built")
-                };
-                output = Some(Output::BroadcastProposal { proposal });
+            (Ok(cl_txs), Ok(da_certs)) => Some((cl_txs.collect(), da_certs.collect())),
+            (Err(_), _) => {
+                tracing::error!("Could not fetch block cl transactions");
+                None
+            }
+            (_, Err(_)) => {
+                tracing::error!("Could not fetch block da certificates");
+                None
             }
-            (Err(_), _) => tracing::error!("Could not fetch block cl transactions"),
-            (_, Err(_)) => tracing::error!("Could not fetch block da certificates"),
         }
-        output
     }
 
     async fn process_view_change(
@@ -772,10 +1563,14 @@ where
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
         adapter: A,
         timeout: Duration,
+        public_keys: Arc<PublicKeys>,
+        monitor: &EquivocationMonitor,
     ) {
         let current_view = carnot.current_view();
         // First we cancel previous processing view tasks
         task_manager.cancel(prev_view);
+        // views can be skipped when catching up, so the window follows the view entered
+        monitor.prune(current_view.prev());
         tracing::debug!("Advanced view from {prev_view} to {current_view}");
         // View change!
         task_manager.push(current_view, async move {
@@ -784,11 +1579,13 @@ where
         });
         task_manager.push(
             current_view.next(),
-            Self::gather_block(adapter.clone(), current_view.next()),
+            Self::gather_block(adapter.clone(), current_view.next(), monitor.clone()),
         );
+        let verifier = Self::timeout_qc_verifier(&carnot, public_keys);
+        adapter.set_timeout_qc_verifier(verifier.clone()).await;
         task_manager.push(
             current_view,
-            Self::gather_timeout_qc(adapter.clone(), current_view),
+            Self::gather_timeout_qc(adapter.clone(), current_view, verifier.clone()),
         );
         if carnot.is_member_of_root_committee() {
             task_manager.push(
@@ -801,19 +1598,36 @@ where
                         threshold: carnot.leader_super_majority_threshold(),
                         participating_nodes: carnot.root_committee(),
                     },
+                    verifier,
                 ),
             );
         }
     }
 
+    fn timeout_qc_verifier(carnot: &Carnot<O>, public_keys: Arc<PublicKeys>) -> TimeoutQcVerifier {
+        TimeoutQcVerifier {
+            view: carnot.current_view(),
+            signers: carnot.qc_signers(),
+            public_keys,
+        }
+    }
+
     async fn gather_timeout_qc(
         adapter: A,
         view: consensus_engine::View,
+        verifier: TimeoutQcVerifier,
     ) -> Event<ClPool::Item, DaPool::Item> {
         if let Some(timeout_qc) = adapter
             .timeout_qc_stream(view)
             .await
             .map(|msg| msg.qc)
+            .filter(|qc| {
+                let verified = verifier
+                    .verify(qc)
+                    .map_err(|e| tracing::debug!("invalid timeout qc {qc:?}: {e}"))
+                    .is_ok();
+                futures::future::ready(verified)
+            })
             .next()
             .await
         {
@@ -841,21 +1655,31 @@ where
         }
     }
 
-    #[instrument(level = "debug", skip(adapter, tally))]
+    #[instrument(level = "debug", skip(adapter, tally, verifier))]
     async fn gather_new_views(
         adapter: A,
         committee: Committee,
         timeout_qc: TimeoutQc,
         tally: CarnotTallySettings,
+        verifier: TimeoutQcVerifier,
     ) -> Event<ClPool::Item, DaPool::Item> {
         let tally = NewViewTally::new(tally);
+        // the new views' high qcs are not signed by the voters, drop the ones that don't check out
         let stream = adapter
             .new_view_stream(&committee, timeout_qc.view().next())
-            .await;
+            .await
+            .filter(move |msg| {
+                let verified = verifier
+                    .verify_high_qc(&msg.vote.high_qc)
+                    .map_err(|e| tracing::debug!("invalid high qc in new view {msg:?}: {e}"))
+                    .is_ok();
+                futures::future::ready(verified)
+            });
         match tally.tally(timeout_qc.clone(), stream).await {
-            Ok((_qc, new_views)) => Event::NewView {
+            Ok((qc, new_views)) => Event::NewView {
                 timeout_qc,
                 new_views,
+                qc,
             },
             Err(_e) => {
                 todo!("Handle tally error {_e}");
@@ -863,38 +1687,61 @@ where
         }
     }
 
-    #[instrument(level = "debug", skip(adapter, tally))]
+    #[instrument(level = "debug", skip(adapter, tally, verifier))]
     async fn gather_timeout(
         adapter: A,
         committee: Committee,
         view: consensus_engine::View,
         tally: CarnotTallySettings,
+        verifier: TimeoutQcVerifier,
     ) -> Event<ClPool::Item, DaPool::Item> {
         let tally = TimeoutTally::new(tally);
-        let stream = adapter.timeout_stream(&committee, view).await;
+        // same as for new views, the timeouts' high qcs end up in the timeout qc unsigned
+        let stream = adapter
+            .timeout_stream(&committee, view)
+            .await
+            .filter(move |msg| {
+                let verified = verifier
+                    .verify_high_qc(&msg.vote.high_qc)
+                    .map_err(|e| tracing::debug!("invalid high qc in timeout {msg:?}: {e}"))
+                    .is_ok();
+                futures::future::ready(verified)
+            });
         match tally.tally(view, stream).await {
-            Ok((_, timeouts)) => Event::RootTimeout { timeouts },
+            Ok((signature, timeouts)) => Event::RootTimeout {
+                timeouts,
+                signature,
+            },
             Err(_e) => {
                 todo!("Handle tally error {_e}");
             }
         }
     }
 
-    #[instrument(level = "debug", skip(adapter))]
+    #[instrument(level = "debug", skip(adapter, monitor))]
     async fn gather_block(
         adapter: A,
         view: consensus_engine::View,
+        monitor: EquivocationMonitor,
     ) -> Event<ClPool::Item, DaPool::Item> {
         let stream = adapter
             .proposal_chunks_stream(view)
             .await
+            .inspect(move |msg| monitor.observe_proposal(msg))
             .filter_map(move |msg| {
                 async move {
-                    let proposal = Block::from_bytes(&msg.data);
-                    if proposal.header().id == msg.proposal {
-                        // TODO: Leader is faulty? what should we do?
+                    let Ok(proposal) = Block::from_bytes(&msg.data) else {
+                        tracing::debug!("undecodable proposal for view {}", msg.view);
+                        return None;
+                    };
+                    let LeaderProof::LeaderId { leader_id } = proposal.header().leader_proof;
+                    if proposal.header().id == msg.proposal
+                        && leader_id == msg.leader
+                        && msg.verify().is_some()
+                    {
                         Some(proposal)
                     } else {
+                        // TODO: Leader is faulty? what should we do?
                         None
                     }
                 }
@@ -946,8 +1793,36 @@ where
     }
 }
 
-async fn handle_output<A, Tx, C>(adapter: &A, node_id: NodeId, output: Output<Tx, C>)
-where
+/// Have the engine receive a block, keeping the ledger `state` after it only if the engine
+/// accepts the block, so that proposals can't build on blocks the engine rejected
+fn receive_block<O: Overlay, Tx: Clone + Eq + Hash, Blob: Clone + Eq + Hash>(
+    carnot: &Carnot<O>,
+    ledger: &mut LedgerState,
+    block: &Block<Tx, Blob>,
+    state: Ledger,
+) -> Result<Carnot<O>, ()> {
+    let new_state = carnot.receive_block(block.header().clone())?;
+    ledger.accept(block.header(), state);
+    Ok(new_state)
+}
+
+/// Check that the random beacon of a block was generated by its proposer for the view of
+/// its parent qc, so that only the elected leader gets to contribute entropy to the overlay.
+fn verify_beacon<Tx: Clone + Eq + Hash, Blob: Clone + Eq + Hash>(
+    block: &Block<Tx, Blob>,
+) -> Result<(), RandomBeaconError> {
+    let LeaderProof::LeaderId { leader_id } = block.header().leader_proof;
+    block
+        .beacon()
+        .verify_happy(block.header().parent_qc.view(), leader_id)
+}
+
+async fn handle_output<A, Tx, C>(
+    adapter: &A,
+    node_id: NodeId,
+    private_key: &PrivateKey,
+    output: Output<Tx, C>,
+) where
     A: NetworkAdapter,
     Tx: Hash + Eq + Clone + Serialize + DeserializeOwned + Debug,
     C: Clone + Eq + Hash + Serialize + DeserializeOwned,
@@ -957,11 +1832,11 @@ where
             Payload::Vote(vote) => {
                 adapter
                     .send(
-                        NetworkMessage::Vote(VoteMsg {
-                            voter: node_id,
+                        NetworkMessage::Vote(VoteMsg::new(
                             vote,
-                            qc: None, // TODO: handle root commmittee members
-                        }),
+                            None, // TODO: handle root commmittee members
+                            private_key,
+                        )),
                         &to,
                     )
                     .await;
@@ -969,10 +1844,7 @@ where
             Payload::Timeout(timeout) => {
                 adapter
                     .send(
-                        NetworkMessage::Timeout(TimeoutMsg {
-                            voter: node_id,
-                            vote: timeout,
-                        }),
+                        NetworkMessage::Timeout(TimeoutMsg::new(timeout, private_key)),
                         &to,
                     )
                     .await;
@@ -980,10 +1852,7 @@ where
             Payload::NewView(new_view) => {
                 adapter
                     .send(
-                        NetworkMessage::NewView(NewViewMsg {
-                            voter: node_id,
-                            vote: new_view,
-                        }),
+                        NetworkMessage::NewView(NewViewMsg::new(new_view, private_key)),
                         &to,
                     )
                     .await;
@@ -991,11 +1860,12 @@ where
         },
         Output::BroadcastProposal { proposal } => {
             adapter
-                .broadcast(NetworkMessage::Proposal(ProposalMsg {
-                    proposal: proposal.header().id,
-                    data: proposal.as_bytes().to_vec().into_boxed_slice(),
-                    view: proposal.header().view,
-                }))
+                .broadcast(NetworkMessage::Proposal(ProposalMsg::new(
+                    proposal.as_bytes().to_vec().into_boxed_slice(),
+                    proposal.header().id,
+                    proposal.header().view,
+                    private_key,
+                )))
                 .await;
         }
         Output::BroadcastTimeoutQc { timeout_qc } => {
@@ -1027,22 +1897,40 @@ enum Event<Tx: Clone + Hash + Eq, BlobCertificate: Clone + Eq + Hash> {
     NewView {
         timeout_qc: TimeoutQc,
         new_views: HashSet<NewView>,
+        qc: AggregateQc,
     },
     TimeoutQc {
         timeout_qc: TimeoutQc,
     },
     RootTimeout {
         timeouts: HashSet<Timeout>,
+        signature: AggregateSignature,
     },
     ProposeBlock {
         qc: Qc,
+        /// Fetched while gathering the votes for the parent block, in pipelined mode
+        contents: Option<ProposalContents<Tx, BlobCertificate>>,
+    },
+    /// Blocks fetched from peers, `then` is processed once they have been replayed
+    Synced {
+        blocks: Vec<Block<Tx, BlobCertificate>>,
+        then: Option<Box<Event<Tx, BlobCertificate>>>,
     },
     None,
 }
 
+/// Transactions and blob certificates included in a proposal
+type ProposalContents<Tx, BlobCertificate> = (Vec<Tx>, Vec<BlobCertificate>);
+
 #[derive(Debug)]
 pub enum ConsensusMsg {
-    Info { tx: Sender<CarnotInfo> },
+    Info {
+        tx: Sender<CarnotInfo>,
+    },
+    /// Verified evidence of misbehaving nodes, from the oldest to the newest
+    Evidence {
+        tx: Sender<Vec<Evidence>>,
+    },
 }
 
 impl RelayMessage for ConsensusMsg {}
@@ -1062,12 +1950,13 @@ pub struct CarnotInfo {
 
 async fn get_mempool_contents<Item, Key>(
     mempool: OutboundRelay<MempoolMsg<Item, Key>>,
+    ancestor_hint: BlockId,
 ) -> Result<Box<dyn Iterator<Item = Item> + Send>, tokio::sync::oneshot::error::RecvError> {
     let (reply_channel, rx) = tokio::sync::oneshot::channel();
 
     mempool
         .send(MempoolMsg::View {
-            ancestor_hint: BlockId::zeros(),
+            ancestor_hint,
             reply_channel,
         })
         .await
@@ -1091,6 +1980,7 @@ mod tests {
             local_high_qc: StandardQc {
                 view: View::new(0),
                 id: BlockId::zeros(),
+                signature: None,
             },
             safe_blocks: HashMap::from([(
                 BlockId::zeros(),
@@ -1100,7 +1990,9 @@ mod tests {
                     parent_qc: Qc::Standard(StandardQc {
                         view: View::new(0),
                         id: BlockId::zeros(),
+                        signature: None,
                     }),
+                    state_root: [0; 32],
                     leader_proof: LeaderProof::LeaderId {
                         leader_id: NodeId::new([0; 32]),
                     },
@@ -1114,10 +2006,151 @@ mod tests {
         eprintln!("{serialized}");
         assert_eq!(
             serialized,
-            r#"{"id":"0x0000000000000000000000000000000000000000000000000000000000000000","current_view":1,"highest_voted_view":-1,"local_high_qc":{"view":0,"id":"0x0000000000000000000000000000000000000000000000000000000000000000"},"safe_blocks":[["0x0000000000000000000000000000000000000000000000000000000000000000",{"view":0,"parent_qc":{"Standard":{"view":0,"id":"0x0000000000000000000000000000000000000000000000000000000000000000"}},"leader_proof":{"LeaderId":{"leader_id":"0x0000000000000000000000000000000000000000000000000000000000000000"}}}]],"last_view_timeout_qc":null,"committed_blocks":["0x0000000000000000000000000000000000000000000000000000000000000000"]}"#
+            r#"{"id":"0x0000000000000000000000000000000000000000000000000000000000000000","current_view":1,"highest_voted_view":-1,"local_high_qc":{"view":0,"id":"0x0000000000000000000000000000000000000000000000000000000000000000","signature":null},"safe_blocks":[["0x0000000000000000000000000000000000000000000000000000000000000000",{"view":0,"parent_qc":{"Standard":{"view":0,"id":"0x0000000000000000000000000000000000000000000000000000000000000000","signature":null}},"state_root":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],"leader_proof":{"LeaderId":{"leader_id":"0x0000000000000000000000000000000000000000000000000000000000000000"}}}]],"last_view_timeout_qc":null,"committed_blocks":["0x0000000000000000000000000000000000000000000000000000000000000000"]}"#
         );
 
         let deserialized: CarnotInfo = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized, info);
     }
+
+    fn proposal(
+        proposer: &PrivateKey,
+        beacon: RandomBeaconState,
+    ) -> nomos_core::block::Block<u8, u8> {
+        nomos_core::block::Block::new(
+            View::new(1),
+            Qc::Standard(StandardQc {
+                view: View::new(0),
+                id: BlockId::zeros(),
+                signature: None,
+            }),
+            [].into_iter(),
+            [].into_iter(),
+            NodeId::from_public_key(&proposer.public_key()),
+            beacon,
+            [0; 32],
+        )
+    }
+
+    #[test]
+    fn beacon_from_leader() {
+        let leader = PrivateKey::new([1; 32]);
+        let beacon = RandomBeaconState::generate_happy(View::new(0), &leader);
+        let block = proposal(&leader, beacon.clone());
+        assert!(verify_beacon(&block).is_ok());
+
+        let state = RandomBeaconState::initial_sad_from_entropy([0; 32]);
+        assert_eq!(
+            UpdateableLeaderSelection::on_new_block_received(&state, &block).unwrap(),
+            beacon
+        );
+        assert_eq!(
+            UpdateableCommitteeMembership::on_new_block_received(&state, &block).unwrap(),
+            beacon
+        );
+    }
+
+    #[test]
+    fn malicious_beacon() {
+        let leader = PrivateKey::new([1; 32]);
+        let other = PrivateKey::new([2; 32]);
+        let state = RandomBeaconState::initial_sad_from_entropy([0; 32]);
+
+        // a valid beacon generated by another node, e.g. ground for a favourable leader
+        let block = proposal(
+            &leader,
+            RandomBeaconState::generate_happy(View::new(0), &other),
+        );
+        assert!(matches!(
+            verify_beacon(&block),
+            Err(RandomBeaconError::NotLeader)
+        ));
+        assert!(UpdateableLeaderSelection::on_new_block_received(&state, &block).is_err());
+        assert!(UpdateableCommitteeMembership::on_new_block_received(&state, &block).is_err());
+
+        // the leader key with a signature by another node
+        let RandomBeaconState::Happy { sig, .. } =
+            RandomBeaconState::generate_happy(View::new(0), &other)
+        else {
+            unreachable!()
+        };
+        let forged = RandomBeaconState::Happy {
+            sig,
+            public_key: leader.public_key(),
+        };
+        assert!(matches!(
+            verify_beacon(&proposal(&leader, forged)),
+            Err(RandomBeaconError::InvalidRandomBeacon)
+        ));
+
+        // a beacon of the leader replayed from another view
+        let replayed = RandomBeaconState::generate_happy(View::new(5), &leader);
+        assert!(matches!(
+            verify_beacon(&proposal(&leader, replayed)),
+            Err(RandomBeaconError::InvalidRandomBeacon)
+        ));
+
+        // leaders always have to generate a happy beacon
+        assert!(matches!(
+            verify_beacon(&proposal(&leader, state)),
+            Err(RandomBeaconError::InvalidRandomBeacon)
+        ));
+    }
+
+    #[test]
+    fn ledger_state_of_rejected_blocks() {
+        use consensus_engine::overlay::{
+            FlatOverlay, FlatOverlaySettings, FreezeMembership, RoundRobin,
+        };
+        use nomos_core::tx::carnot::Tx;
+
+        let leader = PrivateKey::new([1; 32]);
+        let other = PrivateKey::new([2; 32]);
+        let nodes = [&leader, &other].map(|key| NodeId::from_public_key(&key.public_key()));
+        let carnot: Carnot<FlatOverlay<RoundRobin, FreezeMembership>> = Carnot::from_genesis(
+            nodes[0],
+            Block {
+                id: BlockId::zeros(),
+                view: View::new(0),
+                parent_qc: Qc::Standard(StandardQc::genesis()),
+                state_root: [0; 32],
+                leader_proof: LeaderProof::LeaderId {
+                    leader_id: nodes[0],
+                },
+            },
+            FlatOverlay::new(FlatOverlaySettings {
+                nodes: nodes.to_vec(),
+                leader: RoundRobin::default(),
+                leader_super_majority_threshold: None,
+            }),
+        );
+        let mut ledger = LedgerState::new(Ledger::genesis([]));
+        let (_, root) = ledger.execute::<Tx>(BlockId::zeros(), []).unwrap();
+        let block = |proposer: &PrivateKey| {
+            nomos_core::block::Block::<Tx, ()>::new(
+                View::new(1),
+                Qc::Standard(StandardQc {
+                    view: View::new(0),
+                    id: BlockId::zeros(),
+                    signature: None,
+                }),
+                std::iter::empty(),
+                std::iter::empty(),
+                NodeId::from_public_key(&proposer.public_key()),
+                RandomBeaconState::initial_sad_from_entropy([0; 32]),
+                root,
+            )
+        };
+
+        // the block is valid for the ledger but not proposed by the leader
+        let rejected = block(&other);
+        let state = ledger.validate(&rejected).unwrap();
+        assert!(receive_block(&carnot, &mut ledger, &rejected, state).is_err());
+        assert!(ledger.execute::<Tx>(rejected.header().id, []).is_none());
+
+        let accepted = block(&leader);
+        let state = ledger.validate(&accepted).unwrap();
+        assert!(receive_block(&carnot, &mut ledger, &accepted, state).is_ok());
+        assert!(ledger.execute::<Tx>(accepted.header().id, []).is_some());
+    }
 }
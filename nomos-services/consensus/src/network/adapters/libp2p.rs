@@ -1,27 +1,41 @@
 // std
 use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::marker::PhantomData;
 use std::ops::DerefMut;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 // crates
-use serde::{Deserialize, Serialize};
-use tokio::sync::broadcast::error::RecvError;
+use futures::StreamExt;
+use rand::seq::SliceRandom;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
 use tokio::sync::mpsc::{error::TrySendError, Receiver, Sender};
-use tokio_stream::wrappers::ReceiverStream;
+use tokio::sync::oneshot;
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
 // internal
-use crate::network::messages::{NewViewMsg, TimeoutMsg, TimeoutQcMsg};
+use crate::equivocation::EquivocationMonitor;
+use crate::network::messages::{
+    NewViewMsg, SyncRequest, SyncResponseMsg, TimeoutMsg, TimeoutQcMsg,
+};
 use crate::network::{
     messages::{NetworkMessage, ProposalMsg, VoteMsg},
-    BoxedStream, NetworkAdapter,
+    BoxedStream, IncomingSyncRequest, IncomingSyncResponse, NetworkAdapter, TimeoutQcVerifier,
 };
-use consensus_engine::{BlockId, Committee, CommitteeId, View};
+use consensus_engine::{BlockId, Committee, CommitteeId, Evidence, View};
+use nomos_core::block::{Block, MAX_BLOCK_SIZE};
 use nomos_core::wire;
 use nomos_network::{
-    backends::libp2p::{Command, Event, EventKind, Libp2p},
+    backends::libp2p::{
+        message_id, Command, Event, EventKind, IncomingRequest, Libp2p, MessageAcceptance, PeerId,
+        TopicHash,
+    },
     NetworkMsg, NetworkService,
 };
 use overwatch_rs::services::{relay::OutboundRelay, ServiceData};
 
 const TOPIC: &str = "/carnot/proto";
+/// Protocol of the sync requests sent directly to peers
+const SYNC_PROTOCOL: &str = "/carnot/sync";
 // TODO: this could be tailored per message (e.g. we need to store only a few proposals per view but might need a lot of votes)
 const BUFFER_SIZE: usize = 500;
 
@@ -102,10 +116,32 @@ struct Messages {
 
 /// Requesting the same stream type multiple times will re-initialize it and new items will only be forwarded to the latest one.
 /// It's required for the consumer to keep the stream around for the time it's necessary
-#[derive(Clone)]
-pub struct Libp2pAdapter {
+///
+/// `Tx` and `BlobCertificate` are the contents of the proposed blocks, which are decoded to check
+/// them against their signed id before being relayed.
+pub struct Libp2pAdapter<Tx, BlobCertificate> {
     network_relay: OutboundRelay<<NetworkService<Libp2p> as ServiceData>::Message>,
     message_cache: MessageCache,
+    // sync requests are not bound to a view, so they are not kept in the message cache
+    sync_requests: Arc<Mutex<Spsc<IncomingSyncRequest>>>,
+    evidence: broadcast::Sender<Evidence>,
+    timeout_qc_verifier: Arc<RwLock<Option<TimeoutQcVerifier>>>,
+    equivocation_monitor: Arc<RwLock<Option<EquivocationMonitor>>>,
+    _block: PhantomData<fn() -> (Tx, BlobCertificate)>,
+}
+
+impl<Tx, BlobCertificate> Clone for Libp2pAdapter<Tx, BlobCertificate> {
+    fn clone(&self) -> Self {
+        Self {
+            network_relay: self.network_relay.clone(),
+            message_cache: self.message_cache.clone(),
+            sync_requests: self.sync_requests.clone(),
+            evidence: self.evidence.clone(),
+            timeout_qc_verifier: self.timeout_qc_verifier.clone(),
+            equivocation_monitor: self.equivocation_monitor.clone(),
+            _block: PhantomData,
+        }
+    }
 }
 
 impl MessageCache {
@@ -192,18 +228,75 @@ struct GossipsubMessage {
     message: NetworkMessage,
 }
 
+impl wire::WireMessage for GossipsubMessage {
+    const TAG: u16 = wire::tag::CONSENSUS;
+    // proposals carry a whole block
+    const MAX_SIZE: u64 = MAX_BLOCK_SIZE + (64 << 10);
+}
+
 impl GossipsubMessage {
-    pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+    pub fn as_bytes(&self) -> Result<Box<[u8]>, wire::Error> {
+        wire::encode(self).map(Vec::into_boxed_slice)
+    }
+}
+
+/// Checks the signature of signed messages, the rest is left to consensus.
+///
+/// Proposals are only signed by their block id, so the block they carry must decode to that id.
+/// Votes, timeouts and new views are addressed to a committee, so they must say which one.
+/// Evidence is only relayed if it's against a consensus participant.
+/// Messages that can't be judged yet are ignored rather than rejected, so that the peers relaying
+/// them are not penalized.
+fn validate<Tx, BlobCertificate>(
+    GossipsubMessage { to, message }: &GossipsubMessage,
+    timeout_qc_verifier: &RwLock<Option<TimeoutQcVerifier>>,
+    equivocation_monitor: &RwLock<Option<EquivocationMonitor>>,
+) -> MessageAcceptance
+where
+    Tx: Clone + Eq + Hash + Serialize + DeserializeOwned,
+    BlobCertificate: Clone + Eq + Hash + Serialize + DeserializeOwned,
+{
+    let valid = match message {
+        NetworkMessage::Proposal(msg) => {
+            msg.verify().is_some()
+                && Block::<Tx, BlobCertificate>::from_bytes(&msg.data)
+                    .is_ok_and(|block| block.header().id == msg.proposal)
+        }
+        NetworkMessage::Vote(msg) => to.is_some() && msg.verify().is_some(),
+        NetworkMessage::NewView(msg) => to.is_some() && msg.verify().is_some(),
+        NetworkMessage::Timeout(msg) => to.is_some() && msg.verify().is_some(),
+        // timeout qcs can only be checked once the service told us who must have signed them,
+        // which we only know for the current view
+        NetworkMessage::TimeoutQc(msg) => match timeout_qc_verifier.read().unwrap().as_ref() {
+            Some(verifier) if verifier.view == msg.qc.view() => verifier.verify(&msg.qc).is_ok(),
+            _ => return MessageAcceptance::Ignore,
+        },
+        // the consensus participants are only known once the service set the monitor
+        NetworkMessage::Evidence(msg) => match equivocation_monitor.read().unwrap().as_ref() {
+            Some(monitor) => monitor.is_known_offender(msg) && msg.verify().is_ok(),
+            None => return MessageAcceptance::Ignore,
+        },
+    };
+    if valid {
+        MessageAcceptance::Accept
+    } else {
+        MessageAcceptance::Reject
     }
 }
 
-impl Libp2pAdapter {
+impl<Tx, BlobCertificate> Libp2pAdapter<Tx, BlobCertificate> {
     async fn broadcast(&self, message: GossipsubMessage, topic: &str) {
+        let data = match message.as_bytes() {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::error!("could not encode {message:?}: {e}");
+                return;
+            }
+        };
         if let Err((e, message)) = self
             .network_relay
             .send(NetworkMsg::Process(Command::Broadcast {
-                message: message.as_bytes(),
+                message: data,
                 topic: topic.into(),
             }))
             .await
@@ -212,6 +305,109 @@ impl Libp2pAdapter {
         };
     }
 
+    /// Hand a sync request to consensus and send its answer back to the requesting peer
+    fn handle_sync_request(
+        requests: &Mutex<Spsc<IncomingSyncRequest>>,
+        relay: &Relay<Libp2p>,
+        request: IncomingRequest,
+    ) {
+        let Ok(sync_request) = wire::decode::<SyncRequest>(&request.data) else {
+            tracing::debug!("invalid sync request from peer {}", request.peer_id);
+            return;
+        };
+        let (reply, response) = oneshot::channel();
+        requests.lock().unwrap().try_send(IncomingSyncRequest {
+            request: sync_request,
+            reply,
+        });
+        let relay = relay.clone();
+        tokio::spawn(async move {
+            let Ok(response) = response.await else {
+                return;
+            };
+            let data = match wire::encode(&response) {
+                Ok(data) => data.into_boxed_slice(),
+                Err(e) => {
+                    tracing::error!("could not encode sync response: {e}");
+                    return;
+                }
+            };
+            if let Err((e, _)) = relay
+                .send(NetworkMsg::Process(Command::Respond {
+                    request_id: request.id,
+                    data,
+                }))
+                .await
+            {
+                tracing::error!("error answering sync request: {e}");
+            }
+        });
+    }
+
+    /// Ask a single peer for blocks, `None` if the request failed
+    async fn request_blocks(
+        relay: &Relay<Libp2p>,
+        peer_id: PeerId,
+        data: Box<[u8]>,
+    ) -> Option<SyncResponseMsg> {
+        let (reply, response) = oneshot::channel();
+        if let Err((e, _)) = relay
+            .send(NetworkMsg::Process(Command::Request {
+                peer_id,
+                protocol: SYNC_PROTOCOL.into(),
+                data,
+                reply,
+            }))
+            .await
+        {
+            tracing::error!("error sending sync request: {e}");
+            return None;
+        }
+        match response.await {
+            Ok(Ok(data)) => wire::decode(&data)
+                .map_err(|e| tracing::debug!("invalid sync response from peer {peer_id}: {e}"))
+                .ok(),
+            Ok(Err(e)) => {
+                tracing::debug!("sync request to peer {peer_id} failed: {e}");
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    /// Lower the score of a peer that answered a request with invalid data
+    async fn penalize(relay: &Relay<Libp2p>, peer_id: PeerId) {
+        tracing::debug!("penalizing peer {peer_id} for an invalid sync response");
+        if let Err((e, _)) = relay
+            .send(NetworkMsg::Process(Command::Penalize { peer_id }))
+            .await
+        {
+            tracing::error!("error penalizing peer {peer_id}: {e}");
+        }
+    }
+
+    /// Currently connected peers, in random order
+    async fn connected_peers(relay: &Relay<Libp2p>) -> Vec<PeerId> {
+        let (reply, info) = oneshot::channel();
+        if let Err((e, _)) = relay
+            .send(NetworkMsg::Process(Command::Info { reply }))
+            .await
+        {
+            tracing::error!("error requesting network info: {e}");
+            return Vec::new();
+        }
+        let mut peers = info
+            .await
+            .map(|info| info.peers)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|peer| peer.connected)
+            .map(|peer| peer.peer_id)
+            .collect::<Vec<_>>();
+        peers.shuffle(&mut rand::thread_rng());
+        peers
+    }
+
     async fn subscribe(relay: &Relay<Libp2p>, topic: &str) {
         if let Err((e, _)) = relay
             .send(NetworkMsg::Process(Command::Subscribe(topic.into())))
@@ -223,12 +419,24 @@ impl Libp2pAdapter {
 }
 
 #[async_trait::async_trait]
-impl NetworkAdapter for Libp2pAdapter {
+impl<Tx, BlobCertificate> NetworkAdapter for Libp2pAdapter<Tx, BlobCertificate>
+where
+    Tx: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static,
+    BlobCertificate: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
     type Backend = Libp2p;
 
     async fn new(network_relay: Relay<Libp2p>) -> Self {
         let message_cache = MessageCache::new();
         let cache = message_cache.clone();
+        let sync_requests = Arc::new(Mutex::new(Spsc::default()));
+        let requests = sync_requests.clone();
+        let evidence_sender = broadcast::channel(BUFFER_SIZE).0;
+        let evidence = evidence_sender.clone();
+        let timeout_qc_verifier = Arc::new(RwLock::new(None));
+        let verifier = timeout_qc_verifier.clone();
+        let equivocation_monitor = Arc::new(RwLock::new(None));
+        let monitor = equivocation_monitor.clone();
         let relay = network_relay.clone();
         Self::subscribe(&relay, TOPIC).await;
         tracing::debug!("Starting up...");
@@ -236,6 +444,35 @@ impl NetworkAdapter for Libp2pAdapter {
         // to the network to establish connections before we start sending messages
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
 
+        let requests_relay = network_relay.clone();
+        tokio::spawn(async move {
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            if let Err((e, _)) = requests_relay
+                .send(NetworkMsg::Subscribe {
+                    kind: EventKind::Request,
+                    sender,
+                })
+                .await
+            {
+                tracing::error!("error subscribing to incoming requests: {e}");
+            }
+
+            let mut incoming_requests = receiver.await.unwrap();
+            loop {
+                match incoming_requests.recv().await {
+                    Ok(Event::Request(request)) if request.protocol == SYNC_PROTOCOL => {
+                        Self::handle_sync_request(&requests, &requests_relay, request);
+                    }
+                    // requests of other protocols are answered by their own services
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::error!("lagged requests: {n}")
+                    }
+                    Err(RecvError::Closed) => unreachable!(),
+                }
+            }
+        });
+
         // TODO: maybe we need the runtime handle here?
         tokio::spawn(async move {
             let (sender, receiver) = tokio::sync::oneshot::channel();
@@ -250,11 +487,31 @@ impl NetworkAdapter for Libp2pAdapter {
             }
 
             let mut incoming_messages = receiver.await.unwrap();
+            let topic_hash = TopicHash::from_raw(TOPIC);
             loop {
                 match incoming_messages.recv().await {
-                    Ok(Event::Message(message)) => {
-                        match nomos_core::wire::deserialize(&message.data) {
-                            Ok(GossipsubMessage { to, message }) => match message {
+                    Ok(Event::Message(message)) if message.topic == topic_hash => {
+                        let decoded = wire::decode::<GossipsubMessage>(&message.data).ok();
+                        let acceptance = decoded
+                            .as_ref()
+                            .map_or(MessageAcceptance::Reject, |decoded| {
+                                validate::<Tx, BlobCertificate>(decoded, &verifier, &monitor)
+                            });
+                        // ignored messages are not relayed but still kept, consensus checks
+                        // them again once it can
+                        let decoded =
+                            decoded.filter(|_| !matches!(acceptance, MessageAcceptance::Reject));
+                        if let Err((e, _)) = relay
+                            .send(NetworkMsg::Process(Command::ReportValidation {
+                                message_id: message_id(&message),
+                                acceptance,
+                            }))
+                            .await
+                        {
+                            tracing::error!("error reporting message validation: {e}");
+                        }
+                        match decoded {
+                            Some(GossipsubMessage { to, message }) => match message {
                                 NetworkMessage::Proposal(msg) => {
                                     tracing::debug!("received proposal chunk");
                                     let mut cache = cache.cache.lock().unwrap();
@@ -265,12 +522,17 @@ impl NetworkAdapter for Libp2pAdapter {
                                 }
                                 NetworkMessage::Vote(msg) => {
                                     tracing::debug!("received vote");
+                                    // votes are only tallied for the block being voted in the
+                                    // view, so conflicting ones must be caught here
+                                    if let Some(monitor) = monitor.read().unwrap().as_ref() {
+                                        monitor.observe_vote(&msg);
+                                    }
                                     let mut cache = cache.cache.lock().unwrap();
                                     let view = msg.vote.view;
                                     if let Some(messages) = cache.get_mut(&view) {
                                         messages
                                             .votes
-                                            .entry(to.unwrap())
+                                            .entry(to.expect("checked by validate"))
                                             .or_default()
                                             .entry(msg.vote.block)
                                             .or_default()
@@ -284,7 +546,7 @@ impl NetworkAdapter for Libp2pAdapter {
                                     if let Some(messages) = cache.get_mut(&view) {
                                         messages
                                             .timeouts
-                                            .entry(to.unwrap())
+                                            .entry(to.expect("checked by validate"))
                                             .or_default()
                                             .try_send(msg);
                                     }
@@ -304,15 +566,22 @@ impl NetworkAdapter for Libp2pAdapter {
                                     if let Some(messages) = cache.get_mut(&view) {
                                         messages
                                             .new_views
-                                            .entry(to.unwrap())
+                                            .entry(to.expect("checked by validate"))
                                             .or_default()
                                             .try_send(msg);
                                     }
                                 }
+                                NetworkMessage::Evidence(msg) => {
+                                    tracing::debug!("received evidence");
+                                    // an error only means nobody is listening
+                                    let _ = evidence.send(msg);
+                                }
                             },
-                            _ => tracing::debug!("unrecognized gossipsub message"),
+                            None => tracing::debug!("invalid gossipsub message"),
                         }
                     }
+                    // messages of other topics are validated by their own services
+                    Ok(_) => {}
                     Err(RecvError::Lagged(n)) => {
                         tracing::error!("lagged messages: {n}")
                     }
@@ -323,6 +592,11 @@ impl NetworkAdapter for Libp2pAdapter {
         Self {
             network_relay,
             message_cache,
+            sync_requests,
+            evidence: evidence_sender,
+            timeout_qc_verifier,
+            equivocation_monitor,
+            _block: PhantomData,
         }
     }
 
@@ -352,6 +626,14 @@ impl NetworkAdapter for Libp2pAdapter {
             .unwrap_or_else(|| Box::new(tokio_stream::empty()))
     }
 
+    async fn set_timeout_qc_verifier(&self, verifier: TimeoutQcVerifier) {
+        *self.timeout_qc_verifier.write().unwrap() = Some(verifier);
+    }
+
+    async fn set_equivocation_monitor(&self, monitor: EquivocationMonitor) {
+        *self.equivocation_monitor.write().unwrap() = Some(monitor);
+    }
+
     async fn votes_stream(
         &self,
         committee: &Committee,
@@ -378,4 +660,46 @@ impl NetworkAdapter for Libp2pAdapter {
         };
         self.broadcast(message, TOPIC).await;
     }
+
+    async fn sync_request_stream(&self) -> BoxedStream<IncomingSyncRequest> {
+        let requests = self.sync_requests.lock().unwrap().recv_or_restore();
+        Box::new(ReceiverStream::new(requests))
+    }
+
+    async fn sync(&self, request: SyncRequest) -> BoxedStream<IncomingSyncResponse> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        let relay = self.network_relay.clone();
+        tokio::spawn(async move {
+            let data = match wire::encode(&request) {
+                Ok(data) => data.into_boxed_slice(),
+                Err(e) => {
+                    tracing::error!("could not encode sync request: {e}");
+                    return;
+                }
+            };
+            for peer_id in Self::connected_peers(&relay).await {
+                // only ask the next peer once the previous response was consumed
+                let Ok(permit) = sender.reserve().await else {
+                    return;
+                };
+                if let Some(response) = Self::request_blocks(&relay, peer_id, data.clone()).await {
+                    let (response, penalty) = IncomingSyncResponse::new(response);
+                    permit.send(response);
+                    let relay = relay.clone();
+                    tokio::spawn(async move {
+                        // the penalty is dropped once a valid response has been handled
+                        if penalty.await.is_ok() {
+                            Self::penalize(&relay, peer_id).await;
+                        }
+                    });
+                }
+            }
+        });
+        Box::new(ReceiverStream::new(receiver))
+    }
+
+    async fn evidence_stream(&self) -> BoxedStream<Evidence> {
+        let stream = BroadcastStream::new(self.evidence.subscribe());
+        Box::new(stream.filter_map(|msg| futures::future::ready(msg.ok())))
+    }
 }
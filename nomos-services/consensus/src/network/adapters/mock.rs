@@ -1,3 +1,5 @@
+use std::sync::{Arc, RwLock};
+
 use futures::StreamExt;
 use nomos_network::{
     backends::mock::{
@@ -8,12 +10,13 @@ use nomos_network::{
 use overwatch_rs::services::{relay::OutboundRelay, ServiceData};
 use tokio_stream::wrappers::BroadcastStream;
 
-use crate::network::messages::{NetworkMessage, NewViewMsg, TimeoutMsg, TimeoutQcMsg};
+use crate::equivocation::EquivocationMonitor;
+use crate::network::messages::{NetworkMessage, NewViewMsg, SyncRequest, TimeoutMsg, TimeoutQcMsg};
 use crate::network::{
     messages::{ProposalMsg, VoteMsg},
-    BoxedStream, NetworkAdapter,
+    BoxedStream, IncomingSyncRequest, IncomingSyncResponse, NetworkAdapter, TimeoutQcVerifier,
 };
-use consensus_engine::{BlockId, Committee, View};
+use consensus_engine::{BlockId, Committee, Evidence, View};
 
 const MOCK_PUB_SUB_TOPIC: &str = "MockPubSubTopic";
 const MOCK_BLOCK_CONTENT_TOPIC: MockContentTopic = MockContentTopic::new("MockSim", 1, "MockBlock");
@@ -23,6 +26,7 @@ const MOCK_APPROVAL_CONTENT_TOPIC: MockContentTopic =
 #[derive(Clone)]
 pub struct MockAdapter {
     network_relay: OutboundRelay<<NetworkService<Mock> as ServiceData>::Message>,
+    equivocation_monitor: Arc<RwLock<Option<EquivocationMonitor>>>,
 }
 
 impl MockAdapter {
@@ -54,7 +58,10 @@ impl NetworkAdapter for MockAdapter {
     async fn new(
         network_relay: OutboundRelay<<NetworkService<Self::Backend> as ServiceData>::Message>,
     ) -> Self {
-        Self { network_relay }
+        Self {
+            network_relay,
+            equivocation_monitor: Default::default(),
+        }
     }
 
     async fn proposal_chunks_stream(&self, _view: View) -> BoxedStream<ProposalMsg> {
@@ -98,30 +105,45 @@ impl NetworkAdapter for MockAdapter {
         todo!()
     }
 
+    // messages are not relayed by the mock network
+    async fn set_timeout_qc_verifier(&self, _verifier: TimeoutQcVerifier) {}
+
+    async fn set_equivocation_monitor(&self, monitor: EquivocationMonitor) {
+        *self.equivocation_monitor.write().unwrap() = Some(monitor);
+    }
+
     async fn votes_stream(&self, _: &Committee, _: View, _: BlockId) -> BoxedStream<VoteMsg> {
         let stream_channel = self
             .message_subscriber_channel()
             .await
             .unwrap_or_else(|_e| todo!("handle error"));
-        Box::new(Box::pin(BroadcastStream::new(stream_channel).filter_map(
-            |msg| async move {
-                match msg {
-                    Ok(event) => match event {
-                        NetworkEvent::RawMessage(message) => {
-                            if MOCK_APPROVAL_CONTENT_TOPIC.content_topic_name
-                                == message.content_topic().content_topic_name
-                            {
-                                let payload = message.payload();
-                                Some(VoteMsg::from_bytes(payload.as_bytes()))
-                            } else {
-                                None
+        // the mock network hands every vote to every stream, whatever block it is for
+        let monitor = self.equivocation_monitor.read().unwrap().clone();
+        Box::new(Box::pin(
+            BroadcastStream::new(stream_channel)
+                .filter_map(|msg| async move {
+                    match msg {
+                        Ok(event) => match event {
+                            NetworkEvent::RawMessage(message) => {
+                                if MOCK_APPROVAL_CONTENT_TOPIC.content_topic_name
+                                    == message.content_topic().content_topic_name
+                                {
+                                    let payload = message.payload();
+                                    Some(VoteMsg::from_bytes(payload.as_bytes()))
+                                } else {
+                                    None
+                                }
                             }
-                        }
-                    },
-                    Err(_e) => None,
-                }
-            },
-        )))
+                        },
+                        Err(_e) => None,
+                    }
+                })
+                .inspect(move |msg| {
+                    if let Some(monitor) = &monitor {
+                        monitor.observe_vote(msg);
+                    }
+                }),
+        ))
     }
 
     #[allow(clippy::diverging_sub_expression)]
@@ -149,4 +171,16 @@ impl NetworkAdapter for MockAdapter {
             tracing::error!("Failed to forward approval: {:?}", e);
         };
     }
+
+    async fn sync_request_stream(&self) -> BoxedStream<IncomingSyncRequest> {
+        Box::new(tokio_stream::empty())
+    }
+
+    async fn sync(&self, _request: SyncRequest) -> BoxedStream<IncomingSyncResponse> {
+        Box::new(tokio_stream::empty())
+    }
+
+    async fn evidence_stream(&self) -> BoxedStream<Evidence> {
+        Box::new(tokio_stream::empty())
+    }
 }
@@ -1,9 +1,13 @@
 // std
 // crates
+use bls_signatures::{PrivateKey, PublicKey, Serialize as _, Signature};
 use serde::{Deserialize, Serialize};
 // internal
 use crate::NodeId;
-use consensus_engine::{BlockId, NewView, Qc, Timeout, TimeoutQc, View, Vote};
+use consensus_engine::{
+    new_view_payload, proposal_payload, timeout_payload, vote_payload, BlockId, Evidence, NewView,
+    Qc, Timeout, TimeoutQc, View, Vote,
+};
 use nomos_core::wire;
 
 #[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq, Hash)]
@@ -11,9 +15,37 @@ pub struct ProposalMsg {
     pub data: Box<[u8]>,
     pub proposal: BlockId,
     pub view: View,
+    pub leader: NodeId,
+    /// BLS public key of the leader, the leader id is derived from it
+    pub public_key: Box<[u8]>,
+    pub signature: Box<[u8]>,
 }
 
 impl ProposalMsg {
+    pub fn new(data: Box<[u8]>, proposal: BlockId, view: View, private_key: &PrivateKey) -> Self {
+        let (leader, public_key, signature) = sign(private_key, |leader| {
+            proposal_payload(view, proposal, leader)
+        });
+        Self {
+            data,
+            proposal,
+            view,
+            leader,
+            public_key,
+            signature,
+        }
+    }
+
+    /// Returns the leader signature if the proposal was signed by `leader`
+    pub fn verify(&self) -> Option<Signature> {
+        verify(
+            &self.leader,
+            &self.public_key,
+            &self.signature,
+            &proposal_payload(self.view, self.proposal, &self.leader),
+        )
+    }
+
     pub fn as_bytes(&self) -> Box<[u8]> {
         wire::serialize(self).unwrap().into_boxed_slice()
     }
@@ -28,9 +60,35 @@ pub struct VoteMsg {
     pub voter: NodeId,
     pub vote: Vote,
     pub qc: Option<Qc>,
+    /// BLS public key of the voter, the voter id is derived from it
+    pub public_key: Box<[u8]>,
+    pub signature: Box<[u8]>,
 }
 
 impl VoteMsg {
+    pub fn new(vote: Vote, qc: Option<Qc>, private_key: &PrivateKey) -> Self {
+        let (voter, public_key, signature) = sign(private_key, |voter| {
+            vote_payload(vote.view, vote.block, voter)
+        });
+        Self {
+            voter,
+            vote,
+            qc,
+            public_key,
+            signature,
+        }
+    }
+
+    /// Returns the voter signature if the message was signed by `voter`
+    pub fn verify(&self) -> Option<Signature> {
+        verify(
+            &self.voter,
+            &self.public_key,
+            &self.signature,
+            &vote_payload(self.vote.view, self.vote.block, &self.voter),
+        )
+    }
+
     pub fn as_bytes(&self) -> Box<[u8]> {
         wire::serialize(self).unwrap().into_boxed_slice()
     }
@@ -43,9 +101,36 @@ impl VoteMsg {
 pub struct NewViewMsg {
     pub voter: NodeId,
     pub vote: NewView,
+    /// BLS public key of the voter, the voter id is derived from it
+    pub public_key: Box<[u8]>,
+    pub signature: Box<[u8]>,
 }
 
 impl NewViewMsg {
+    pub fn new(vote: NewView, private_key: &PrivateKey) -> Self {
+        let (voter, public_key, signature) =
+            sign(private_key, |voter| new_view_payload(vote.view, voter));
+        Self {
+            voter,
+            vote,
+            public_key,
+            signature,
+        }
+    }
+
+    /// Returns the voter signature if the message was signed by `voter`
+    pub fn verify(&self) -> Option<Signature> {
+        if self.vote.sender != self.voter {
+            return None;
+        }
+        verify(
+            &self.voter,
+            &self.public_key,
+            &self.signature,
+            &new_view_payload(self.vote.view, &self.voter),
+        )
+    }
+
     pub fn as_bytes(&self) -> Box<[u8]> {
         wire::serialize(self).unwrap().into_boxed_slice()
     }
@@ -58,9 +143,36 @@ impl NewViewMsg {
 pub struct TimeoutMsg {
     pub voter: NodeId,
     pub vote: Timeout,
+    /// BLS public key of the voter, the voter id is derived from it
+    pub public_key: Box<[u8]>,
+    pub signature: Box<[u8]>,
 }
 
 impl TimeoutMsg {
+    pub fn new(vote: Timeout, private_key: &PrivateKey) -> Self {
+        let (voter, public_key, signature) =
+            sign(private_key, |voter| timeout_payload(vote.view, voter));
+        Self {
+            voter,
+            vote,
+            public_key,
+            signature,
+        }
+    }
+
+    /// Returns the voter signature if the message was signed by `voter`
+    pub fn verify(&self) -> Option<Signature> {
+        if self.vote.sender != self.voter {
+            return None;
+        }
+        verify(
+            &self.voter,
+            &self.public_key,
+            &self.signature,
+            &timeout_payload(self.vote.view, &self.voter),
+        )
+    }
+
     pub fn as_bytes(&self) -> Box<[u8]> {
         wire::serialize(self).unwrap().into_boxed_slice()
     }
@@ -84,6 +196,33 @@ impl TimeoutQcMsg {
     }
 }
 
+/// Blocks a node is missing and asks its peers for
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SyncRequest {
+    /// `block` and up to `depth - 1` of its ancestors
+    Ancestors { block: BlockId, depth: usize },
+    /// The chain ending at the peer's highest certified block, limited to views after `view`
+    Since { view: View },
+}
+
+impl wire::WireMessage for SyncRequest {
+    const TAG: u16 = wire::tag::SYNC_REQUEST;
+    const MAX_SIZE: u64 = 1 << 10;
+}
+
+/// Answer to a [`SyncRequest`], sent directly to the requester
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub struct SyncResponseMsg {
+    /// Encoded blocks, from the oldest to the newest
+    pub blocks: Vec<Box<[u8]>>,
+}
+
+impl wire::WireMessage for SyncResponseMsg {
+    const TAG: u16 = wire::tag::SYNC_RESPONSE;
+    // direct responses are limited to 16MiB, envelope included
+    const MAX_SIZE: u64 = (16 << 20) - wire::HEADER_SIZE as u64;
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum NetworkMessage {
     Timeout(TimeoutMsg),
@@ -91,6 +230,7 @@ pub enum NetworkMessage {
     Vote(VoteMsg),
     NewView(NewViewMsg),
     Proposal(ProposalMsg),
+    Evidence(Evidence),
 }
 
 impl NetworkMessage {
@@ -101,3 +241,93 @@ impl NetworkMessage {
         wire::deserialize(data).unwrap()
     }
 }
+
+fn sign(
+    private_key: &PrivateKey,
+    payload: impl FnOnce(&NodeId) -> Vec<u8>,
+) -> (NodeId, Box<[u8]>, Box<[u8]>) {
+    let public_key = private_key.public_key();
+    let signer = NodeId::from_public_key(&public_key);
+    let signature = private_key.sign(payload(&signer));
+    (
+        signer,
+        public_key.as_bytes().into_boxed_slice(),
+        signature.as_bytes().into_boxed_slice(),
+    )
+}
+
+fn verify(
+    signer: &NodeId,
+    public_key: &[u8],
+    signature: &[u8],
+    payload: &[u8],
+) -> Option<Signature> {
+    let public_key = PublicKey::from_bytes(public_key).ok()?;
+    if NodeId::from_public_key(&public_key) != *signer {
+        return None;
+    }
+    let signature = Signature::from_bytes(signature).ok()?;
+    public_key.verify(signature, payload).then_some(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consensus_engine::StandardQc;
+
+    #[test]
+    fn signed_vote() {
+        let private_key = PrivateKey::new([1; 32]);
+        let vote = Vote {
+            view: View::new(1),
+            block: BlockId::new([1; 32]),
+        };
+        let msg = VoteMsg::new(vote.clone(), None, &private_key);
+        assert_eq!(
+            msg.voter,
+            NodeId::from_public_key(&private_key.public_key())
+        );
+        assert!(msg.verify().is_some());
+
+        // tampering with the vote invalidates the signature
+        let mut forged = msg.clone();
+        forged.vote.view = View::new(2);
+        assert!(forged.verify().is_none());
+
+        // impersonating another node is detected
+        let mut forged = msg;
+        forged.voter = NodeId::new([0; 32]);
+        assert!(forged.verify().is_none());
+    }
+
+    #[test]
+    fn signed_proposal() {
+        let private_key = PrivateKey::new([1; 32]);
+        let (proposal, view) = (BlockId::new([1; 32]), View::new(1));
+        let msg = ProposalMsg::new(Box::new([]), proposal, view, &private_key);
+        assert!(msg.verify().is_some());
+
+        // the signature covers the proposed block
+        let mut forged = msg;
+        forged.proposal = BlockId::new([2; 32]);
+        assert!(forged.verify().is_none());
+    }
+
+    #[test]
+    fn signed_timeout() {
+        let private_key = PrivateKey::new([1; 32]);
+        let sender = NodeId::from_public_key(&private_key.public_key());
+        let timeout = Timeout {
+            view: View::new(1),
+            sender,
+            high_qc: StandardQc::genesis(),
+            timeout_qc: None,
+        };
+        let msg = TimeoutMsg::new(timeout.clone(), &private_key);
+        assert!(msg.verify().is_some());
+
+        let other = PrivateKey::new([2; 32]);
+        let forged = TimeoutMsg::new(timeout, &other);
+        assert!(forged.verify().is_none());
+    }
+}
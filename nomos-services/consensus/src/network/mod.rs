@@ -2,13 +2,20 @@ pub mod adapters;
 pub mod messages;
 
 // std
+use std::sync::Arc;
 // crates
 use futures::Stream;
+use tokio::sync::oneshot;
 // internal
+use crate::equivocation::EquivocationMonitor;
 use crate::network::messages::{
-    NetworkMessage, NewViewMsg, ProposalMsg, TimeoutMsg, TimeoutQcMsg, VoteMsg,
+    NetworkMessage, NewViewMsg, ProposalMsg, SyncRequest, SyncResponseMsg, TimeoutMsg,
+    TimeoutQcMsg, VoteMsg,
+};
+use crate::PublicKeys;
+use consensus_engine::{
+    BlockId, Committee, Evidence, QcSigners, SignatureError, StandardQc, TimeoutQc, View,
 };
-use consensus_engine::{BlockId, Committee, View};
 use nomos_network::backends::NetworkBackend;
 use nomos_network::NetworkService;
 use overwatch_rs::services::relay::OutboundRelay;
@@ -16,6 +23,55 @@ use overwatch_rs::services::ServiceData;
 
 type BoxedStream<T> = Box<dyn Stream<Item = T> + Send + Sync + Unpin>;
 
+/// A block request from a peer that is catching up, answered to that peer only
+#[derive(Debug)]
+pub struct IncomingSyncRequest {
+    pub request: SyncRequest,
+    pub reply: oneshot::Sender<SyncResponseMsg>,
+}
+
+/// Blocks sent by a peer in answer to one of our sync requests
+#[derive(Debug)]
+pub struct IncomingSyncResponse {
+    pub response: SyncResponseMsg,
+    penalty: oneshot::Sender<()>,
+}
+
+impl IncomingSyncResponse {
+    /// Returns the response along with the receiver of its penalty, if any
+    pub fn new(response: SyncResponseMsg) -> (Self, oneshot::Receiver<()>) {
+        let (penalty, receiver) = oneshot::channel();
+        (Self { response, penalty }, receiver)
+    }
+
+    /// Report the response as invalid, penalizing the peer that sent it
+    pub fn penalize(self) {
+        // an error only means the adapter does not track the sender anymore
+        let _ = self.penalty.send(());
+    }
+}
+
+/// Signers a timeout qc of the current view must come from, along with the ones of the
+/// views its high qc may have been built in
+#[derive(Clone, Debug)]
+pub struct TimeoutQcVerifier {
+    pub view: View,
+    pub signers: QcSigners,
+    pub public_keys: Arc<PublicKeys>,
+}
+
+impl TimeoutQcVerifier {
+    pub fn verify(&self, qc: &TimeoutQc) -> Result<(), SignatureError> {
+        qc.verify(&self.signers, |id| self.public_keys.get(id).copied())
+    }
+
+    /// Check a high qc carried by a timeout or new view of the current view, which the
+    /// sender's signature does not cover
+    pub fn verify_high_qc(&self, qc: &StandardQc) -> Result<(), SignatureError> {
+        qc.verify(&self.signers, |id| self.public_keys.get(id).copied())
+    }
+}
+
 #[async_trait::async_trait]
 pub trait NetworkAdapter {
     type Backend: NetworkBackend + 'static;
@@ -29,6 +85,12 @@ pub trait NetworkAdapter {
     async fn broadcast(&self, message: NetworkMessage);
     async fn timeout_stream(&self, committee: &Committee, view: View) -> BoxedStream<TimeoutMsg>;
     async fn timeout_qc_stream(&self, view: View) -> BoxedStream<TimeoutQcMsg>;
+    /// Check gossiped timeout qcs against `verifier` from now on, updated on every view change.
+    /// Adapters relaying gossiped messages must not relay the timeout qcs it rejects.
+    async fn set_timeout_qc_verifier(&self, verifier: TimeoutQcVerifier);
+    /// Report every signed vote received from now on to `monitor`, whatever block or committee
+    /// it is for, so that votes for conflicting blocks are caught even if they are never tallied.
+    async fn set_equivocation_monitor(&self, monitor: EquivocationMonitor);
     async fn votes_stream(
         &self,
         committee: &Committee,
@@ -37,4 +99,12 @@ pub trait NetworkAdapter {
     ) -> BoxedStream<VoteMsg>;
     async fn new_view_stream(&self, committee: &Committee, view: View) -> BoxedStream<NewViewMsg>;
     async fn send(&self, message: NetworkMessage, committee: &Committee);
+    /// Block requests coming from peers that are catching up
+    async fn sync_request_stream(&self) -> BoxedStream<IncomingSyncRequest>;
+    /// Ask peers for the blocks described by `request`, one peer at a time: the next peer is
+    /// only asked once the response of the previous one has been consumed.
+    async fn sync(&self, request: SyncRequest) -> BoxedStream<IncomingSyncResponse>;
+    /// Evidence of equivocating nodes gossiped by peers.
+    /// Evidence is not guaranteed to be verified, although adapters must not relay invalid evidence.
+    async fn evidence_stream(&self) -> BoxedStream<Evidence>;
 }
@@ -0,0 +1,249 @@
+// std
+use std::collections::HashMap;
+use std::hash::Hash;
+// crates
+// internal
+use crate::network::messages::{SyncRequest, SyncResponseMsg};
+use consensus_engine::{BlockId, Qc, StandardQc, View};
+use nomos_core::block::Block;
+use nomos_core::wire::WireMessage;
+
+/// Maximum number of blocks served in a single sync response.
+/// Nodes that fall further behind will ask for the missing ancestors in subsequent requests.
+pub const MAX_SYNC_BLOCKS: usize = 100;
+
+/// Ids of the blocks answering `request`, from the oldest to the newest.
+///
+/// Only blocks in `safe_blocks` are considered and the genesis block is never included,
+/// as every node already has it.
+pub fn requested_blocks(
+    safe_blocks: &HashMap<BlockId, consensus_engine::Block>,
+    high_qc: &StandardQc,
+    request: SyncRequest,
+) -> Vec<BlockId> {
+    let (tip, after, depth) = match request {
+        SyncRequest::Ancestors { block, depth } => {
+            (block, View::new(0), depth.min(MAX_SYNC_BLOCKS))
+        }
+        SyncRequest::Since { view } => (high_qc.id, view.max(View::new(0)), MAX_SYNC_BLOCKS),
+    };
+    let mut ids = Vec::new();
+    let mut current = safe_blocks.get(&tip);
+    while let Some(block) = current {
+        if block.view <= after || ids.len() == depth {
+            break;
+        }
+        ids.push(block.id);
+        current = safe_blocks.get(&block.parent());
+    }
+    ids.reverse();
+    ids
+}
+
+/// Answer `request` with as many of `blocks`, from the oldest to the newest, as fit in a
+/// single [`SyncResponseMsg`].
+///
+/// Answers to [`SyncRequest::Ancestors`] must end at the requested block, so the oldest blocks
+/// are left out, while answers to [`SyncRequest::Since`] must extend the chain of the requester,
+/// so the newest are. The requester asks for the rest in a subsequent request.
+pub fn fit_response(request: SyncRequest, mut blocks: Vec<Box<[u8]>>) -> SyncResponseMsg {
+    // the list and each block are prefixed with their length as an u64
+    let mut size = 8;
+    let mut fits = |len: u64| {
+        size += 8 + len;
+        size <= SyncResponseMsg::MAX_SIZE
+    };
+    let lens = blocks.iter().map(|block| block.len() as u64);
+    match request {
+        SyncRequest::Ancestors { .. } => {
+            let kept = lens.rev().take_while(|len| fits(*len)).count();
+            blocks.drain(..blocks.len() - kept);
+        }
+        SyncRequest::Since { .. } => {
+            let kept = lens.take_while(|len| fits(*len)).count();
+            blocks.truncate(kept);
+        }
+    }
+    SyncResponseMsg { blocks }
+}
+
+/// Check that each block extends the previous one
+pub fn is_chain<Tx: Clone + Eq + Hash, Blob: Clone + Eq + Hash>(
+    blocks: &[Block<Tx, Blob>],
+) -> bool {
+    blocks.windows(2).all(|pair| {
+        let (parent, child) = (pair[0].header(), pair[1].header());
+        child.parent() == parent.id && child.view > parent.view
+    })
+}
+
+/// Check that the aggregated parent qcs of the blocks have a high qc older than their view,
+/// as aggregated qcs are only built after a timeout following their high qc
+pub fn has_well_formed_qcs<Tx: Clone + Eq + Hash, Blob: Clone + Eq + Hash>(
+    blocks: &[Block<Tx, Blob>],
+) -> bool {
+    blocks.iter().all(|block| match &block.header().parent_qc {
+        Qc::Aggregated(qc) => qc.high_qc.view < qc.view,
+        Qc::Standard(_) => true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consensus_engine::{overlay::RandomBeaconState, LeaderProof, NodeId};
+
+    fn child(parent: &consensus_engine::Block, id: BlockId) -> consensus_engine::Block {
+        consensus_engine::Block {
+            id,
+            view: parent.view.next(),
+            parent_qc: Qc::Standard(StandardQc {
+                view: parent.view,
+                id: parent.id,
+                signature: None,
+            }),
+            state_root: [0; 32],
+            leader_proof: LeaderProof::LeaderId {
+                leader_id: NodeId::new([0; 32]),
+            },
+        }
+    }
+
+    fn chain(len: usize) -> Vec<consensus_engine::Block> {
+        let mut blocks = vec![consensus_engine::Block {
+            id: BlockId::zeros(),
+            view: View::new(0),
+            parent_qc: Qc::Standard(StandardQc::genesis()),
+            state_root: [0; 32],
+            leader_proof: LeaderProof::LeaderId {
+                leader_id: NodeId::new([0; 32]),
+            },
+        }];
+        for i in 1..len {
+            blocks.push(child(blocks.last().unwrap(), BlockId::new([i as u8; 32])));
+        }
+        blocks
+    }
+
+    fn high_qc(block: &consensus_engine::Block) -> StandardQc {
+        StandardQc {
+            view: block.view,
+            id: block.id,
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn ancestors() {
+        let blocks = chain(5);
+        let safe_blocks = blocks.iter().map(|b| (b.id, b.clone())).collect();
+        let request = SyncRequest::Ancestors {
+            block: blocks[3].id,
+            depth: 2,
+        };
+        assert_eq!(
+            requested_blocks(&safe_blocks, &high_qc(&blocks[4]), request),
+            vec![blocks[2].id, blocks[3].id]
+        );
+
+        // genesis is never served
+        let request = SyncRequest::Ancestors {
+            block: blocks[3].id,
+            depth: 10,
+        };
+        assert_eq!(
+            requested_blocks(&safe_blocks, &high_qc(&blocks[4]), request),
+            vec![blocks[1].id, blocks[2].id, blocks[3].id]
+        );
+
+        // unknown blocks can't be served
+        let request = SyncRequest::Ancestors {
+            block: BlockId::new([42; 32]),
+            depth: 10,
+        };
+        assert!(requested_blocks(&safe_blocks, &high_qc(&blocks[4]), request).is_empty());
+    }
+
+    #[test]
+    fn since() {
+        let blocks = chain(5);
+        let safe_blocks = blocks.iter().map(|b| (b.id, b.clone())).collect();
+        let request = SyncRequest::Since { view: View::new(2) };
+        assert_eq!(
+            requested_blocks(&safe_blocks, &high_qc(&blocks[4]), request),
+            vec![blocks[3].id, blocks[4].id]
+        );
+        let request = SyncRequest::Since { view: View::new(4) };
+        assert!(requested_blocks(&safe_blocks, &high_qc(&blocks[4]), request).is_empty());
+    }
+
+    #[test]
+    fn response_size() {
+        let blocks = (0..4)
+            .map(|i| vec![i; 6 << 20].into_boxed_slice())
+            .collect::<Vec<_>>();
+        let request = SyncRequest::Ancestors {
+            block: BlockId::zeros(),
+            depth: 4,
+        };
+        let response = fit_response(request, blocks.clone());
+        assert_eq!(response.blocks, blocks[2..]);
+        assert!(nomos_core::wire::encode(&response).is_ok());
+
+        let request = SyncRequest::Since { view: View::new(0) };
+        let response = fit_response(request, blocks.clone());
+        assert_eq!(response.blocks, blocks[..2]);
+        assert!(nomos_core::wire::encode(&response).is_ok());
+    }
+
+    #[test]
+    fn chain_of_blocks() {
+        let beacon = RandomBeaconState::initial_sad_from_entropy([0; 32]);
+        let parent = Block::<(), ()>::new(
+            View::new(1),
+            Qc::Standard(StandardQc::genesis()),
+            std::iter::empty(),
+            std::iter::empty(),
+            NodeId::new([0; 32]),
+            beacon.clone(),
+            [0; 32],
+        );
+        let child = Block::<(), ()>::new(
+            View::new(2),
+            Qc::Standard(high_qc(parent.header())),
+            std::iter::empty(),
+            std::iter::empty(),
+            NodeId::new([0; 32]),
+            beacon,
+            [0; 32],
+        );
+        assert!(is_chain(&[parent.clone(), child.clone()]));
+        assert!(!is_chain(&[child, parent]));
+    }
+
+    #[test]
+    fn malformed_aggregated_qc() {
+        let block = |high_qc_view| {
+            Block::<(), ()>::new(
+                View::new(4),
+                Qc::Aggregated(consensus_engine::AggregateQc {
+                    high_qc: StandardQc {
+                        view: View::new(high_qc_view),
+                        id: BlockId::zeros(),
+                        signature: None,
+                    },
+                    view: View::new(3),
+                    signature: None,
+                }),
+                std::iter::empty(),
+                std::iter::empty(),
+                NodeId::new([0; 32]),
+                RandomBeaconState::initial_sad_from_entropy([0; 32]),
+                [0; 32],
+            )
+        };
+        assert!(has_well_formed_qcs(&[block(2)]));
+        assert!(!has_well_formed_qcs(&[block(3)]));
+        assert!(!has_well_formed_qcs(&[block(4)]));
+    }
+}
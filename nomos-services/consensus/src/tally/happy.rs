@@ -1,14 +1,14 @@
 #![allow(dead_code)]
 // TODO: Well, remove this when we actually use the fields from the specification
 // std
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 // crates
 use futures::{Stream, StreamExt};
 
 // internal
 use super::CarnotTallySettings;
 use crate::network::messages::VoteMsg;
-use consensus_engine::{Block, Qc, StandardQc, Vote};
+use consensus_engine::{AggregateSignature, Block, Qc, Quorum, StandardQc, Vote};
 use nomos_core::crypto::PublicKey;
 use nomos_core::vote::Tally;
 
@@ -47,14 +47,15 @@ impl Tally for CarnotTally {
         block: Block,
         mut vote_stream: S,
     ) -> Result<(Self::Qc, Self::Outcome), Self::TallyError> {
-        let mut seen = HashSet::new();
+        let mut seen = HashMap::new();
         let mut outcome = HashSet::new();
         // return early for leaf nodes
-        if self.settings.threshold == 0 {
+        if self.settings.threshold == Quorum::Count(0) {
             return Ok((
                 Qc::Standard(StandardQc {
                     view: block.view,
                     id: block.id,
+                    signature: None,
                 }),
                 outcome,
             ));
@@ -70,13 +71,22 @@ impl Tally for CarnotTally {
                 continue;
             }
 
-            seen.insert(vote.voter);
+            // only count votes actually signed by the voter
+            let Some(signature) = vote.verify() else {
+                continue;
+            };
+
+            seen.insert(vote.voter, signature);
             outcome.insert(vote.vote.clone());
-            if seen.len() >= self.settings.threshold {
+            if self.settings.threshold.is_reached(seen.keys()) {
+                let signature =
+                    AggregateSignature::aggregate(&self.settings.participating_nodes, seen)
+                        .map_err(|e| CarnotTallyError::InvalidVote(e.to_string()))?;
                 return Ok((
                     Qc::Standard(StandardQc {
                         view: vote.vote.view,
                         id: vote.vote.block,
+                        signature: Some(signature),
                     }),
                     outcome,
                 ));
@@ -8,11 +8,11 @@ pub mod unhappy;
 use serde::{Deserialize, Serialize};
 
 // internal
-use consensus_engine::Committee;
+use consensus_engine::{Committee, Quorum};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CarnotTallySettings {
-    pub threshold: usize,
+    pub threshold: Quorum,
     // TODO: this probably should be dynamic and should change with the view (?)
     pub participating_nodes: Committee,
 }
@@ -1,11 +1,14 @@
 // std
-use std::{collections::HashSet, convert::Infallible};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+};
 // crates
 use futures::{Stream, StreamExt};
 // internal
 use super::CarnotTallySettings;
 use crate::network::messages::TimeoutMsg;
-use consensus_engine::{Timeout, View};
+use consensus_engine::{AggregateSignature, Timeout, View};
 use nomos_core::vote::Tally;
 
 #[derive(Clone, Debug)]
@@ -16,7 +19,7 @@ pub struct TimeoutTally {
 #[async_trait::async_trait]
 impl Tally for TimeoutTally {
     type Vote = TimeoutMsg;
-    type Qc = ();
+    type Qc = AggregateSignature;
     type Subject = View;
     type Outcome = HashSet<Timeout>;
     type TallyError = Infallible;
@@ -31,7 +34,7 @@ impl Tally for TimeoutTally {
         view: View,
         mut vote_stream: S,
     ) -> Result<(Self::Qc, Self::Outcome), Self::TallyError> {
-        let mut seen = HashSet::new();
+        let mut seen = HashMap::new();
         let mut outcome = HashSet::new();
         while let Some(vote) = vote_stream.next().await {
             // check timeout view is valid
@@ -44,10 +47,18 @@ impl Tally for TimeoutTally {
                 continue;
             }
 
-            seen.insert(vote.voter);
+            // only count timeouts actually signed by the voter
+            let Some(signature) = vote.verify() else {
+                continue;
+            };
+
+            seen.insert(vote.voter, signature);
             outcome.insert(vote.vote.clone());
-            if seen.len() >= self.settings.threshold {
-                return Ok(((), outcome));
+            if self.settings.threshold.is_reached(seen.keys()) {
+                let signature =
+                    AggregateSignature::aggregate(&self.settings.participating_nodes, seen)
+                        .expect("at least one signature from committee members");
+                return Ok((signature, outcome));
             }
         }
         unreachable!()
@@ -1,18 +1,20 @@
 // std
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 // crates
 use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 // internal
 use super::CarnotTallySettings;
 use crate::network::messages::NewViewMsg;
-use consensus_engine::{NewView, TimeoutQc};
+use consensus_engine::{AggregateQc, AggregateSignature, NewView, Quorum, TimeoutQc};
 use nomos_core::vote::Tally;
 
 #[derive(thiserror::Error, Debug)]
 pub enum NewViewTallyError {
     #[error("Did not receive enough votes")]
     InsufficientVotes,
+    #[error("Received invalid vote: {0}")]
+    InvalidVote(String),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -23,7 +25,7 @@ pub struct NewViewTally {
 #[async_trait::async_trait]
 impl Tally for NewViewTally {
     type Vote = NewViewMsg;
-    type Qc = ();
+    type Qc = AggregateQc;
     type Subject = TimeoutQc;
     type Outcome = HashSet<NewView>;
     type TallyError = NewViewTallyError;
@@ -38,16 +40,24 @@ impl Tally for NewViewTally {
         timeout_qc: TimeoutQc,
         mut vote_stream: S,
     ) -> Result<(Self::Qc, Self::Outcome), Self::TallyError> {
-        let mut seen = HashSet::new();
+        let mut seen = HashMap::new();
         let mut outcome = HashSet::new();
+        let view = timeout_qc.view().next();
         // return early for leaf nodes
-        if self.settings.threshold == 0 {
-            return Ok(((), outcome));
+        if self.settings.threshold == Quorum::Count(0) {
+            return Ok((
+                AggregateQc {
+                    high_qc: timeout_qc.high_qc().clone(),
+                    view,
+                    signature: None,
+                },
+                outcome,
+            ));
         }
 
         while let Some(vote) = vote_stream.next().await {
             // check vote view is valid
-            if vote.vote.view != timeout_qc.view().next() {
+            if vote.vote.view != view {
                 continue;
             }
 
@@ -55,10 +65,32 @@ impl Tally for NewViewTally {
             if !self.settings.participating_nodes.contains(&vote.voter) {
                 continue;
             }
-            seen.insert(vote.voter);
+            // only count new views actually signed by the voter
+            let Some(signature) = vote.verify() else {
+                continue;
+            };
+
+            seen.insert(vote.voter, signature);
             outcome.insert(vote.vote.clone());
-            if seen.len() >= self.settings.threshold {
-                return Ok(((), outcome));
+            if self.settings.threshold.is_reached(seen.keys()) {
+                let signature =
+                    AggregateSignature::aggregate(&self.settings.participating_nodes, seen)
+                        .map_err(|e| NewViewTallyError::InvalidVote(e.to_string()))?;
+                let high_qc = outcome
+                    .iter()
+                    .map(|nv: &NewView| &nv.high_qc)
+                    .chain(std::iter::once(timeout_qc.high_qc()))
+                    .max_by_key(|qc| qc.view)
+                    .expect("at least the timeout qc high qc is present")
+                    .clone();
+                return Ok((
+                    AggregateQc {
+                        high_qc,
+                        view,
+                        signature: Some(signature),
+                    },
+                    outcome,
+                ));
             }
         }
         Err(NewViewTallyError::InsufficientVotes)
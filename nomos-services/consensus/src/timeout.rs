@@ -0,0 +1,197 @@
+// std
+use std::time::Duration;
+// crates
+use serde::{Deserialize, Serialize};
+// internal
+
+/// Bounds and growth rate of adaptive view timeouts.
+///
+/// The timeout is multiplied by `backoff` every time a view ends with a timeout qc, so that a
+/// faulty leader does not stall the network for long while slow networks eventually get enough
+/// time to make progress, and divided by it every time a block is committed.
+///
+/// Settings are checked when created or deserialized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "AdaptiveTimeoutConfig", into = "AdaptiveTimeoutConfig")]
+pub struct AdaptiveTimeoutSettings {
+    min_timeout: Duration,
+    max_timeout: Duration,
+    backoff: u32,
+}
+
+/// Adaptive timeout settings as written in the node config
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct AdaptiveTimeoutConfig {
+    min_timeout: Duration,
+    max_timeout: Duration,
+    /// Growth factor of the timeout, at least 1
+    backoff: u32,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum AdaptiveTimeoutError {
+    #[error("Minimum timeout {min:?} is greater than the maximum one {max:?}")]
+    InvalidBounds { min: Duration, max: Duration },
+    #[error("Timeouts should not shrink on backoff, but the backoff factor is 0")]
+    InvalidBackoff,
+}
+
+impl AdaptiveTimeoutSettings {
+    pub fn new(
+        min_timeout: Duration,
+        max_timeout: Duration,
+        backoff: u32,
+    ) -> Result<Self, AdaptiveTimeoutError> {
+        if min_timeout > max_timeout {
+            return Err(AdaptiveTimeoutError::InvalidBounds {
+                min: min_timeout,
+                max: max_timeout,
+            });
+        }
+        if backoff < 1 {
+            return Err(AdaptiveTimeoutError::InvalidBackoff);
+        }
+        Ok(Self {
+            min_timeout,
+            max_timeout,
+            backoff,
+        })
+    }
+
+    pub fn min_timeout(&self) -> Duration {
+        self.min_timeout
+    }
+
+    pub fn max_timeout(&self) -> Duration {
+        self.max_timeout
+    }
+
+    pub fn backoff(&self) -> u32 {
+        self.backoff
+    }
+}
+
+impl TryFrom<AdaptiveTimeoutConfig> for AdaptiveTimeoutSettings {
+    type Error = AdaptiveTimeoutError;
+
+    fn try_from(config: AdaptiveTimeoutConfig) -> Result<Self, Self::Error> {
+        Self::new(config.min_timeout, config.max_timeout, config.backoff)
+    }
+}
+
+impl From<AdaptiveTimeoutSettings> for AdaptiveTimeoutConfig {
+    fn from(settings: AdaptiveTimeoutSettings) -> Self {
+        Self {
+            min_timeout: settings.min_timeout,
+            max_timeout: settings.max_timeout,
+            backoff: settings.backoff,
+        }
+    }
+}
+
+/// Timeout of the current view, either fixed or adapted to the outcome of previous views
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ViewTimeout {
+    timeout: Duration,
+    adaptive: Option<AdaptiveTimeoutSettings>,
+}
+
+impl ViewTimeout {
+    pub fn new(timeout: Duration, adaptive: Option<AdaptiveTimeoutSettings>) -> Self {
+        let mut this = Self { timeout, adaptive };
+        this.set(timeout);
+        this
+    }
+
+    pub fn get(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Back off after a view ended with a timeout qc
+    pub fn on_timeout_qc(&mut self) {
+        if let Some(adaptive) = self.adaptive {
+            self.set(self.timeout.saturating_mul(adaptive.backoff));
+        }
+    }
+
+    /// Decay after a block was committed
+    pub fn on_commit(&mut self) {
+        if let Some(adaptive) = self.adaptive {
+            self.set(self.timeout / adaptive.backoff);
+        }
+    }
+
+    fn set(&mut self, timeout: Duration) {
+        self.timeout = match self.adaptive {
+            Some(adaptive) => timeout.min(adaptive.max_timeout).max(adaptive.min_timeout),
+            None => timeout,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_and_decay_within_bounds() {
+        let mut timeout = ViewTimeout::new(
+            Duration::from_secs(1),
+            Some(
+                AdaptiveTimeoutSettings::new(Duration::from_millis(500), Duration::from_secs(5), 2)
+                    .unwrap(),
+            ),
+        );
+        timeout.on_timeout_qc();
+        assert_eq!(timeout.get(), Duration::from_secs(2));
+        timeout.on_timeout_qc();
+        timeout.on_timeout_qc();
+        assert_eq!(timeout.get(), Duration::from_secs(5));
+
+        timeout.on_commit();
+        assert_eq!(timeout.get(), Duration::from_millis(2500));
+        for _ in 0..10 {
+            timeout.on_commit();
+        }
+        assert_eq!(timeout.get(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn reject_invalid_settings() {
+        assert_eq!(
+            AdaptiveTimeoutSettings::new(Duration::from_secs(5), Duration::from_secs(1), 2),
+            Err(AdaptiveTimeoutError::InvalidBounds {
+                min: Duration::from_secs(5),
+                max: Duration::from_secs(1),
+            })
+        );
+        assert_eq!(
+            AdaptiveTimeoutSettings::new(Duration::from_secs(1), Duration::from_secs(5), 0),
+            Err(AdaptiveTimeoutError::InvalidBackoff)
+        );
+        // a fixed timeout is a valid adaptive one
+        assert!(
+            AdaptiveTimeoutSettings::new(Duration::from_secs(1), Duration::from_secs(1), 1).is_ok()
+        );
+
+        let config = |min_secs: u64, max_secs: u64, backoff: u32| {
+            serde_json::json!({
+                "min_timeout": Duration::from_secs(min_secs),
+                "max_timeout": Duration::from_secs(max_secs),
+                "backoff": backoff,
+            })
+        };
+        let settings: AdaptiveTimeoutSettings = serde_json::from_value(config(1, 5, 2)).unwrap();
+        assert_eq!(serde_json::to_value(settings).unwrap(), config(1, 5, 2));
+        assert!(serde_json::from_value::<AdaptiveTimeoutSettings>(config(5, 1, 2)).is_err());
+        assert!(serde_json::from_value::<AdaptiveTimeoutSettings>(config(1, 5, 0)).is_err());
+    }
+
+    #[test]
+    fn fixed_timeout() {
+        let mut timeout = ViewTimeout::new(Duration::from_secs(30), None);
+        timeout.on_timeout_qc();
+        timeout.on_commit();
+        assert_eq!(timeout.get(), Duration::from_secs(30));
+    }
+}
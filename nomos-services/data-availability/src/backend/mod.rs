@@ -6,6 +6,7 @@ use overwatch_rs::DynError;
 #[derive(Debug)]
 pub enum DaError {
     Dyn(DynError),
+    InvalidBlob,
 }
 
 #[async_trait::async_trait]
@@ -92,7 +92,7 @@ where
         blob: Protocol::Blob,
     ) -> Result<(), DaError> {
         // we need to handle the reply (verification + signature)
-        let attestation = da.attest(&blob);
+        let attestation = da.attest(&blob).ok_or(DaError::InvalidBlob)?;
         backend.add_blob(blob).await?;
         // we do not call `da.recv_blob` here because that is meant to
         // be called to retrieve the original data, while here we're only interested
@@ -7,14 +7,15 @@ use std::marker::PhantomData;
 // internal
 use crate::network::NetworkAdapter;
 use nomos_core::wire;
-use nomos_network::backends::libp2p::{Command, Event, EventKind, Libp2p, Message, TopicHash};
+use nomos_network::backends::libp2p::{
+    message_id, Command, Event, EventKind, Libp2p, Message, MessageAcceptance, TopicHash,
+};
 use nomos_network::{NetworkMsg, NetworkService};
 use overwatch_rs::services::relay::OutboundRelay;
 use overwatch_rs::services::ServiceData;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use tokio_stream::wrappers::BroadcastStream;
-use tokio_stream::StreamExt;
 use tracing::debug;
 
 pub const NOMOS_DA_TOPIC: &str = "NomosDa";
@@ -30,7 +31,14 @@ where
     B: Serialize + DeserializeOwned + Send + Sync + 'static,
     A: Serialize + DeserializeOwned + Send + Sync + 'static,
 {
-    async fn stream_for<E: DeserializeOwned>(&self) -> Box<dyn Stream<Item = E> + Unpin + Send> {
+    /// Stream of the messages of type `E` from the da topic.
+    ///
+    /// Blobs and attestations share the same topic, so only one of the streams should
+    /// `report` message validations to the network.
+    async fn stream_for<E: DeserializeOwned>(
+        &self,
+        report: bool,
+    ) -> Box<dyn Stream<Item = E> + Unpin + Send> {
         let topic_hash = TopicHash::from_raw(NOMOS_DA_TOPIC);
         let (sender, receiver) = tokio::sync::oneshot::channel();
         self.network_relay
@@ -41,22 +49,56 @@ where
             .await
             .expect("Network backend should be ready");
         let receiver = receiver.await.unwrap();
-        Box::new(Box::pin(BroadcastStream::new(receiver).filter_map(
-            move |msg| match msg {
-                Ok(Event::Message(Message { topic, data, .. })) if topic == topic_hash => {
-                    match wire::deserialize::<E>(&data) {
-                        Ok(msg) => Some(msg),
-                        Err(e) => {
-                            debug!("Unrecognized message: {e}");
-                            None
+        let network_relay = self.network_relay.clone();
+        Box::new(Box::pin(futures::StreamExt::filter_map(
+            BroadcastStream::new(receiver),
+            move |msg| {
+                let network_relay = network_relay.clone();
+                let topic_hash = topic_hash.clone();
+                async move {
+                    match msg {
+                        Ok(Event::Message(message)) if message.topic == topic_hash => {
+                            if report {
+                                Self::report(&network_relay, &message).await;
+                            }
+                            match wire::deserialize::<E>(&message.data) {
+                                Ok(msg) => Some(msg),
+                                Err(e) => {
+                                    debug!("Unrecognized message: {e}");
+                                    None
+                                }
+                            }
                         }
+                        _ => None,
                     }
                 }
-                _ => None,
             },
         )))
     }
 
+    /// Only relay messages that are either blobs or attestations
+    async fn report(
+        network_relay: &OutboundRelay<<NetworkService<Libp2p> as ServiceData>::Message>,
+        message: &Message,
+    ) {
+        let acceptance = if wire::deserialize::<B>(&message.data).is_ok()
+            || wire::deserialize::<A>(&message.data).is_ok()
+        {
+            MessageAcceptance::Accept
+        } else {
+            MessageAcceptance::Reject
+        };
+        if let Err((e, _)) = network_relay
+            .send(NetworkMsg::Process(Command::ReportValidation {
+                message_id: message_id(message),
+                acceptance,
+            }))
+            .await
+        {
+            tracing::error!("failed to report message validation: {e}");
+        }
+    }
+
     async fn send<E: Serialize>(&self, data: E) -> Result<(), DynError> {
         let message = wire::serialize(&data)?.into_boxed_slice();
         self.network_relay
@@ -96,11 +138,11 @@ where
     }
 
     async fn blob_stream(&self) -> Box<dyn Stream<Item = Self::Blob> + Unpin + Send> {
-        self.stream_for::<Self::Blob>().await
+        self.stream_for::<Self::Blob>(true).await
     }
 
     async fn attestation_stream(&self) -> Box<dyn Stream<Item = Self::Attestation> + Unpin + Send> {
-        self.stream_for::<Self::Attestation>().await
+        self.stream_for::<Self::Attestation>(false).await
     }
 
     async fn send_attestation(&self, attestation: Self::Attestation) -> Result<(), DynError> {
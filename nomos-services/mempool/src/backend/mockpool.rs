@@ -74,7 +74,7 @@ where
         Box::new(pending_items.into_iter())
     }
 
-    fn mark_in_block(&mut self, keys: &[Self::Key], block: BlockId) {
+    fn mark_in_block(&mut self, keys: &[Self::Key], block: BlockId, _parent: BlockId) {
         let mut items_in_block = Vec::with_capacity(keys.len());
         for key in keys {
             if let Some(item) = self.pending_items.remove(key) {
@@ -1,5 +1,6 @@
 #[cfg(feature = "mock")]
 pub mod mockpool;
+pub mod priority;
 
 use nomos_core::block::BlockId;
 use serde::{Deserialize, Serialize};
@@ -8,6 +9,10 @@ use serde::{Deserialize, Serialize};
 pub enum MempoolError {
     #[error("Item already in mempool")]
     ExistingItem,
+    #[error("Mempool is full of items with a higher priority")]
+    PoolFull,
+    #[error("Sender reached its limit of pending items")]
+    SenderLimit,
     #[error(transparent)]
     DynamicPoolError(#[from] overwatch_rs::DynError),
 }
@@ -30,8 +35,8 @@ pub trait MemPool {
     /// items that were not included up to that point if available.
     fn view(&self, ancestor_hint: BlockId) -> Box<dyn Iterator<Item = Self::Item> + Send>;
 
-    /// Record that a set of items were included in a block
-    fn mark_in_block(&mut self, items: &[Self::Key], block: BlockId);
+    /// Record that a set of items were included in a block extending `parent`
+    fn mark_in_block(&mut self, items: &[Self::Key], block: BlockId, parent: BlockId);
 
     /// Returns all of the transactions for the block
     #[cfg(test)]
@@ -0,0 +1,525 @@
+// std
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+// crates
+use serde::{Deserialize, Serialize};
+// internal
+use crate::backend::{MemPool, MempoolError, Status};
+use nomos_core::account::AccountId;
+use nomos_core::block::BlockId;
+use nomos_core::tx::{carnot::Tx, Transaction};
+
+/// Information the [`PriorityPool`] needs to order and bound its items
+pub trait PoolItem {
+    type Sender: Clone + Eq + Hash;
+    /// Items paying higher fees are served first and evicted last
+    fn fee(&self) -> u64;
+    fn sender(&self) -> Self::Sender;
+    /// Size in bytes accounted against the pool limit
+    fn size(&self) -> usize;
+}
+
+/// Transactions do not pay fees yet, so they are served in the order they arrived
+impl PoolItem for Tx {
+    type Sender = AccountId;
+
+    fn fee(&self) -> u64 {
+        0
+    }
+
+    fn sender(&self) -> Self::Sender {
+        let Tx::Transfer(tx) = self;
+        *tx.from()
+    }
+
+    fn size(&self) -> usize {
+        self.as_bytes().len()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PriorityPoolSettings {
+    /// Maximum number of pending items
+    pub max_items: usize,
+    /// Maximum total size in bytes of the pending items
+    pub max_bytes: usize,
+    /// Maximum number of pending items from the same sender
+    pub max_items_per_sender: usize,
+    /// Pending items are dropped once they have been waiting for longer than this
+    pub ttl: Duration,
+}
+
+struct Entry<Item> {
+    item: Item,
+    fee: u64,
+    size: usize,
+    // insertion order, breaks ties between items paying the same fee
+    seq: u64,
+    timestamp: u64,
+}
+
+/// Items ordered by ascending priority: lowest fee first, newest first among equal fees
+type PriorityKey = (u64, Reverse<u64>);
+
+/// A mempool serving pending items by fee, bounded in number of items and bytes.
+///
+/// When full, the lowest paying items are evicted to make room for better paying ones.
+/// Items included in blocks are kept until pruned, so that a view built on a different branch
+/// can still serve them.
+pub struct PriorityPool<Item: PoolItem, Key> {
+    settings: PriorityPoolSettings,
+    pending: HashMap<Key, Entry<Item>>,
+    by_priority: BTreeMap<PriorityKey, Key>,
+    // pending items by insertion order, used for expiration
+    by_age: BTreeMap<u64, Key>,
+    by_sender: HashMap<Item::Sender, usize>,
+    pending_bytes: usize,
+    in_block: HashMap<BlockId, InBlock<Item, Key>>,
+    in_block_by_key: HashMap<Key, BlockId>,
+    // blocks extending each block, used to find what is still needed when pruning
+    children: HashMap<BlockId, Vec<BlockId>>,
+    next_seq: u64,
+    last_item_timestamp: u64,
+}
+
+struct InBlock<Item, Key> {
+    parent: BlockId,
+    items: Vec<(Key, Entry<Item>)>,
+}
+
+impl<Item, Key> PriorityPool<Item, Key>
+where
+    Item: PoolItem,
+    Key: Clone + Eq + Hash,
+{
+    pub fn new(settings: PriorityPoolSettings) -> Self {
+        Self {
+            settings,
+            pending: HashMap::new(),
+            by_priority: BTreeMap::new(),
+            by_age: BTreeMap::new(),
+            by_sender: HashMap::new(),
+            pending_bytes: 0,
+            in_block: HashMap::new(),
+            in_block_by_key: HashMap::new(),
+            children: HashMap::new(),
+            next_seq: 0,
+            last_item_timestamp: 0,
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    fn is_expired(&self, entry: &Entry<Item>, now: u64) -> bool {
+        now.saturating_sub(entry.timestamp) > self.settings.ttl.as_millis() as u64
+    }
+
+    fn insert_pending(&mut self, key: Key, entry: Entry<Item>) {
+        self.by_priority
+            .insert((entry.fee, Reverse(entry.seq)), key.clone());
+        self.by_age.insert(entry.seq, key.clone());
+        *self.by_sender.entry(entry.item.sender()).or_default() += 1;
+        self.pending_bytes += entry.size;
+        self.pending.insert(key, entry);
+    }
+
+    fn remove_pending(&mut self, key: &Key) -> Option<Entry<Item>> {
+        let entry = self.pending.remove(key)?;
+        self.by_priority.remove(&(entry.fee, Reverse(entry.seq)));
+        self.by_age.remove(&entry.seq);
+        let sender = entry.item.sender();
+        if let Some(count) = self.by_sender.get_mut(&sender) {
+            *count -= 1;
+            if *count == 0 {
+                self.by_sender.remove(&sender);
+            }
+        }
+        self.pending_bytes -= entry.size;
+        Some(entry)
+    }
+
+    fn expire(&mut self, now: u64) {
+        while let Some(key) = self.by_age.values().next().cloned() {
+            if !self.is_expired(&self.pending[&key], now) {
+                break;
+            }
+            self.remove_pending(&key);
+        }
+    }
+
+    /// Pending items to evict, lowest priority first, so that an item with `fee` and `size`
+    /// fits in the pool. Fails if that would mean evicting items paying as much or more.
+    fn evictions(&self, fee: u64, size: usize) -> Result<Vec<Key>, MempoolError> {
+        let mut items = self.pending.len() + 1;
+        let mut bytes = self.pending_bytes + size;
+        let mut evicted = Vec::new();
+        let mut candidates = self.by_priority.iter();
+        while items > self.settings.max_items || bytes > self.settings.max_bytes {
+            match candidates.next() {
+                Some(((candidate_fee, _), key)) if *candidate_fee < fee => {
+                    items -= 1;
+                    bytes -= self.pending[key].size;
+                    evicted.push(key.clone());
+                }
+                _ => return Err(MempoolError::PoolFull),
+            }
+        }
+        Ok(evicted)
+    }
+
+    /// Blocks in the chain ending at `block`, as far as the pool knows it
+    fn ancestors(&self, mut block: BlockId) -> HashSet<BlockId> {
+        let mut ancestors = HashSet::new();
+        while let Some(InBlock { parent, .. }) = self.in_block.get(&block) {
+            if !ancestors.insert(block) {
+                break;
+            }
+            block = *parent;
+        }
+        ancestors
+    }
+
+    /// Blocks in `roots` along with every block extending them, as far as the pool knows them
+    fn descendants(&self, roots: impl IntoIterator<Item = BlockId>) -> HashSet<BlockId> {
+        let mut descendants = HashSet::new();
+        let mut to_visit: Vec<BlockId> = roots.into_iter().collect();
+        while let Some(block) = to_visit.pop() {
+            if descendants.insert(block) {
+                to_visit.extend(self.children.get(&block).into_iter().flatten());
+            }
+        }
+        descendants
+    }
+
+    fn remove_block(&mut self, block: BlockId) {
+        let Some(InBlock { parent, .. }) = self.in_block.remove(&block) else {
+            return;
+        };
+        self.children.remove(&block);
+        if let Some(siblings) = self.children.get_mut(&parent) {
+            siblings.retain(|sibling| *sibling != block);
+            if siblings.is_empty() {
+                self.children.remove(&parent);
+            }
+        }
+    }
+}
+
+impl<Item, Key> MemPool for PriorityPool<Item, Key>
+where
+    Item: PoolItem + Clone + Send + Sync + 'static,
+    Key: Clone + Eq + Hash,
+{
+    type Settings = PriorityPoolSettings;
+    type Item = Item;
+    type Key = Key;
+
+    fn new(settings: Self::Settings) -> Self {
+        Self::new(settings)
+    }
+
+    fn add_item(&mut self, key: Self::Key, item: Self::Item) -> Result<(), MempoolError> {
+        let now = Self::now();
+        self.expire(now);
+        if self.pending.contains_key(&key) || self.in_block_by_key.contains_key(&key) {
+            return Err(MempoolError::ExistingItem);
+        }
+        let sender_items = self.by_sender.get(&item.sender()).copied().unwrap_or(0);
+        if sender_items >= self.settings.max_items_per_sender {
+            return Err(MempoolError::SenderLimit);
+        }
+        let (fee, size) = (item.fee(), item.size());
+        for evicted in self.evictions(fee, size)? {
+            self.remove_pending(&evicted);
+        }
+        let entry = Entry {
+            item,
+            fee,
+            size,
+            seq: self.next_seq,
+            timestamp: now,
+        };
+        self.next_seq += 1;
+        self.insert_pending(key, entry);
+        self.last_item_timestamp = now;
+        Ok(())
+    }
+
+    fn view(&self, ancestor_hint: BlockId) -> Box<dyn Iterator<Item = Self::Item> + Send> {
+        let now = Self::now();
+        let ancestors = self.ancestors(ancestor_hint);
+        // items included in blocks outside of the chain ending at `ancestor_hint` are still
+        // available to blocks extending it
+        let mut entries: Vec<&Entry<Item>> = self
+            .in_block
+            .iter()
+            .filter(|(block, _)| !ancestors.contains(block))
+            .flat_map(|(_, in_block)| in_block.items.iter().map(|(_, entry)| entry))
+            .chain(
+                self.pending
+                    .values()
+                    .filter(|entry| !self.is_expired(entry, now)),
+            )
+            .collect();
+        entries.sort_by_key(|entry| Reverse((entry.fee, Reverse(entry.seq))));
+        let items: Vec<Item> = entries
+            .into_iter()
+            .map(|entry| entry.item.clone())
+            .collect();
+        Box::new(items.into_iter())
+    }
+
+    fn mark_in_block(&mut self, keys: &[Self::Key], block: BlockId, parent: BlockId) {
+        let mut items = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(entry) = self.remove_pending(key) {
+                self.in_block_by_key.insert(key.clone(), block);
+                items.push((key.clone(), entry));
+            }
+        }
+        if !self.in_block.contains_key(&block) {
+            self.children.entry(parent).or_default().push(block);
+        }
+        let in_block = self.in_block.entry(block).or_insert_with(|| InBlock {
+            parent,
+            items: Vec::new(),
+        });
+        in_block.items.append(&mut items);
+    }
+
+    #[cfg(test)]
+    fn block_items(&self, block: BlockId) -> Option<Box<dyn Iterator<Item = Self::Item> + Send>> {
+        self.in_block.get(&block).map(|in_block| {
+            let items: Vec<Item> = in_block
+                .items
+                .iter()
+                .map(|(_, entry)| entry.item.clone())
+                .collect();
+            Box::new(items.into_iter()) as Box<dyn Iterator<Item = Self::Item> + Send>
+        })
+    }
+
+    fn prune(&mut self, keys: &[Self::Key]) {
+        for key in keys {
+            self.remove_pending(key);
+            if let Some(block) = self.in_block_by_key.remove(key) {
+                if let Some(in_block) = self.in_block.get_mut(&block) {
+                    in_block.items.retain(|(other, _)| other != key);
+                }
+            }
+        }
+        // blocks are only needed to resolve the ancestors of blocks with items left
+        let needed = self.descendants(
+            self.in_block
+                .iter()
+                .filter(|(_, in_block)| !in_block.items.is_empty())
+                .map(|(block, _)| *block),
+        );
+        let unneeded: Vec<BlockId> = self
+            .in_block
+            .keys()
+            .filter(|block| !needed.contains(block))
+            .copied()
+            .collect();
+        for block in unneeded {
+            self.remove_block(block);
+        }
+    }
+
+    fn pending_item_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn last_item_timestamp(&self) -> u64 {
+        self.last_item_timestamp
+    }
+
+    fn status(&self, items: &[Self::Key]) -> Vec<Status> {
+        items
+            .iter()
+            .map(|key| {
+                if self.pending.contains_key(key) {
+                    Status::Pending
+                } else if let Some(block) = self.in_block_by_key.get(key) {
+                    Status::InBlock { block: *block }
+                } else {
+                    Status::Unknown
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Item {
+        sender: u8,
+        fee: u64,
+        size: usize,
+    }
+
+    impl PoolItem for Item {
+        type Sender = u8;
+
+        fn fee(&self) -> u64 {
+            self.fee
+        }
+
+        fn sender(&self) -> Self::Sender {
+            self.sender
+        }
+
+        fn size(&self) -> usize {
+            self.size
+        }
+    }
+
+    fn item(sender: u8, fee: u64) -> Item {
+        Item {
+            sender,
+            fee,
+            size: 10,
+        }
+    }
+
+    fn pool(max_items: usize, max_items_per_sender: usize) -> PriorityPool<Item, u32> {
+        PriorityPool::new(PriorityPoolSettings {
+            max_items,
+            max_bytes: 1000,
+            max_items_per_sender,
+            ttl: Duration::from_secs(60),
+        })
+    }
+
+    fn fees(pool: &PriorityPool<Item, u32>, ancestor_hint: BlockId) -> Vec<u64> {
+        pool.view(ancestor_hint).map(|item| item.fee).collect()
+    }
+
+    #[test]
+    fn evict_lowest_fee() {
+        let mut pool = pool(2, 10);
+        pool.add_item(0, item(0, 5)).unwrap();
+        pool.add_item(1, item(1, 1)).unwrap();
+        assert!(matches!(
+            pool.add_item(1, item(1, 1)),
+            Err(MempoolError::ExistingItem)
+        ));
+        // the new item pays more than the cheapest one
+        pool.add_item(2, item(2, 3)).unwrap();
+        assert_eq!(fees(&pool, BlockId::zeros()), vec![5, 3]);
+        assert_eq!(pool.status(&[1]), vec![Status::Unknown]);
+        // but not enough to evict any other
+        assert!(matches!(
+            pool.add_item(3, item(3, 3)),
+            Err(MempoolError::PoolFull)
+        ));
+        assert_eq!(pool.pending_item_count(), 2);
+    }
+
+    #[test]
+    fn bounded_bytes_and_senders() {
+        let mut pool = pool(10, 2);
+        pool.add_item(0, item(0, 1)).unwrap();
+        pool.add_item(1, item(0, 1)).unwrap();
+        assert!(matches!(
+            pool.add_item(2, item(0, 1)),
+            Err(MempoolError::SenderLimit)
+        ));
+
+        let large = Item {
+            sender: 1,
+            fee: 2,
+            size: 995,
+        };
+        // both pending items need to go to make room
+        pool.add_item(3, large.clone()).unwrap();
+        assert_eq!(pool.view(BlockId::zeros()).collect::<Vec<_>>(), vec![large]);
+    }
+
+    #[test]
+    fn expire_old_items() {
+        let mut pool = PriorityPool::new(PriorityPoolSettings {
+            max_items: 10,
+            max_bytes: 1000,
+            max_items_per_sender: 10,
+            ttl: Duration::from_millis(10),
+        });
+        pool.add_item(0u32, item(0, 1)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(fees(&pool, BlockId::zeros()).is_empty());
+        pool.add_item(1, item(1, 1)).unwrap();
+        assert_eq!(pool.pending_item_count(), 1);
+        assert_eq!(pool.status(&[0, 1]), vec![Status::Unknown, Status::Pending]);
+    }
+
+    #[test]
+    fn view_excludes_ancestors() {
+        let mut pool = pool(10, 10);
+        for (key, fee) in [(0, 1), (1, 2), (2, 3)] {
+            pool.add_item(key, item(key as u8, fee)).unwrap();
+        }
+        let (genesis, first, second, fork) = (
+            BlockId::zeros(),
+            BlockId::new([1; 32]),
+            BlockId::new([2; 32]),
+            BlockId::new([3; 32]),
+        );
+        pool.mark_in_block(&[0], first, genesis);
+        pool.mark_in_block(&[1], second, first);
+        assert_eq!(pool.status(&[1]), vec![Status::InBlock { block: second }]);
+        assert_eq!(fees(&pool, second), vec![3]);
+        // building on `first` can include what `second` did
+        assert_eq!(fees(&pool, first), vec![3, 2]);
+
+        pool.mark_in_block(&[2], fork, first);
+        assert_eq!(fees(&pool, second), vec![3]);
+        assert_eq!(fees(&pool, fork), vec![2]);
+
+        // pruned items are gone for good, but the chain is kept to resolve ancestors
+        pool.prune(&[0, 2]);
+        assert_eq!(fees(&pool, first), vec![2]);
+        assert_eq!(fees(&pool, second), Vec::<u64>::new());
+        assert!(pool.block_items(fork).is_none());
+        assert_eq!(pool.children, HashMap::new());
+    }
+
+    #[test]
+    fn carnot_transactions_in_arrival_order() {
+        use nomos_core::tx::carnot::TransferTransaction;
+        let tx = |sender: u8, nonce| {
+            Tx::Transfer(TransferTransaction::new(
+                &[sender; 32],
+                AccountId::new([0; 32]),
+                1,
+                nonce,
+            ))
+        };
+        let mut pool = PriorityPool::new(PriorityPoolSettings {
+            max_items: 10,
+            max_bytes: 10_000,
+            max_items_per_sender: 2,
+            ttl: Duration::from_secs(60),
+        });
+        for (key, tx) in [tx(1, 0), tx(2, 0), tx(1, 1)].into_iter().enumerate() {
+            pool.add_item(key, tx).unwrap();
+        }
+        assert!(matches!(
+            pool.add_item(3, tx(1, 2)),
+            Err(MempoolError::SenderLimit)
+        ));
+        assert_eq!(
+            pool.view(BlockId::zeros()).collect::<Vec<_>>(),
+            vec![tx(1, 0), tx(2, 0), tx(1, 1)]
+        );
+    }
+}
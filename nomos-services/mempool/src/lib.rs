@@ -1,9 +1,11 @@
 pub mod backend;
 pub mod network;
+pub mod validator;
 
 // std
 use std::{
     fmt::{Debug, Error, Formatter},
+    hash::Hash,
     marker::PhantomData,
 };
 
@@ -23,8 +25,9 @@ use overwatch_rs::services::{
     ServiceCore, ServiceData, ServiceId,
 };
 use tracing::error;
+use validator::{AcceptAll, Rejections, Validator, MAX_REJECTED_ITEMS};
 
-pub struct MempoolService<N, P, D>
+pub struct MempoolService<N, P, D, V = AcceptAll>
 where
     N: NetworkAdapter<Item = P::Item, Key = P::Key>,
     P: MemPool,
@@ -32,10 +35,12 @@ where
     P::Item: Debug + 'static,
     P::Key: Debug + 'static,
     D: Discriminant,
+    V: Validator<P::Item>,
 {
     service_state: ServiceStateHandle<Self>,
     network_relay: Relay<NetworkService<N::Backend>>,
     pool: P,
+    validator: V,
     // This is an hack because SERVICE_ID has to be univoque and associated const
     // values can't depend on generic parameters.
     // Unfortunately, this means that the mempools for certificates and transactions
@@ -69,6 +74,7 @@ pub enum MempoolMsg<Item, Key> {
     MarkInBlock {
         ids: Vec<Key>,
         block: BlockId,
+        parent: BlockId,
     },
     Metrics {
         reply_channel: Sender<MempoolMetrics>,
@@ -91,10 +97,10 @@ where
             }
             Self::Add { item, .. } => write!(f, "MempoolMsg::Add{{item: {item:?}}}"),
             Self::Prune { ids } => write!(f, "MempoolMsg::Prune{{ids: {ids:?}}}"),
-            Self::MarkInBlock { ids, block } => {
+            Self::MarkInBlock { ids, block, parent } => {
                 write!(
                     f,
-                    "MempoolMsg::MarkInBlock{{ids: {ids:?}, block: {block:?}}}"
+                    "MempoolMsg::MarkInBlock{{ids: {ids:?}, block: {block:?}, parent: {parent:?}}}"
                 )
             }
             #[cfg(test)]
@@ -124,7 +130,7 @@ impl Discriminant for Certificate {
     const ID: &'static str = "mempool-da";
 }
 
-impl<N, P, D> ServiceData for MempoolService<N, P, D>
+impl<N, P, D, V> ServiceData for MempoolService<N, P, D, V>
 where
     N: NetworkAdapter<Item = P::Item, Key = P::Key>,
     P: MemPool,
@@ -132,24 +138,27 @@ where
     P::Item: Debug + 'static,
     P::Key: Debug + 'static,
     D: Discriminant,
+    V: Validator<P::Item>,
 {
     const SERVICE_ID: ServiceId = D::ID;
-    type Settings = Settings<P::Settings, N::Settings>;
+    type Settings = Settings<P::Settings, N::Settings, V::Settings>;
     type State = NoState<Self::Settings>;
     type StateOperator = NoOperator<Self::State>;
     type Message = MempoolMsg<<P as MemPool>::Item, <P as MemPool>::Key>;
 }
 
 #[async_trait::async_trait]
-impl<N, P, D> ServiceCore for MempoolService<N, P, D>
+impl<N, P, D, V> ServiceCore for MempoolService<N, P, D, V>
 where
     P: MemPool + Send + 'static,
     P::Settings: Clone + Send + Sync + 'static,
     N::Settings: Clone + Send + Sync + 'static,
     P::Item: Clone + Debug + Send + Sync + 'static,
-    P::Key: Debug + Send + Sync + 'static,
+    P::Key: Debug + Clone + Eq + Hash + Send + Sync + 'static,
     N: NetworkAdapter<Item = P::Item, Key = P::Key> + Send + Sync + 'static,
     D: Discriminant + Send,
+    V: Validator<P::Item> + Send + Sync + 'static,
+    V::Settings: Send + Sync + 'static,
 {
     fn init(service_state: ServiceStateHandle<Self>) -> Result<Self, overwatch_rs::DynError> {
         let network_relay = service_state.overwatch_handle.relay();
@@ -158,6 +167,7 @@ where
             service_state,
             network_relay,
             pool: P::new(settings.backend),
+            validator: V::new(settings.validator),
             _d: PhantomData,
         })
     }
@@ -167,8 +177,10 @@ where
             mut service_state,
             network_relay,
             mut pool,
+            validator,
             ..
         } = self;
+        let mut rejections = Rejections::new(MAX_REJECTED_ITEMS);
 
         let mut network_relay: OutboundRelay<_> = network_relay
             .connect()
@@ -187,12 +199,16 @@ where
         loop {
             tokio::select! {
                 Some(msg) = service_state.inbound_relay.recv() => {
-                    Self::handle_mempool_message(msg, &mut pool, &mut network_relay, &mut service_state).await;
+                    Self::handle_mempool_message(msg, &mut pool, &validator, &mut rejections, &mut network_relay, &mut service_state).await;
                 }
                 Some((key, item )) = network_items.next() => {
-                    pool.add_item(key, item).unwrap_or_else(|e| {
-                        tracing::debug!("could not add item to the pool due to: {}", e)
-                    });
+                    let valid = Self::validate(&validator, &mut rejections, &key, &item);
+                    adapter.report_validation(&key, valid).await;
+                    if valid {
+                        pool.add_item(key, item).unwrap_or_else(|e| {
+                            tracing::debug!("could not add item to the pool due to: {}", e)
+                        });
+                    }
                 }
                 Some(msg) = lifecycle_stream.next() =>  {
                     if Self::should_stop_service(msg).await {
@@ -205,15 +221,17 @@ where
     }
 }
 
-impl<N, P, D> MempoolService<N, P, D>
+impl<N, P, D, V> MempoolService<N, P, D, V>
 where
     P: MemPool + Send + 'static,
     P::Settings: Clone + Send + Sync + 'static,
     N::Settings: Clone + Send + Sync + 'static,
     P::Item: Clone + Debug + Send + Sync + 'static,
-    P::Key: Debug + Send + Sync + 'static,
+    P::Key: Debug + Clone + Eq + Hash + Send + Sync + 'static,
     N: NetworkAdapter<Item = P::Item, Key = P::Key> + Send + Sync + 'static,
     D: Discriminant + Send,
+    V: Validator<P::Item> + Send + Sync + 'static,
+    V::Settings: Send + Sync + 'static,
 {
     async fn should_stop_service(message: LifecycleMessage) -> bool {
         match message {
@@ -230,9 +248,28 @@ where
         }
     }
 
+    /// Check an item before admitting it, remembering it as rejected if it is not valid
+    fn validate(
+        validator: &V,
+        rejections: &mut Rejections<P::Key>,
+        key: &P::Key,
+        item: &P::Item,
+    ) -> bool {
+        match validator.validate(item) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!("rejected item {key:?}: {e}");
+                rejections.insert(key.clone());
+                false
+            }
+        }
+    }
+
     async fn handle_mempool_message(
         message: MempoolMsg<P::Item, P::Key>,
         pool: &mut P,
+        validator: &V,
+        rejections: &mut Rejections<P::Key>,
         network_relay: &mut OutboundRelay<NetworkMsg<N::Backend>>,
         service_state: &mut ServiceStateHandle<Self>,
     ) {
@@ -242,6 +279,12 @@ where
                 key,
                 reply_channel,
             } => {
+                if !Self::validate(validator, rejections, &key, &item) {
+                    if let Err(e) = reply_channel.send(Err(())) {
+                        tracing::debug!("Failed to send reply to AddTx: {:?}", e);
+                    }
+                    return;
+                }
                 match pool.add_item(key, item.clone()) {
                     Ok(_id) => {
                         // Broadcast the item to the network
@@ -258,6 +301,9 @@ where
                     }
                     Err(e) => {
                         tracing::debug!("could not add tx to the pool due to: {}", e);
+                        if let Err(e) = reply_channel.send(Err(())) {
+                            tracing::debug!("Failed to send reply to AddTx: {:?}", e);
+                        }
                     }
                 }
             }
@@ -269,8 +315,8 @@ where
                     .send(pool.view(ancestor_hint))
                     .unwrap_or_else(|_| tracing::debug!("could not send back pool view"));
             }
-            MempoolMsg::MarkInBlock { ids, block } => {
-                pool.mark_in_block(&ids, block);
+            MempoolMsg::MarkInBlock { ids, block, parent } => {
+                pool.mark_in_block(&ids, block, parent);
             }
             #[cfg(test)]
             MempoolMsg::BlockItems {
@@ -297,8 +343,17 @@ where
                 items,
                 reply_channel,
             } => {
+                let status = pool
+                    .status(&items)
+                    .into_iter()
+                    .zip(&items)
+                    .map(|(status, key)| match status {
+                        Status::Unknown if rejections.contains(key) => Status::Rejected,
+                        status => status,
+                    })
+                    .collect();
                 reply_channel
-                    .send(pool.status(&items))
+                    .send(status)
                     .unwrap_or_else(|_| tracing::debug!("could not send back mempool status"));
             }
         }
@@ -306,7 +361,8 @@ where
 }
 
 #[derive(Clone, Debug)]
-pub struct Settings<B, N> {
+pub struct Settings<B, N, V = ()> {
     pub backend: B,
     pub network: N,
+    pub validator: V,
 }
@@ -1,12 +1,17 @@
+// std
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
 // crates
 use futures::Stream;
 use serde::{de::DeserializeOwned, Serialize};
 use tokio_stream::wrappers::BroadcastStream;
-use tokio_stream::StreamExt;
 // internal
 use crate::network::NetworkAdapter;
 use nomos_core::wire;
-use nomos_network::backends::libp2p::{Command, Event, EventKind, Libp2p, Message, TopicHash};
+use nomos_network::backends::libp2p::{
+    message_id, Command, Event, EventKind, Libp2p, MessageAcceptance, MessageId, TopicHash,
+};
 use nomos_network::{NetworkMsg, NetworkService};
 use overwatch_rs::services::relay::OutboundRelay;
 use overwatch_rs::services::ServiceData;
@@ -14,13 +19,38 @@ use overwatch_rs::services::ServiceData;
 pub struct Libp2pAdapter<Item, Key> {
     network_relay: OutboundRelay<<NetworkService<Libp2p> as ServiceData>::Message>,
     settings: Settings<Key, Item>,
+    // received items waiting for the mempool to validate them, along with every message
+    // carrying them, as different encodings of the same item have different ids
+    pending_validations: Arc<Mutex<HashMap<Key, Vec<MessageId>>>>,
+}
+
+impl<Item, Key> Libp2pAdapter<Item, Key> {
+    async fn report(&self, message_id: MessageId, acceptance: MessageAcceptance) {
+        report(&self.network_relay, message_id, acceptance).await
+    }
+}
+
+async fn report(
+    network_relay: &OutboundRelay<<NetworkService<Libp2p> as ServiceData>::Message>,
+    message_id: MessageId,
+    acceptance: MessageAcceptance,
+) {
+    if let Err((e, _)) = network_relay
+        .send(NetworkMsg::Process(Command::ReportValidation {
+            message_id,
+            acceptance,
+        }))
+        .await
+    {
+        tracing::error!("failed to report message validation: {e}");
+    }
 }
 
 #[async_trait::async_trait]
 impl<Item, Key> NetworkAdapter for Libp2pAdapter<Item, Key>
 where
     Item: DeserializeOwned + Serialize + Send + Sync + 'static + Clone,
-    Key: Clone + Send + Sync + 'static,
+    Key: Clone + Eq + Hash + Send + Sync + 'static,
 {
     type Backend = Libp2p;
     type Settings = Settings<Key, Item>;
@@ -40,6 +70,7 @@ where
         Self {
             network_relay,
             settings,
+            pending_validations: Default::default(),
         }
     }
     async fn transactions_stream(
@@ -56,18 +87,41 @@ where
             .await
             .expect("Network backend should be ready");
         let receiver = receiver.await.unwrap();
-        Box::new(Box::pin(BroadcastStream::new(receiver).filter_map(
-            move |message| match message {
-                Ok(Event::Message(Message { data, topic, .. })) if topic == topic_hash => {
-                    match wire::deserialize::<Item>(&data) {
-                        Ok(item) => Some((id(&item), item)),
-                        Err(e) => {
-                            tracing::debug!("Unrecognized message: {e}");
-                            None
+        let network_relay = self.network_relay.clone();
+        let pending_validations = self.pending_validations.clone();
+        Box::new(Box::pin(futures::StreamExt::filter_map(
+            BroadcastStream::new(receiver),
+            move |message| {
+                let network_relay = network_relay.clone();
+                let pending_validations = pending_validations.clone();
+                let topic_hash = topic_hash.clone();
+                async move {
+                    match message {
+                        Ok(Event::Message(message)) if message.topic == topic_hash => {
+                            match wire::deserialize::<Item>(&message.data) {
+                                Ok(item) => {
+                                    let key = id(&item);
+                                    let mut pending = pending_validations.lock().unwrap();
+                                    let message_ids = pending.entry(key.clone()).or_default();
+                                    message_ids.push(message_id(&message));
+                                    // the item is validated once, along with its first message
+                                    (message_ids.len() == 1).then_some((key, item))
+                                }
+                                Err(e) => {
+                                    tracing::debug!("Unrecognized message: {e}");
+                                    report(
+                                        &network_relay,
+                                        message_id(&message),
+                                        MessageAcceptance::Reject,
+                                    )
+                                    .await;
+                                    None
+                                }
+                            }
                         }
+                        _ => None,
                     }
                 }
-                _ => None,
             },
         )))
     }
@@ -88,6 +142,18 @@ where
             tracing::error!("Failed to serialize item");
         }
     }
+
+    async fn report_validation(&self, key: &Key, valid: bool) {
+        let message_ids = self.pending_validations.lock().unwrap().remove(key);
+        for message_id in message_ids.into_iter().flatten() {
+            let acceptance = if valid {
+                MessageAcceptance::Accept
+            } else {
+                MessageAcceptance::Reject
+            };
+            self.report(message_id, acceptance).await;
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
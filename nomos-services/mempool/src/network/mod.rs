@@ -27,4 +27,8 @@ pub trait NetworkAdapter {
     ) -> Box<dyn Stream<Item = (Self::Key, Self::Item)> + Unpin + Send>;
 
     async fn send(&self, item: Self::Item);
+
+    /// Reports whether an item received from the network passed validation, so that it is
+    /// only relayed to other nodes if it did
+    async fn report_validation(&self, _key: &Self::Key, _valid: bool) {}
 }
@@ -0,0 +1,161 @@
+// std
+use std::collections::{HashSet, VecDeque};
+use std::convert::Infallible;
+use std::hash::Hash;
+// crates
+use serde::{Deserialize, Serialize};
+// internal
+use nomos_core::tx::{carnot::Tx, Transaction};
+
+/// Number of rejected items remembered to answer status requests
+pub const MAX_REJECTED_ITEMS: usize = 1024;
+
+/// Check performed on every item before admitting it into the mempool.
+///
+/// Items failing validation are neither added to the pool nor gossiped to other nodes.
+/// Validators needing access to some external state (e.g. a ledger) should share it through
+/// their settings.
+pub trait Validator<Item> {
+    type Settings: Clone;
+    type Error: std::error::Error;
+
+    fn new(settings: Self::Settings) -> Self;
+
+    fn validate(&self, item: &Item) -> Result<(), Self::Error>;
+}
+
+/// Validator admitting every item
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcceptAll;
+
+impl<Item> Validator<Item> for AcceptAll {
+    type Settings = ();
+    type Error = Infallible;
+
+    fn new(_settings: Self::Settings) -> Self {
+        Self
+    }
+
+    fn validate(&self, _item: &Item) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TxValidatorSettings {
+    /// Largest encoded transaction admitted, in bytes
+    pub max_tx_size: usize,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TxValidationError {
+    #[error("Transaction is not signed by its sender")]
+    InvalidSignature,
+    #[error("Transaction of {size} bytes is larger than the limit of {max_size} bytes")]
+    TooLarge { size: usize, max_size: usize },
+}
+
+/// Validator of carnot transactions, checking their signature and size.
+///
+/// Transactions that can't be executed against the ledger are only left out when proposing.
+#[derive(Clone, Copy, Debug)]
+pub struct TxValidator {
+    settings: TxValidatorSettings,
+}
+
+impl Validator<Tx> for TxValidator {
+    type Settings = TxValidatorSettings;
+    type Error = TxValidationError;
+
+    fn new(settings: Self::Settings) -> Self {
+        Self { settings }
+    }
+
+    fn validate(&self, tx: &Tx) -> Result<(), Self::Error> {
+        let Tx::Transfer(transfer) = tx;
+        if !transfer.verify_signature() {
+            return Err(TxValidationError::InvalidSignature);
+        }
+        let size = tx.as_bytes().len();
+        if size > self.settings.max_tx_size {
+            return Err(TxValidationError::TooLarge {
+                size,
+                max_size: self.settings.max_tx_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Keys of the most recently rejected items, the oldest ones are forgotten first
+pub struct Rejections<Key> {
+    keys: HashSet<Key>,
+    order: VecDeque<Key>,
+    capacity: usize,
+}
+
+impl<Key: Clone + Eq + Hash> Rejections<Key> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            keys: HashSet::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn insert(&mut self, key: Key) {
+        if self.capacity == 0 || !self.keys.insert(key.clone()) {
+            return;
+        }
+        self.order.push_back(key);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn contains(&self, key: &Key) -> bool {
+        self.keys.contains(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nomos_core::account::AccountId;
+    use nomos_core::tx::carnot::TransferTransaction;
+
+    #[test]
+    fn bounded_tx_size() {
+        let tx = Tx::Transfer(TransferTransaction::new(
+            &[1; 32],
+            AccountId::new([2; 32]),
+            10,
+            0,
+        ));
+        let size = tx.as_bytes().len();
+        let validator = TxValidator::new(TxValidatorSettings { max_tx_size: size });
+        assert!(validator.validate(&tx).is_ok());
+        let validator = TxValidator::new(TxValidatorSettings {
+            max_tx_size: size - 1,
+        });
+        assert!(matches!(
+            validator.validate(&tx),
+            Err(TxValidationError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn forget_oldest_rejections() {
+        let mut rejections = Rejections::new(2);
+        rejections.insert(0);
+        rejections.insert(1);
+        rejections.insert(1);
+        assert!(rejections.contains(&0));
+        rejections.insert(2);
+        assert!(!rejections.contains(&0));
+        assert!(rejections.contains(&1));
+        assert!(rejections.contains(&2));
+    }
+}
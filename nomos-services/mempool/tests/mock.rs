@@ -11,8 +11,9 @@ use overwatch_derive::*;
 use overwatch_rs::{overwatch::OverwatchRunner, services::handle::ServiceHandle};
 
 use nomos_mempool::{
-    backend::mockpool::MockPool,
-    network::adapters::mock::{MockAdapter, MOCK_TX_CONTENT_TOPIC},
+    backend::{mockpool::MockPool, Status},
+    network::adapters::mock::{MockAdapter, MOCK_PUB_SUB_TOPIC, MOCK_TX_CONTENT_TOPIC},
+    validator::Validator,
     MempoolMsg, MempoolService, Settings, Transaction,
 };
 
@@ -25,6 +26,43 @@ struct MockPoolNode {
     >,
 }
 
+/// Rejects the transactions with the payload it is set up with
+struct RejectPayload(String);
+
+#[derive(Debug, thiserror::Error)]
+#[error("rejected payload")]
+struct RejectedPayload;
+
+impl Validator<MockTransaction<MockMessage>> for RejectPayload {
+    type Settings = String;
+    type Error = RejectedPayload;
+
+    fn new(settings: Self::Settings) -> Self {
+        Self(settings)
+    }
+
+    fn validate(&self, item: &MockTransaction<MockMessage>) -> Result<(), Self::Error> {
+        if item.message().payload == self.0 {
+            return Err(RejectedPayload);
+        }
+        Ok(())
+    }
+}
+
+type ValidatingMempool = MempoolService<
+    MockAdapter,
+    MockPool<MockTransaction<MockMessage>, MockTxId>,
+    Transaction,
+    RejectPayload,
+>;
+
+#[derive(Services)]
+struct ValidatingPoolNode {
+    logging: ServiceHandle<Logger>,
+    network: ServiceHandle<NetworkService<Mock>>,
+    mockpool: ServiceHandle<ValidatingMempool>,
+}
+
 #[test]
 fn test_mockmempool() {
     let exist = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
@@ -64,6 +102,7 @@ fn test_mockmempool() {
             mockpool: Settings {
                 backend: (),
                 network: (),
+                validator: (),
             },
             logging: LoggerSettings::default(),
         },
@@ -121,3 +160,98 @@ fn test_mockmempool() {
         std::thread::sleep(std::time::Duration::from_millis(200));
     }
 }
+
+#[test]
+fn test_mockmempool_rejects_invalid_items() {
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let done2 = done.clone();
+
+    let app = OverwatchRunner::<ValidatingPoolNode>::run(
+        ValidatingPoolNodeServiceSettings {
+            network: NetworkConfig {
+                backend: MockConfig {
+                    predefined_messages: vec![],
+                    duration: tokio::time::Duration::from_millis(100),
+                    seed: 0,
+                    version: 1,
+                    weights: None,
+                },
+            },
+            mockpool: Settings {
+                backend: (),
+                network: (),
+                validator: "invalid".to_string(),
+            },
+            logging: LoggerSettings::default(),
+        },
+        None,
+    )
+    .map_err(|e| eprintln!("Error encountered: {}", e))
+    .unwrap();
+
+    let network = app.handle().relay::<NetworkService<Mock>>();
+    let mempool = app.handle().relay::<ValidatingMempool>();
+
+    app.spawn(async move {
+        let network_outbound = network.connect().await.unwrap();
+        let mempool_outbound = mempool.connect().await.unwrap();
+
+        let tx = |payload: &str| {
+            MockTransaction::new(MockMessage {
+                payload: payload.to_string(),
+                content_topic: MOCK_TX_CONTENT_TOPIC,
+                version: 0,
+                timestamp: 0,
+            })
+        };
+        let (valid, invalid) = (tx("valid"), tx("invalid"));
+        for (item, accepted) in [(valid.clone(), true), (invalid.clone(), false)] {
+            let (reply_channel, reply) = tokio::sync::oneshot::channel();
+            mempool_outbound
+                .send(MempoolMsg::Add {
+                    key: item.id(),
+                    item,
+                    reply_channel,
+                })
+                .await
+                .unwrap();
+            assert_eq!(reply.await.unwrap().is_ok(), accepted);
+        }
+
+        let (reply_channel, reply) = tokio::sync::oneshot::channel();
+        mempool_outbound
+            .send(MempoolMsg::Status {
+                items: vec![valid.id(), invalid.id()],
+                reply_channel,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            reply.await.unwrap(),
+            vec![Status::Pending, Status::Rejected]
+        );
+
+        // only the valid item is relayed to the network
+        loop {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            network_outbound
+                .send(NetworkMsg::Process(MockBackendMessage::Query {
+                    topic: MOCK_PUB_SUB_TOPIC.to_string(),
+                    tx,
+                }))
+                .await
+                .unwrap();
+            let relayed = rx.await.unwrap();
+            if !relayed.is_empty() {
+                assert_eq!(relayed, vec![valid.message().clone()]);
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        done.store(true, std::sync::atomic::Ordering::SeqCst);
+    });
+
+    while !done2.load(std::sync::atomic::Ordering::SeqCst) {
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+}
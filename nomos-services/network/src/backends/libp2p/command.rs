@@ -1,4 +1,9 @@
-use nomos_libp2p::Multiaddr;
+use std::time::Duration;
+
+use nomos_libp2p::{
+    request_response::{OutboundFailure, RequestId},
+    MessageAcceptance, MessageId, Multiaddr, PeerId,
+};
 use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
@@ -15,6 +20,28 @@ pub enum Command {
     Info {
         reply: oneshot::Sender<Libp2pInfo>,
     },
+    /// Sends a request directly to a single peer instead of broadcasting it.
+    /// `protocol` tells the receiving node which service should handle the request.
+    Request {
+        peer_id: PeerId,
+        protocol: String,
+        data: Box<[u8]>,
+        reply: oneshot::Sender<Result<Box<[u8]>, OutboundFailure>>,
+    },
+    /// Answers a request received through [`Event::Request`](super::Event::Request)
+    Respond {
+        request_id: RequestId,
+        data: Box<[u8]>,
+    },
+    /// Outcome of the validation of a received message, which is only propagated once accepted
+    ReportValidation {
+        message_id: MessageId,
+        acceptance: MessageAcceptance,
+    },
+    /// Lowers the score of a peer that answered a request with invalid data
+    Penalize {
+        peer_id: PeerId,
+    },
     #[doc(hidden)]
     // broadcast a message directly through gossipsub without mixnet
     DirectBroadcastAndRetry {
@@ -38,4 +65,15 @@ pub struct Libp2pInfo {
     pub n_peers: usize,
     pub n_connections: u32,
     pub n_pending_connections: u32,
+    /// Peers known through discovery
+    pub peers: Vec<PeerInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub peer_id: PeerId,
+    pub addresses: Vec<Multiaddr>,
+    pub connected: bool,
+    /// Last round-trip time measured by a ping, if connected
+    pub rtt: Option<Duration>,
 }
@@ -1,13 +1,14 @@
 use std::{ops::Range, time::Duration};
 
-use mixnet_client::MixnetClient;
+use futures::future::BoxFuture;
+use mixnet_client::{MessageStream, MixnetClient};
 use nomos_core::wire;
 use rand::{rngs::OsRng, thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
 
-use super::{command::Topic, Command, Libp2pConfig};
+use super::{command::Topic, Command};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MixnetMessage {
@@ -32,22 +33,21 @@ pub fn random_delay(range: &Range<Duration>) -> Duration {
 }
 
 pub struct MixnetHandler {
-    client: MixnetClient<OsRng>,
+    // receives through the same client the swarm sends with, to get its acknowledgements
+    receiver: BoxFuture<'static, mixnet_client::Result<MessageStream>>,
     commands_tx: mpsc::Sender<Command>,
 }
 
 impl MixnetHandler {
-    pub fn new(config: &Libp2pConfig, commands_tx: mpsc::Sender<Command>) -> Self {
-        let client = MixnetClient::new(config.mixnet_client.clone(), OsRng);
-
+    pub fn new(client: &MixnetClient<OsRng>, commands_tx: mpsc::Sender<Command>) -> Self {
         Self {
-            client,
+            receiver: client.run(),
             commands_tx,
         }
     }
 
     pub async fn run(&mut self) {
-        let Ok(mut stream) = self.client.run().await else {
+        let Ok(mut stream) = (&mut self.receiver).await else {
             tracing::error!("Could not quickstart mixnet stream");
             return;
         };
@@ -4,32 +4,57 @@ mod mixnet;
 mod swarm;
 
 // std
-pub use self::command::{Command, Libp2pInfo};
+pub use self::command::{Command, Libp2pInfo, PeerInfo};
 pub use self::config::Libp2pConfig;
-use self::mixnet::MixnetHandler;
+use self::mixnet::{random_delay, MixnetHandler};
 use self::swarm::SwarmHandler;
 
 // internal
 use super::NetworkBackend;
 pub use nomos_libp2p::libp2p::gossipsub::{Message, TopicHash};
+pub use nomos_libp2p::{
+    request_response::{OutboundFailure, RequestId},
+    MessageAcceptance, MessageId, PeerId,
+};
 // crates
 use overwatch_rs::{overwatch::handle::OverwatchHandle, services::state::NoState};
+use rand::rngs::OsRng;
 use tokio::sync::{broadcast, mpsc};
 
 pub struct Libp2p {
     events_tx: broadcast::Sender<Event>,
+    requests_tx: broadcast::Sender<Event>,
     commands_tx: mpsc::Sender<Command>,
 }
 
 #[derive(Debug)]
 pub enum EventKind {
     Message,
+    Request,
 }
 
 /// Events emitted from [`NomosLibp2p`], which users can subscribe
+///
+/// Received messages are held until their validation is reported with
+/// [`Command::ReportValidation`].
 #[derive(Debug, Clone)]
 pub enum Event {
     Message(Message),
+    /// A request sent directly to this node, to be answered with [`Command::Respond`]
+    Request(IncomingRequest),
+}
+
+#[derive(Debug, Clone)]
+pub struct IncomingRequest {
+    pub id: RequestId,
+    pub peer_id: PeerId,
+    pub protocol: String,
+    pub data: Box<[u8]>,
+}
+
+/// Id to report the validation of a received message with
+pub fn message_id(message: &Message) -> MessageId {
+    nomos_libp2p::compute_message_id(message)
 }
 
 const BUFFER_SIZE: usize = 64;
@@ -45,20 +70,44 @@ impl NetworkBackend for Libp2p {
     fn new(config: Self::Settings, overwatch_handle: OverwatchHandle) -> Self {
         let (commands_tx, commands_rx) = tokio::sync::mpsc::channel(BUFFER_SIZE);
         let (events_tx, _) = tokio::sync::broadcast::channel(BUFFER_SIZE);
+        let (requests_tx, _) = tokio::sync::broadcast::channel(BUFFER_SIZE);
+
+        let mut swarm_handler = SwarmHandler::new(
+            &config,
+            commands_tx.clone(),
+            commands_rx,
+            events_tx.clone(),
+            requests_tx.clone(),
+        );
 
-        let mut mixnet_handler = MixnetHandler::new(&config, commands_tx.clone());
+        let mut mixnet_handler =
+            MixnetHandler::new(&swarm_handler.mixnet_client, commands_tx.clone());
         overwatch_handle.runtime().spawn(async move {
             mixnet_handler.run().await;
         });
-
-        let mut swarm_handler =
-            SwarmHandler::new(&config, commands_tx.clone(), commands_rx, events_tx.clone());
+        if let Some(retransmitter) = swarm_handler.mixnet_client.retransmitter(OsRng) {
+            overwatch_handle.runtime().spawn(retransmitter.run());
+        }
+        if let Some(provider) = config.mixnet_client.topology_provider.provider() {
+            let topology = swarm_handler.mixnet_client.topology();
+            let interval = config.mixnet_client.topology_refresh_interval;
+            overwatch_handle.runtime().spawn(async move {
+                topology.follow(provider, interval).await;
+            });
+        }
+        if let Some(cover_traffic) = swarm_handler.mixnet_client.cover_traffic(OsRng) {
+            let mixnet_delay = config.mixnet_delay.clone();
+            overwatch_handle.runtime().spawn(async move {
+                cover_traffic.run(|| random_delay(&mixnet_delay)).await;
+            });
+        }
         overwatch_handle.runtime().spawn(async move {
             swarm_handler.run(config.initial_peers).await;
         });
 
         Self {
             events_tx,
+            requests_tx,
             commands_tx,
         }
     }
@@ -78,6 +127,10 @@ impl NetworkBackend for Libp2p {
                 tracing::debug!("processed subscription to incoming messages");
                 self.events_tx.subscribe()
             }
+            EventKind::Request => {
+                tracing::debug!("processed subscription to incoming requests");
+                self.requests_tx.subscribe()
+            }
         }
     }
 }
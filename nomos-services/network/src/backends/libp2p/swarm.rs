@@ -1,24 +1,30 @@
-use std::{collections::HashMap, ops::Range, time::Duration};
+use std::{
+    collections::HashMap,
+    ops::Range,
+    time::{Duration, Instant},
+};
 
 use mixnet_client::MixnetClient;
 #[allow(deprecated)]
 use nomos_libp2p::{
     gossipsub::{self, Message},
+    identify, kad,
     libp2p::swarm::ConnectionId,
-    Behaviour, BehaviourEvent, Multiaddr, Swarm, SwarmEvent, THandlerErr,
+    ping, request_response, Behaviour, BehaviourEvent, MessageAcceptance, MessageId, Multiaddr,
+    PeerId, Swarm, SwarmEvent, THandlerErr,
 };
 use rand::rngs::OsRng;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_stream::StreamExt;
 
 use crate::backends::libp2p::{
     mixnet::{random_delay, MixnetMessage},
-    Libp2pInfo,
+    IncomingRequest, Libp2pInfo, PeerInfo,
 };
 
 use super::{
     command::{Command, Dial, Topic},
-    Event, Libp2pConfig,
+    Event, Libp2pConfig, OutboundFailure, RequestId,
 };
 
 pub struct SwarmHandler {
@@ -27,8 +33,20 @@ pub struct SwarmHandler {
     pub commands_tx: mpsc::Sender<Command>,
     pub commands_rx: mpsc::Receiver<Command>,
     pub events_tx: broadcast::Sender<Event>,
+    pub requests_tx: broadcast::Sender<Event>,
+    // Requests sent to other peers, waiting for a response
+    pub pending_requests: HashMap<RequestId, oneshot::Sender<Result<Box<[u8]>, OutboundFailure>>>,
+    // Requests received from other peers, waiting for a service to answer them
+    pub response_channels: HashMap<RequestId, request_response::ResponseChannel<Vec<u8>>>,
     pub mixnet_client: MixnetClient<OsRng>,
     pub mixnet_delay: Range<Duration>,
+    pub bootstrap_interval: Duration,
+    // Interval between decays of the penalties given to peers
+    pub score_decay_interval: Duration,
+    // Last round-trip time measured with each connected peer
+    pub rtts: HashMap<PeerId, Duration>,
+    // Received messages waiting for their validation, along with the peer that relayed them
+    pub pending_validations: HashMap<MessageId, (PeerId, Instant)>,
 }
 
 macro_rules! log_error {
@@ -43,6 +61,8 @@ macro_rules! log_error {
 const BACKOFF: u64 = 5;
 // TODO: make this configurable
 const MAX_RETRY: usize = 3;
+// Gossipsub drops unvalidated messages from its cache well before this
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(60);
 
 impl SwarmHandler {
     pub fn new(
@@ -50,6 +70,7 @@ impl SwarmHandler {
         commands_tx: mpsc::Sender<Command>,
         commands_rx: mpsc::Receiver<Command>,
         events_tx: broadcast::Sender<Event>,
+        requests_tx: broadcast::Sender<Event>,
     ) -> Self {
         let swarm = Swarm::build(&config.inner).unwrap();
         let mixnet_client = MixnetClient::new(config.mixnet_client.clone(), OsRng);
@@ -63,8 +84,15 @@ impl SwarmHandler {
             commands_tx,
             commands_rx,
             events_tx,
+            requests_tx,
+            pending_requests: HashMap::new(),
+            response_channels: HashMap::new(),
             mixnet_client,
             mixnet_delay: config.mixnet_delay.clone(),
+            bootstrap_interval: config.inner.bootstrap_interval,
+            score_decay_interval: config.inner.peer_score.decay_interval,
+            rtts: HashMap::new(),
+            pending_validations: HashMap::new(),
         }
     }
 
@@ -77,8 +105,24 @@ impl SwarmHandler {
             Self::schedule_connect(dial, self.commands_tx.clone()).await;
         }
 
+        let mut bootstrap = tokio::time::interval(self.bootstrap_interval);
+        let mut expire_validations = tokio::time::interval(VALIDATION_TIMEOUT);
+        let mut decay_scores = tokio::time::interval(self.score_decay_interval);
         loop {
             tokio::select! {
+                _ = decay_scores.tick() => {
+                    self.swarm.decay_application_scores();
+                }
+                _ = expire_validations.tick() => {
+                    self.pending_validations
+                        .retain(|_, (_, received)| received.elapsed() < VALIDATION_TIMEOUT);
+                }
+                _ = bootstrap.tick() => {
+                    // only fails if no peer is known yet, nothing to do until we connect to one
+                    if let Err(e) = self.swarm.bootstrap() {
+                        tracing::debug!("skipping bootstrap: {e}");
+                    }
+                }
                 Some(event) = self.swarm.next() => {
                     self.handle_event(event);
                 }
@@ -98,7 +142,70 @@ impl SwarmHandler {
                 message,
             })) => {
                 tracing::debug!("Got message with id: {id} from peer: {peer_id}");
-                log_error!(self.events_tx.send(Event::Message(message)));
+                if self.events_tx.send(Event::Message(message)).is_ok() {
+                    self.pending_validations
+                        .insert(id, (peer_id, Instant::now()));
+                } else {
+                    // nobody is listening, so nobody can tell whether the message is valid
+                    log_error!(self.swarm.report_validation(
+                        &id,
+                        &peer_id,
+                        MessageAcceptance::Ignore
+                    ));
+                }
+            }
+            SwarmEvent::Behaviour(BehaviourEvent::RequestResponse(event)) => {
+                self.handle_request_response_event(event);
+            }
+            SwarmEvent::Behaviour(BehaviourEvent::Identify(identify::Event::Received {
+                peer_id,
+                info,
+            })) => {
+                tracing::debug!(
+                    "identified peer: {peer_id} listening on {:?}",
+                    info.listen_addrs
+                );
+                for addr in info.listen_addrs {
+                    self.swarm.add_peer_address(peer_id, addr);
+                }
+            }
+            SwarmEvent::Behaviour(BehaviourEvent::Kademlia(kad::Event::RoutingUpdated {
+                peer,
+                is_new_peer,
+                addresses,
+                ..
+            })) => {
+                tracing::debug!("routing table updated with peer: {peer}");
+                // connect to newly discovered peers so that gossipsub can include them in its mesh
+                if is_new_peer && !self.swarm.is_connected(&peer) {
+                    self.connect(Dial {
+                        addr: addresses.first().clone(),
+                        retry_count: 0,
+                    });
+                }
+            }
+            SwarmEvent::Behaviour(BehaviourEvent::Kademlia(
+                kad::Event::OutboundQueryProgressed {
+                    result: kad::QueryResult::Bootstrap(result),
+                    ..
+                },
+            )) => match result {
+                Ok(kad::BootstrapOk { num_remaining, .. }) => {
+                    tracing::debug!("bootstrap progressed, {num_remaining} buckets remaining");
+                }
+                Err(e) => tracing::error!("bootstrap failed: {e:?}"),
+            },
+            SwarmEvent::Behaviour(BehaviourEvent::Ping(ping::Event { peer, result, .. })) => {
+                match result {
+                    Ok(rtt) => {
+                        self.rtts.insert(peer, rtt);
+                    }
+                    Err(e) => {
+                        tracing::debug!("peer {peer} failed to answer ping: {e}");
+                        self.rtts.remove(&peer);
+                        self.swarm.disconnect(peer);
+                    }
+                }
             }
             SwarmEvent::ConnectionEstablished {
                 peer_id,
@@ -109,6 +216,9 @@ impl SwarmHandler {
                 tracing::debug!("connected to peer:{peer_id}, connection_id:{connection_id:?}");
                 if endpoint.is_dialer() {
                     self.complete_connect(connection_id);
+                    // peers we dialed, e.g. initial ones, are the entry points of discovery
+                    self.swarm
+                        .add_peer_address(peer_id, endpoint.get_remote_address().clone());
                 }
             }
             SwarmEvent::ConnectionClosed {
@@ -120,6 +230,9 @@ impl SwarmHandler {
                 tracing::debug!(
                     "connection closed from peer: {peer_id} {connection_id:?} due to {cause:?}"
                 );
+                if !self.swarm.is_connected(&peer_id) {
+                    self.rtts.remove(&peer_id);
+                }
             }
             SwarmEvent::OutgoingConnectionError {
                 peer_id,
@@ -136,6 +249,61 @@ impl SwarmHandler {
         }
     }
 
+    fn handle_request_response_event(
+        &mut self,
+        event: request_response::Event<nomos_libp2p::Request, Vec<u8>>,
+    ) {
+        match event {
+            request_response::Event::Message { peer, message } => match message {
+                request_response::Message::Request {
+                    request_id,
+                    request,
+                    channel,
+                } => {
+                    tracing::debug!("received {} request from peer: {peer}", request.protocol);
+                    let request = IncomingRequest {
+                        id: request_id,
+                        peer_id: peer,
+                        protocol: request.protocol,
+                        data: request.data.into(),
+                    };
+                    // if nobody is listening, dropping the channel makes the request fail
+                    if self.requests_tx.send(Event::Request(request)).is_ok() {
+                        self.response_channels.insert(request_id, channel);
+                    }
+                }
+                request_response::Message::Response {
+                    request_id,
+                    response,
+                } => {
+                    if let Some(reply) = self.pending_requests.remove(&request_id) {
+                        // an error only means that the requester is not waiting anymore
+                        let _ = reply.send(Ok(response.into()));
+                    }
+                }
+            },
+            request_response::Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+            } => {
+                tracing::debug!("request to peer {peer} failed: {error}");
+                if let Some(reply) = self.pending_requests.remove(&request_id) {
+                    let _ = reply.send(Err(error));
+                }
+            }
+            request_response::Event::InboundFailure {
+                peer,
+                request_id,
+                error,
+            } => {
+                tracing::debug!("failed to answer request from peer {peer}: {error}");
+                self.response_channels.remove(&request_id);
+            }
+            request_response::Event::ResponseSent { .. } => {}
+        }
+    }
+
     async fn handle_command(&mut self, command: Command) {
         match command {
             Command::Connect(dial) => {
@@ -156,6 +324,17 @@ impl SwarmHandler {
                 log_error!(self.swarm.unsubscribe(&topic));
             }
             Command::Info { reply } => {
+                let peers = self
+                    .swarm
+                    .routing_table()
+                    .into_iter()
+                    .map(|(peer_id, addresses)| PeerInfo {
+                        peer_id,
+                        addresses,
+                        connected: self.swarm.is_connected(&peer_id),
+                        rtt: self.rtts.get(&peer_id).copied(),
+                    })
+                    .collect();
                 let swarm = self.swarm.swarm();
                 let network_info = swarm.network_info();
                 let counters = network_info.connection_counters();
@@ -164,9 +343,50 @@ impl SwarmHandler {
                     n_peers: network_info.num_peers(),
                     n_connections: counters.num_connections(),
                     n_pending_connections: counters.num_pending(),
+                    peers,
                 };
                 log_error!(reply.send(info));
             }
+            Command::Request {
+                peer_id,
+                protocol,
+                data,
+                reply,
+            } => {
+                tracing::debug!("sending {protocol} request to peer: {peer_id}");
+                let request_id = self.swarm.send_request(
+                    &peer_id,
+                    nomos_libp2p::Request {
+                        protocol,
+                        data: data.into(),
+                    },
+                );
+                self.pending_requests.insert(request_id, reply);
+            }
+            Command::Respond { request_id, data } => {
+                match self.response_channels.remove(&request_id) {
+                    Some(channel) => {
+                        if self.swarm.send_response(channel, data.into()).is_err() {
+                            tracing::debug!("request {request_id} expired before it was answered");
+                        }
+                    }
+                    None => tracing::debug!("no pending request with id {request_id}"),
+                }
+            }
+            Command::ReportValidation {
+                message_id,
+                acceptance,
+            } => {
+                // messages published by this node are not pending validation
+                if let Some((peer_id, _)) = self.pending_validations.remove(&message_id) {
+                    log_error!(self
+                        .swarm
+                        .report_validation(&message_id, &peer_id, acceptance));
+                }
+            }
+            Command::Penalize { peer_id } => {
+                self.swarm.penalize(&peer_id);
+            }
             Command::DirectBroadcastAndRetry {
                 topic,
                 message,
@@ -1,18 +1,21 @@
 // std
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 // crates
 use async_trait::async_trait;
 use bytes::Bytes;
 use thiserror::Error;
 // internal
-use super::{StorageBackend, StorageSerde, StorageTransaction};
+use super::{
+    Column, KeyRange, StorageBackend, StorageSerde, StorageTransaction, WriteBatch, WriteOp,
+};
 
 #[derive(Debug, Error)]
 #[error("Errors in MockStorage should not happen")]
 pub enum MockStorageError {}
 
-pub type MockStorageTransaction = Box<dyn Fn(&mut HashMap<Bytes, Bytes>) + Send + Sync>;
+/// Transactions operate over the default column
+pub type MockStorageTransaction = Box<dyn Fn(&mut BTreeMap<Bytes, Bytes>) + Send + Sync>;
 
 impl StorageTransaction for MockStorageTransaction {
     type Result = ();
@@ -21,13 +24,19 @@ impl StorageTransaction for MockStorageTransaction {
 
 //
 pub struct MockStorage<SerdeOp> {
-    inner: HashMap<Bytes, Bytes>,
+    columns: HashMap<Column, BTreeMap<Bytes, Bytes>>,
     _serde_op: PhantomData<SerdeOp>,
 }
 
+impl<SerdeOp> MockStorage<SerdeOp> {
+    fn column(&mut self, column: Column) -> &mut BTreeMap<Bytes, Bytes> {
+        self.columns.entry(column).or_default()
+    }
+}
+
 impl<SerdeOp> core::fmt::Debug for MockStorage<SerdeOp> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        format!("MockStorage {{ inner: {:?} }}", self.inner).fmt(f)
+        format!("MockStorage {{ columns: {:?} }}", self.columns).fmt(f)
     }
 }
 
@@ -40,26 +49,78 @@ impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for MockStora
 
     fn new(_config: Self::Settings) -> Result<Self, Self::Error> {
         Ok(Self {
-            inner: HashMap::new(),
+            columns: HashMap::new(),
             _serde_op: Default::default(),
         })
     }
 
     async fn store(&mut self, key: Bytes, value: Bytes) -> Result<(), Self::Error> {
-        let _ = self.inner.insert(key, value);
+        let _ = self.column(Column::Default).insert(key, value);
         Ok(())
     }
 
     async fn load(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
-        Ok(self.inner.get(key).cloned())
+        self.load_from(Column::Default, key).await
     }
 
     async fn remove(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
-        Ok(self.inner.remove(key))
+        Ok(self.column(Column::Default).remove(key))
+    }
+
+    async fn load_from(
+        &mut self,
+        column: Column,
+        key: &[u8],
+    ) -> Result<Option<Bytes>, Self::Error> {
+        Ok(self.column(column).get(key).cloned())
+    }
+
+    async fn scan(
+        &mut self,
+        column: Column,
+        range: KeyRange,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Bytes, Bytes)>, Self::Error> {
+        // `BTreeMap::range` panics on inverted ranges, for which sled returns nothing
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .column(column)
+            .range(range)
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    async fn write_batch(&mut self, batch: WriteBatch) -> Result<(), Self::Error> {
+        for op in batch {
+            match op {
+                WriteOp::Store { column, key, value } => {
+                    self.column(column).insert(key, value);
+                }
+                WriteOp::Remove { column, key } => {
+                    self.column(column).remove(&key);
+                }
+            }
+        }
+        Ok(())
     }
 
     async fn execute(&mut self, transaction: Self::Transaction) -> Result<(), Self::Error> {
-        transaction(&mut self.inner);
+        transaction(self.column(Column::Default));
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{self, NoStorageSerde};
+    use super::*;
+
+    #[tokio::test]
+    async fn scan_empty_ranges() {
+        let mut storage: MockStorage<NoStorageSerde> = MockStorage::new(()).unwrap();
+        testing::scan_empty_ranges(&mut storage).await;
+    }
+}
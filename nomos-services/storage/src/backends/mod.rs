@@ -1,10 +1,13 @@
 #[cfg(feature = "mock")]
 pub mod mock;
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb;
 #[cfg(feature = "sled")]
 pub mod sled;
 
 // std
 use std::error::Error;
+use std::ops::{Bound, RangeBounds};
 // crates
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -26,6 +29,173 @@ pub trait StorageTransaction: Send + Sync {
     type Transaction: Send + Sync;
 }
 
+/// Named column families, keeping unrelated data apart within the same backend.
+/// Keys only need to be unique within a column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Column {
+    /// Column used by the single key operations
+    Default,
+    Blocks,
+    Qcs,
+    Blobs,
+    Metadata,
+}
+
+impl Column {
+    pub const ALL: [Column; 5] = [
+        Column::Default,
+        Column::Blocks,
+        Column::Qcs,
+        Column::Blobs,
+        Column::Metadata,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Column::Default => "default",
+            Column::Blocks => "blocks",
+            Column::Qcs => "qcs",
+            Column::Blobs => "blobs",
+            Column::Metadata => "metadata",
+        }
+    }
+}
+
+/// Range of keys to scan, compared in lexicographic byte order.
+///
+/// Keys are scanned as raw bytes, so they should be encoded so that their byte order matches the
+/// intended one (e.g. big endian integers).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyRange {
+    pub start: Bound<Bytes>,
+    pub end: Bound<Bytes>,
+}
+
+impl KeyRange {
+    pub fn new(range: impl RangeBounds<Bytes>) -> Self {
+        Self {
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    /// Every key in the column
+    pub fn all() -> Self {
+        Self::new(..)
+    }
+
+    /// Keys starting with `prefix`
+    pub fn prefix(prefix: impl Into<Bytes>) -> Self {
+        let prefix = prefix.into();
+        // the first key past the prefix is the prefix with its last non 0xff byte incremented
+        let mut end = prefix.to_vec();
+        while let Some(last) = end.pop() {
+            if last < u8::MAX {
+                end.push(last + 1);
+                break;
+            }
+        }
+        let end = if end.is_empty() {
+            Bound::Unbounded
+        } else {
+            Bound::Excluded(end.into())
+        };
+        Self {
+            start: Bound::Included(prefix),
+            end,
+        }
+    }
+
+    /// Whether no key can be within the range, i.e. its start is past its end
+    pub fn is_empty(&self) -> bool {
+        match (&self.start, &self.end) {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (Bound::Included(start), Bound::Excluded(end))
+            | (Bound::Excluded(start), Bound::Included(end))
+            | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        let bounds = (
+            self.start.as_ref().map(Bytes::as_ref),
+            self.end.as_ref().map(Bytes::as_ref),
+        );
+        RangeBounds::<[u8]>::contains(&bounds, key)
+    }
+}
+
+impl RangeBounds<Bytes> for KeyRange {
+    fn start_bound(&self) -> Bound<&Bytes> {
+        self.start.as_ref()
+    }
+
+    fn end_bound(&self) -> Bound<&Bytes> {
+        self.end.as_ref()
+    }
+}
+
+/// Single write in a [`WriteBatch`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WriteOp {
+    Store {
+        column: Column,
+        key: Bytes,
+        value: Bytes,
+    },
+    Remove {
+        column: Column,
+        key: Bytes,
+    },
+}
+
+/// Set of writes, possibly over different columns, applied atomically and in order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteBatch {
+    ops: Vec<WriteOp>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn store(&mut self, column: Column, key: impl Into<Bytes>, value: Bytes) -> &mut Self {
+        self.ops.push(WriteOp::Store {
+            column,
+            key: key.into(),
+            value,
+        });
+        self
+    }
+
+    pub fn remove(&mut self, column: Column, key: impl Into<Bytes>) -> &mut Self {
+        self.ops.push(WriteOp::Remove {
+            column,
+            key: key.into(),
+        });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+}
+
+impl IntoIterator for WriteBatch {
+    type Item = WriteOp;
+    type IntoIter = std::vec::IntoIter<WriteOp>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ops.into_iter()
+    }
+}
+
 /// Main storage functionality trait
 #[async_trait]
 pub trait StorageBackend: Sized {
@@ -43,6 +213,21 @@ pub trait StorageBackend: Sized {
     async fn store(&mut self, key: Bytes, value: Bytes) -> Result<(), Self::Error>;
     async fn load(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error>;
     async fn remove(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error>;
+    async fn load_from(&mut self, column: Column, key: &[u8])
+        -> Result<Option<Bytes>, Self::Error>;
+    /// Entries of `column` with a key within `range`, in ascending key order.
+    /// At most `limit` entries are returned if set.
+    async fn scan(
+        &mut self,
+        column: Column,
+        range: KeyRange,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Bytes, Bytes)>, Self::Error>;
+    /// Apply every write in `batch` or none of them.
+    ///
+    /// Unlike single stores, batches are durable once applied, so they must be used for data
+    /// that has to survive a crash as soon as it is written.
+    async fn write_batch(&mut self, batch: WriteBatch) -> Result<(), Self::Error>;
     /// Execute a transaction in the current backend
     async fn execute(
         &mut self,
@@ -50,12 +235,36 @@ pub trait StorageBackend: Sized {
     ) -> Result<<Self::Transaction as StorageTransaction>::Result, Self::Error>;
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_range() {
+        let range = KeyRange::prefix(&b"ab"[..]);
+        assert!(range.contains(b"ab"));
+        assert!(range.contains(b"ab\xff\xff"));
+        assert!(!range.contains(b"aa\xff"));
+        assert!(!range.contains(b"ac"));
+
+        let range = KeyRange::prefix(&b"a\xff"[..]);
+        assert!(range.contains(b"a\xff\x00"));
+        assert!(!range.contains(b"b"));
+
+        let range = KeyRange::prefix(&b"\xff"[..]);
+        assert_eq!(range.end, Bound::Unbounded);
+        assert!(range.contains(b"\xff\xff"));
+        assert!(!range.contains(b"\xfe"));
+    }
+}
+
 #[cfg(test)]
 pub mod testing {
-    use super::StorageSerde;
+    use super::{Column, KeyRange, StorageBackend, StorageSerde};
     use bytes::Bytes;
     use serde::de::DeserializeOwned;
     use serde::Serialize;
+    use std::ops::Bound;
     use thiserror::Error;
 
     pub struct NoStorageSerde;
@@ -75,4 +284,42 @@ pub mod testing {
             Err(NoError)
         }
     }
+
+    /// Scans over empty or inverted ranges return nothing on every backend instead of failing
+    pub async fn scan_empty_ranges<Backend: StorageBackend>(storage: &mut Backend) {
+        for key in [&b"a"[..], b"b", b"c"] {
+            storage
+                .store(Bytes::from(key), Bytes::from("value"))
+                .await
+                .unwrap();
+        }
+
+        let key = |key: &'static [u8]| Bytes::from(key);
+        for range in [
+            KeyRange::new(key(b"c")..key(b"a")),
+            KeyRange::new(key(b"c")..=key(b"a")),
+            KeyRange::new(key(b"b")..key(b"b")),
+            KeyRange {
+                start: Bound::Excluded(key(b"b")),
+                end: Bound::Excluded(key(b"b")),
+            },
+            KeyRange {
+                start: Bound::Excluded(key(b"b")),
+                end: Bound::Included(key(b"b")),
+            },
+        ] {
+            assert!(range.is_empty());
+            assert_eq!(
+                storage.scan(Column::Default, range, None).await.unwrap(),
+                vec![]
+            );
+        }
+
+        let single = KeyRange::new(key(b"b")..=key(b"b"));
+        assert!(!single.is_empty());
+        assert_eq!(
+            storage.scan(Column::Default, single, None).await.unwrap(),
+            vec![(key(b"b"), Bytes::from("value"))]
+        );
+    }
 }
@@ -0,0 +1,344 @@
+// std
+use std::marker::PhantomData;
+use std::ops::Bound;
+use std::path::PathBuf;
+// crates
+use async_trait::async_trait;
+use bytes::Bytes;
+use rocksdb::{
+    BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, DBCompactionStyle, Direction,
+    IteratorMode, Options, TransactionDB, TransactionDBOptions, WriteBatchWithTransaction,
+    WriteOptions,
+};
+// internal
+use super::{
+    Column, KeyRange, StorageBackend, StorageSerde, StorageTransaction, WriteBatch, WriteOp,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Error(#[from] rocksdb::Error),
+}
+
+/// Compaction strategy used by every column
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompactionStyle {
+    /// Lower space and read amplification, at the cost of more write amplification
+    #[default]
+    Level,
+    /// Lower write amplification, at the cost of more space and read amplification
+    Universal,
+    /// Drops the oldest files once the size limit is reached, only suitable for caches
+    Fifo,
+}
+
+impl From<CompactionStyle> for DBCompactionStyle {
+    fn from(style: CompactionStyle) -> Self {
+        match style {
+            CompactionStyle::Level => DBCompactionStyle::Level,
+            CompactionStyle::Universal => DBCompactionStyle::Universal,
+            CompactionStyle::Fifo => DBCompactionStyle::Fifo,
+        }
+    }
+}
+
+/// Rocksdb backend setting
+#[derive(Clone, Debug)]
+pub struct RocksBackendSettings {
+    /// File path to the db directory
+    pub db_path: PathBuf,
+    pub compaction_style: CompactionStyle,
+    /// Size in bytes of the block cache shared by every column
+    pub block_cache_size: usize,
+    /// Size in bytes of the memtable of each column before it gets flushed to disk
+    pub write_buffer_size: usize,
+    /// Maximum number of concurrent background compactions and flushes
+    pub max_background_jobs: i32,
+}
+
+impl RocksBackendSettings {
+    /// Settings with rocksdb defaults, storing the db under `db_path`
+    pub fn new(db_path: PathBuf) -> Self {
+        Self {
+            db_path,
+            compaction_style: CompactionStyle::Level,
+            block_cache_size: 8 << 20,
+            write_buffer_size: 64 << 20,
+            max_background_jobs: 2,
+        }
+    }
+
+    fn options(&self) -> Options {
+        let mut block_options = BlockBasedOptions::default();
+        block_options.set_block_cache(&Cache::new_lru_cache(self.block_cache_size));
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        options.set_compaction_style(self.compaction_style.into());
+        options.set_write_buffer_size(self.write_buffer_size);
+        options.set_max_background_jobs(self.max_background_jobs);
+        options.set_block_based_table_factory(&block_options);
+        options
+    }
+}
+
+/// Rocksdb transactions are write batches, applied atomically
+impl StorageTransaction for WriteBatch {
+    type Result = ();
+    type Transaction = Self;
+}
+
+/// Rocksdb storage backend
+///
+/// The db is opened as a transaction db so that reads and writes of the same key can be done
+/// atomically.
+pub struct RocksBackend<SerdeOp> {
+    rocks: TransactionDB,
+    path: PathBuf,
+    _serde_op: PhantomData<SerdeOp>,
+}
+
+impl<SerdeOp> RocksBackend<SerdeOp> {
+    fn column(&self, column: Column) -> &ColumnFamily {
+        self.rocks
+            .cf_handle(column.name())
+            .expect("every column is opened along with the db")
+    }
+
+    /// Write options of batches.
+    ///
+    /// Every write goes through rocksdb's write-ahead log, which survives the process crashing,
+    /// but the log is only synced to disk by the OS later on. With `sync` set, the log is synced
+    /// before the write returns, so that batches also survive the machine crashing.
+    fn batch_write_options() -> WriteOptions {
+        let mut options = WriteOptions::default();
+        options.set_sync(true);
+        options
+    }
+}
+
+impl<SerdeOp> core::fmt::Debug for RocksBackend<SerdeOp> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format!("RocksBackend {{ path: {:?} }}", self.path).fmt(f)
+    }
+}
+
+#[async_trait]
+impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for RocksBackend<SerdeOp> {
+    type Settings = RocksBackendSettings;
+    type Error = Error;
+    type Transaction = WriteBatch;
+    type SerdeOperator = SerdeOp;
+
+    fn new(config: Self::Settings) -> Result<Self, Self::Error> {
+        let options = config.options();
+        let columns = Column::ALL
+            .iter()
+            .map(|column| ColumnFamilyDescriptor::new(column.name(), options.clone()));
+        Ok(Self {
+            rocks: TransactionDB::open_cf_descriptors(
+                &options,
+                &TransactionDBOptions::default(),
+                &config.db_path,
+                columns,
+            )?,
+            path: config.db_path,
+            _serde_op: Default::default(),
+        })
+    }
+
+    async fn store(&mut self, key: Bytes, value: Bytes) -> Result<(), Self::Error> {
+        self.rocks
+            .put_cf(self.column(Column::Default), key, value)?;
+        Ok(())
+    }
+
+    async fn load(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
+        self.load_from(Column::Default, key).await
+    }
+
+    async fn remove(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
+        let column = self.column(Column::Default);
+        // the key is locked by the transaction, so the value can't change before it is deleted
+        let transaction = self.rocks.transaction();
+        let value = transaction.get_for_update_cf(column, key, true)?;
+        if value.is_some() {
+            transaction.delete_cf(column, key)?;
+        }
+        transaction.commit()?;
+        Ok(value.map(Bytes::from))
+    }
+
+    async fn load_from(
+        &mut self,
+        column: Column,
+        key: &[u8],
+    ) -> Result<Option<Bytes>, Self::Error> {
+        Ok(self
+            .rocks
+            .get_cf(self.column(column), key)?
+            .map(Bytes::from))
+    }
+
+    async fn scan(
+        &mut self,
+        column: Column,
+        range: KeyRange,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Bytes, Bytes)>, Self::Error> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mode = match &range.start {
+            Bound::Included(start) | Bound::Excluded(start) => {
+                IteratorMode::From(start, Direction::Forward)
+            }
+            Bound::Unbounded => IteratorMode::Start,
+        };
+        let mut entries = Vec::new();
+        for entry in self.rocks.iterator_cf(self.column(column), mode) {
+            if entries.len() >= limit {
+                break;
+            }
+            let (key, value) = entry?;
+            if !range.contains(&key) {
+                // keys are visited in order, so only an excluded start can precede the range
+                if matches!(&range.start, Bound::Excluded(start) if start[..] == key[..]) {
+                    continue;
+                }
+                break;
+            }
+            entries.push((Bytes::from(key.into_vec()), Bytes::from(value.into_vec())));
+        }
+        Ok(entries)
+    }
+
+    async fn write_batch(&mut self, batch: WriteBatch) -> Result<(), Self::Error> {
+        let mut rocks_batch = WriteBatchWithTransaction::<true>::default();
+        for op in batch {
+            match op {
+                WriteOp::Store { column, key, value } => {
+                    rocks_batch.put_cf(self.column(column), key, value)
+                }
+                WriteOp::Remove { column, key } => rocks_batch.delete_cf(self.column(column), key),
+            }
+        }
+        self.rocks
+            .write_opt(rocks_batch, &Self::batch_write_options())?;
+        Ok(())
+    }
+
+    async fn execute(
+        &mut self,
+        transaction: Self::Transaction,
+    ) -> Result<<Self::Transaction as StorageTransaction>::Result, Self::Error> {
+        self.write_batch(transaction).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::testing::{self, NoStorageSerde};
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_store_load_remove(
+    ) -> Result<(), <RocksBackend<NoStorageSerde> as StorageBackend>::Error> {
+        let temp_path = TempDir::new().unwrap();
+        let settings = RocksBackendSettings::new(temp_path.path().to_path_buf());
+        let key = "foo";
+        let value = "bar";
+
+        let mut db: RocksBackend<NoStorageSerde> = RocksBackend::new(settings)?;
+        db.store(key.as_bytes().into(), value.as_bytes().into())
+            .await?;
+        let load_value = db.load(key.as_bytes()).await?;
+        assert_eq!(load_value, Some(value.as_bytes().into()));
+        let removed_value = db.remove(key.as_bytes()).await?;
+        assert_eq!(removed_value, Some(value.as_bytes().into()));
+        assert_eq!(db.load(key.as_bytes()).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction(
+    ) -> Result<(), <RocksBackend<NoStorageSerde> as StorageBackend>::Error> {
+        let temp_path = TempDir::new().unwrap();
+        let settings = RocksBackendSettings::new(temp_path.path().to_path_buf());
+
+        let mut db: RocksBackend<NoStorageSerde> = RocksBackend::new(settings)?;
+        let mut transaction = WriteBatch::new();
+        transaction
+            .store(Column::Default, "foo", Bytes::from("bar"))
+            .store(Column::Default, "baz", Bytes::from("qux"))
+            .remove(Column::Default, "baz");
+        db.execute(transaction).await?;
+        assert_eq!(db.load(b"foo").await?, Some("bar".as_bytes().into()));
+        assert_eq!(db.load(b"baz").await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_columns_scan_and_batch(
+    ) -> Result<(), <RocksBackend<NoStorageSerde> as StorageBackend>::Error> {
+        let temp_path = TempDir::new().unwrap();
+        let settings = RocksBackendSettings {
+            compaction_style: CompactionStyle::Universal,
+            ..RocksBackendSettings::new(temp_path.path().to_path_buf())
+        };
+        let mut db: RocksBackend<NoStorageSerde> = RocksBackend::new(settings)?;
+
+        let mut batch = WriteBatch::new();
+        for view in [3u64, 1, 2] {
+            batch.store(
+                Column::Blocks,
+                view.to_be_bytes().to_vec(),
+                Bytes::from(format!("block {view}")),
+            );
+        }
+        batch
+            .store(Column::Metadata, "tip", Bytes::from("block 3"))
+            .remove(Column::Blocks, 2u64.to_be_bytes().to_vec());
+        db.write_batch(batch).await?;
+
+        // columns are independent from each other
+        assert_eq!(db.load(b"tip").await?, None);
+        assert_eq!(
+            db.load_from(Column::Metadata, b"tip").await?,
+            Some(Bytes::from("block 3"))
+        );
+
+        let blocks = db
+            .scan(Column::Blocks, KeyRange::all(), None)
+            .await?
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![Bytes::from("block 1"), Bytes::from("block 3")]);
+        let after_one = KeyRange::new((
+            Bound::Excluded(Bytes::from(1u64.to_be_bytes().to_vec())),
+            Bound::Unbounded,
+        ));
+        assert_eq!(
+            db.scan(Column::Blocks, after_one, Some(1)).await?,
+            vec![(
+                Bytes::from(3u64.to_be_bytes().to_vec()),
+                Bytes::from("block 3")
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scan_empty_ranges(
+    ) -> Result<(), <RocksBackend<NoStorageSerde> as StorageBackend>::Error> {
+        let temp_path = TempDir::new().unwrap();
+        let settings = RocksBackendSettings::new(temp_path.path().to_path_buf());
+        let mut db: RocksBackend<NoStorageSerde> = RocksBackend::new(settings)?;
+        testing::scan_empty_ranges(&mut db).await;
+        Ok(())
+    }
+}
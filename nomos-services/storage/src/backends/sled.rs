@@ -1,4 +1,5 @@
 // std
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::path::PathBuf;
 // crates
@@ -7,8 +8,11 @@ use bytes::Bytes;
 use sled::transaction::{
     ConflictableTransactionResult, TransactionError, TransactionResult, TransactionalTree,
 };
+use sled::Transactional;
 // internal
-use super::{StorageBackend, StorageSerde, StorageTransaction};
+use super::{
+    Column, KeyRange, StorageBackend, StorageSerde, StorageTransaction, WriteBatch, WriteOp,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -43,9 +47,17 @@ impl StorageTransaction for SledTransaction {
 
 pub struct SledBackend<SerdeOp> {
     sled: sled::Db,
+    /// Each column lives in its own sled tree, the default one being the db main tree
+    columns: HashMap<Column, sled::Tree>,
     _serde_op: PhantomData<SerdeOp>,
 }
 
+impl<SerdeOp> SledBackend<SerdeOp> {
+    fn column(&self, column: Column) -> &sled::Tree {
+        &self.columns[&column]
+    }
+}
+
 impl<SerdeOp> core::fmt::Debug for SledBackend<SerdeOp> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         format!("SledBackend {{ sled: {:?} }}", self.sled).fmt(f)
@@ -60,8 +72,20 @@ impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for SledBacke
     type SerdeOperator = SerdeOp;
 
     fn new(config: Self::Settings) -> Result<Self, Self::Error> {
+        let sled = sled::open(config.db_path)?;
+        let columns = Column::ALL
+            .into_iter()
+            .map(|column| {
+                let tree = match column {
+                    Column::Default => Ok((*sled).clone()),
+                    column => sled.open_tree(column.name()),
+                };
+                tree.map(|tree| (column, tree))
+            })
+            .collect::<Result<_, _>>()?;
         Ok(Self {
-            sled: sled::open(config.db_path)?,
+            sled,
+            columns,
             _serde_op: Default::default(),
         })
     }
@@ -79,6 +103,72 @@ impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for SledBacke
         Ok(self.sled.remove(key)?.map(|ivec| ivec.to_vec().into()))
     }
 
+    async fn load_from(
+        &mut self,
+        column: Column,
+        key: &[u8],
+    ) -> Result<Option<Bytes>, Self::Error> {
+        Ok(self
+            .column(column)
+            .get(key)?
+            .map(|ivec| ivec.to_vec().into()))
+    }
+
+    async fn scan(
+        &mut self,
+        column: Column,
+        range: KeyRange,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Bytes, Bytes)>, Self::Error> {
+        // answer empty and inverted ranges consistently with the other backends
+        if range.is_empty() {
+            return Ok(vec![]);
+        }
+        self.column(column)
+            .range(range)
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|entry| {
+                entry
+                    .map(|(key, value)| (key.to_vec().into(), value.to_vec().into()))
+                    .map_err(Error::from)
+            })
+            .collect()
+    }
+
+    async fn write_batch(&mut self, batch: WriteBatch) -> Result<(), Self::Error> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let mut batches: HashMap<Column, sled::Batch> = HashMap::new();
+        for op in batch {
+            match op {
+                WriteOp::Store { column, key, value } => batches
+                    .entry(column)
+                    .or_default()
+                    .insert(key.as_ref(), value.as_ref()),
+                WriteOp::Remove { column, key } => {
+                    batches.entry(column).or_default().remove(key.as_ref())
+                }
+            }
+        }
+        let (columns, batches): (Vec<_>, Vec<_>) = batches
+            .into_iter()
+            .map(|(column, batch)| (self.column(column).clone(), batch))
+            .unzip();
+        // a single transaction over every tree involved keeps the batch atomic across columns
+        columns.as_slice().transaction(
+            |trees| -> ConflictableTransactionResult<(), sled::Error> {
+                for (tree, batch) in trees.iter().zip(&batches) {
+                    tree.apply_batch(batch)?;
+                }
+                Ok(())
+            },
+        )?;
+        // sled only flushes periodically on its own, batches must be durable once applied
+        self.sled.flush_async().await?;
+        Ok(())
+    }
+
     async fn execute(
         &mut self,
         transaction: Self::Transaction,
@@ -89,7 +179,7 @@ impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for SledBacke
 
 #[cfg(test)]
 mod test {
-    use super::super::testing::NoStorageSerde;
+    use super::super::testing::{self, NoStorageSerde};
     use super::*;
     use tempfile::TempDir;
 
@@ -115,6 +205,66 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_columns_scan_and_batch(
+    ) -> Result<(), <SledBackend<NoStorageSerde> as StorageBackend>::Error> {
+        let temp_path = TempDir::new().unwrap();
+        let sled_settings = SledBackendSettings {
+            db_path: temp_path.path().to_path_buf(),
+        };
+        let mut sled_db: SledBackend<NoStorageSerde> = SledBackend::new(sled_settings)?;
+
+        let mut batch = WriteBatch::new();
+        for view in [3u64, 1, 2] {
+            batch.store(
+                Column::Blocks,
+                view.to_be_bytes().to_vec(),
+                Bytes::from(format!("block {view}")),
+            );
+        }
+        batch
+            .store(Column::Metadata, "tip", Bytes::from("block 3"))
+            .remove(Column::Blocks, 2u64.to_be_bytes().to_vec());
+        sled_db.write_batch(batch).await?;
+
+        // columns are independent from each other
+        assert_eq!(sled_db.load(b"tip").await?, None);
+        assert_eq!(
+            sled_db.load_from(Column::Metadata, b"tip").await?,
+            Some(Bytes::from("block 3"))
+        );
+
+        let blocks = sled_db
+            .scan(Column::Blocks, KeyRange::all(), None)
+            .await?
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![Bytes::from("block 1"), Bytes::from("block 3")]);
+        let from_two = KeyRange::new(Bytes::from(2u64.to_be_bytes().to_vec())..);
+        assert_eq!(
+            sled_db.scan(Column::Blocks, from_two, Some(1)).await?,
+            vec![(
+                Bytes::from(3u64.to_be_bytes().to_vec()),
+                Bytes::from("block 3")
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scan_empty_ranges(
+    ) -> Result<(), <SledBackend<NoStorageSerde> as StorageBackend>::Error> {
+        let temp_path = TempDir::new().unwrap();
+        let sled_settings = SledBackendSettings {
+            db_path: temp_path.path().to_path_buf(),
+        };
+        let mut sled_db: SledBackend<NoStorageSerde> = SledBackend::new(sled_settings)?;
+        testing::scan_empty_ranges(&mut sled_db).await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_transaction() -> Result<(), <SledBackend<NoStorageSerde> as StorageBackend>::Error>
     {
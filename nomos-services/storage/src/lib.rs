@@ -12,13 +12,16 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 // internal
 use backends::StorageBackend;
-use backends::{StorageSerde, StorageTransaction};
+use backends::{Column, KeyRange, StorageSerde, StorageTransaction, WriteBatch};
 use overwatch_rs::services::life_cycle::LifecycleMessage;
 use overwatch_rs::services::relay::RelayMessage;
 use overwatch_rs::services::state::{NoOperator, NoState};
 use overwatch_rs::services::{ServiceCore, ServiceData, ServiceId};
 use tracing::error;
 
+/// Raw key value pairs returned by scans
+pub type Entries = Vec<(Bytes, Bytes)>;
+
 /// Storage message that maps to [`StorageBackend`] trait
 pub enum StorageMsg<Backend: StorageBackend> {
     Load {
@@ -38,6 +41,21 @@ pub enum StorageMsg<Backend: StorageBackend> {
         reply_channel:
             tokio::sync::oneshot::Sender<<Backend::Transaction as StorageTransaction>::Result>,
     },
+    LoadFrom {
+        column: Column,
+        key: Bytes,
+        reply_channel: tokio::sync::oneshot::Sender<Option<Bytes>>,
+    },
+    Scan {
+        column: Column,
+        range: KeyRange,
+        limit: Option<usize>,
+        reply_channel: tokio::sync::oneshot::Sender<Entries>,
+    },
+    WriteBatch {
+        batch: WriteBatch,
+        reply_channel: tokio::sync::oneshot::Sender<Result<(), Backend::Error>>,
+    },
 }
 
 /// Reply channel for storage messages
@@ -80,6 +98,28 @@ impl<Backend: StorageBackend> StorageReplyReceiver<Option<Bytes>, Backend> {
     }
 }
 
+impl<Backend: StorageBackend> StorageReplyReceiver<Entries, Backend> {
+    /// Receive scanned entries, transforming their values into the desired type.
+    /// Keys are kept as raw bytes, as they are chosen by the caller to get the desired ordering.
+    pub async fn recv<Output>(
+        self,
+    ) -> Result<Vec<(Bytes, Output)>, tokio::sync::oneshot::error::RecvError>
+    where
+        Output: DeserializeOwned,
+    {
+        self.channel.await.map(|entries| {
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = Backend::SerdeOperator::deserialize(value)
+                        .expect("Recovery from storage should never fail");
+                    (key, value)
+                })
+                .collect()
+        })
+    }
+}
+
 impl<Backend: StorageBackend> StorageMsg<Backend> {
     pub fn new_load_message<K: Serialize>(
         key: K,
@@ -132,6 +172,76 @@ impl<Backend: StorageBackend> StorageMsg<Backend> {
     }
 }
 
+impl<Backend: StorageBackend> StorageMsg<Backend> {
+    /// Load the value stored under a raw `key` in `column`
+    pub fn new_load_from_message(
+        column: Column,
+        key: impl Into<Bytes>,
+    ) -> (
+        StorageMsg<Backend>,
+        StorageReplyReceiver<Option<Bytes>, Backend>,
+    ) {
+        let (reply_channel, receiver) = tokio::sync::oneshot::channel();
+        (
+            Self::LoadFrom {
+                column,
+                key: key.into(),
+                reply_channel,
+            },
+            StorageReplyReceiver::new(receiver),
+        )
+    }
+
+    /// Scan the entries of `column` within `range`, in ascending key order
+    pub fn new_scan_message(
+        column: Column,
+        range: KeyRange,
+        limit: Option<usize>,
+    ) -> (
+        StorageMsg<Backend>,
+        StorageReplyReceiver<Entries, Backend>,
+    ) {
+        let (reply_channel, receiver) = tokio::sync::oneshot::channel();
+        (
+            Self::Scan {
+                column,
+                range,
+                limit,
+                reply_channel,
+            },
+            StorageReplyReceiver::new(receiver),
+        )
+    }
+
+    /// Apply every write in `batch` or none of them, the reply tells whether it was applied.
+    /// The receiver can be dropped if the caller doesn't need to wait for the batch.
+    pub fn new_write_batch_message(
+        batch: WriteBatch,
+    ) -> (
+        StorageMsg<Backend>,
+        StorageReplyReceiver<Result<(), Backend::Error>, Backend>,
+    ) {
+        let (reply_channel, receiver) = tokio::sync::oneshot::channel();
+        (
+            Self::WriteBatch {
+                batch,
+                reply_channel,
+            },
+            StorageReplyReceiver::new(receiver),
+        )
+    }
+
+    /// Add a store of `value` under a raw `key` to `batch`, serialized as any other stored value
+    pub fn batch_store<V: Serialize>(
+        batch: &mut WriteBatch,
+        column: Column,
+        key: impl Into<Bytes>,
+        value: V,
+    ) {
+        batch.store(column, key, Backend::SerdeOperator::serialize(value));
+    }
+}
+
 // Implement `Debug` manually to avoid constraining `Backend` to `Debug`
 impl<Backend: StorageBackend> Debug for StorageMsg<Backend> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -146,6 +256,20 @@ impl<Backend: StorageBackend> Debug for StorageMsg<Backend> {
                 write!(f, "Remove {{ {key:?} }}")
             }
             StorageMsg::Execute { .. } => write!(f, "Execute transaction"),
+            StorageMsg::LoadFrom { column, key, .. } => {
+                write!(f, "LoadFrom {{ {column:?}, {key:?} }}")
+            }
+            StorageMsg::Scan {
+                column,
+                range,
+                limit,
+                ..
+            } => {
+                write!(f, "Scan {{ {column:?}, {range:?}, {limit:?} }}")
+            }
+            StorageMsg::WriteBatch { batch, .. } => {
+                write!(f, "WriteBatch {{ {} operations }}", batch.len())
+            }
         }
     }
 }
@@ -197,6 +321,21 @@ impl<Backend: StorageBackend + Send + Sync + 'static> StorageService<Backend> {
                 transaction,
                 reply_channel,
             } => Self::handle_execute(backend, transaction, reply_channel).await,
+            StorageMsg::LoadFrom {
+                column,
+                key,
+                reply_channel,
+            } => Self::handle_load_from(backend, column, key, reply_channel).await,
+            StorageMsg::Scan {
+                column,
+                range,
+                limit,
+                reply_channel,
+            } => Self::handle_scan(backend, column, range, limit, reply_channel).await,
+            StorageMsg::WriteBatch {
+                batch,
+                reply_channel,
+            } => Self::handle_write_batch(backend, batch, reply_channel).await,
         } {
             // TODO: add proper logging
             println!("{e}");
@@ -250,6 +389,59 @@ impl<Backend: StorageBackend + Send + Sync + 'static> StorageService<Backend> {
             .map_err(StorageServiceError::BackendError)
     }
 
+    /// Handle load from column message
+    async fn handle_load_from(
+        backend: &mut Backend,
+        column: Column,
+        key: Bytes,
+        reply_channel: tokio::sync::oneshot::Sender<Option<Bytes>>,
+    ) -> Result<(), StorageServiceError<Backend>> {
+        let result: Option<Bytes> = backend
+            .load_from(column, &key)
+            .await
+            .map_err(StorageServiceError::BackendError)?;
+        reply_channel
+            .send(result)
+            .map_err(|_| StorageServiceError::ReplyError {
+                operation: "LoadFrom".to_string(),
+                key,
+            })
+    }
+
+    /// Handle scan message
+    async fn handle_scan(
+        backend: &mut Backend,
+        column: Column,
+        range: KeyRange,
+        limit: Option<usize>,
+        reply_channel: tokio::sync::oneshot::Sender<Entries>,
+    ) -> Result<(), StorageServiceError<Backend>> {
+        let result = backend
+            .scan(column, range, limit)
+            .await
+            .map_err(StorageServiceError::BackendError)?;
+        reply_channel
+            .send(result)
+            .map_err(|_| StorageServiceError::ReplyError {
+                operation: "Scan".to_string(),
+                key: Bytes::new(),
+            })
+    }
+
+    /// Handle write batch message
+    async fn handle_write_batch(
+        backend: &mut Backend,
+        batch: WriteBatch,
+        reply_channel: tokio::sync::oneshot::Sender<Result<(), Backend::Error>>,
+    ) -> Result<(), StorageServiceError<Backend>> {
+        let result = backend.write_batch(batch).await;
+        // nobody may be waiting for the reply, but failures must not go unnoticed
+        if let Err(Err(e)) = reply_channel.send(result) {
+            return Err(StorageServiceError::BackendError(e));
+        }
+        Ok(())
+    }
+
     /// Handle execute message
     async fn handle_execute(
         backend: &mut Backend,
@@ -130,6 +130,7 @@ impl SimulationApp {
                     RandomBeaconState::Sad {
                         entropy: Box::new([0; 32]),
                     },
+                    [0; 32],
                 );
                 let mut rng = SmallRng::seed_from_u64(seed);
                 overlay_node::to_overlay_node(
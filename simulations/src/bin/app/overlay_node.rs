@@ -83,6 +83,9 @@ pub fn to_overlay_node<R: Rng>(
                     node_id,
                     CarnotSettings::new(
                         settings.node_settings.timeout,
+                        settings.node_settings.adaptive_timeout,
+                        settings.node_settings.mempool_latency,
+                        settings.node_settings.pipelined,
                         settings.record_settings.clone(),
                         fmt,
                     ),
@@ -107,6 +110,9 @@ pub fn to_overlay_node<R: Rng>(
                     node_id,
                     CarnotSettings::new(
                         settings.node_settings.timeout,
+                        settings.node_settings.adaptive_timeout,
+                        settings.node_settings.mempool_latency,
+                        settings.node_settings.pipelined,
                         settings.record_settings.clone(),
                         fmt,
                     ),
@@ -130,6 +136,9 @@ pub fn to_overlay_node<R: Rng>(
                     node_id,
                     CarnotSettings::new(
                         settings.node_settings.timeout,
+                        settings.node_settings.adaptive_timeout,
+                        settings.node_settings.mempool_latency,
+                        settings.node_settings.pipelined,
                         settings.record_settings.clone(),
                         fmt,
                     ),
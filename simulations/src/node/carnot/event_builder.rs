@@ -3,6 +3,7 @@ use consensus_engine::{
     AggregateQc, Carnot, NewView, Overlay, Qc, StandardQc, Timeout, TimeoutQc, View, Vote,
 };
 use nomos_consensus::network::messages::{NewViewMsg, TimeoutMsg, VoteMsg};
+use nomos_consensus::timeout::ViewTimeout;
 use nomos_consensus::NodeId;
 use nomos_core::block::Block;
 use std::collections::HashSet;
@@ -19,12 +20,12 @@ pub(crate) struct EventBuilder {
     timeout_message: Tally<TimeoutMsg>,
     leader_new_view_message: Tally<NewViewMsg>,
     new_view_message: Tally<NewViewMsg>,
-    timeout_handler: TimeoutHandler,
+    pub(crate) timeout_handler: TimeoutHandler,
     pub(crate) current_view: View,
 }
 
 impl EventBuilder {
-    pub fn new(id: NodeId, timeout: Duration) -> Self {
+    pub fn new(id: NodeId, timeout: ViewTimeout) -> Self {
         Self {
             vote_message: Tally::new(),
             leader_vote_message: Tally::new(),
@@ -84,6 +85,7 @@ impl EventBuilder {
                 qc: Qc::Standard(StandardQc {
                     view: genesis.view,
                     id: genesis.id,
+                    signature: None,
                 }),
                 block: genesis,
                 votes: HashSet::new(),
@@ -93,7 +95,10 @@ impl EventBuilder {
         for message in messages {
             match message {
                 CarnotMessage::Proposal(msg) => {
-                    let block = Block::from_bytes(&msg.data);
+                    let Ok(block) = Block::from_bytes(&msg.data) else {
+                        tracing::error!(node=%self.id, "invalid proposal message");
+                        continue;
+                    };
                     tracing::info!(
                         node=%self.id,
                         current_view = %engine.current_view(),
@@ -140,7 +145,7 @@ impl EventBuilder {
                         engine.super_majority_threshold()
                     };
 
-                    if let Some(votes) = tally.tally_by(msg_view, msg, threshold) {
+                    if let Some(votes) = tally.tally_by(msg_view, msg, voter, &threshold) {
                         if let Some(block) = engine
                             .blocks_in_view(msg_view)
                             .iter()
@@ -161,6 +166,7 @@ impl EventBuilder {
                                     qc: Qc::Standard(StandardQc {
                                         view: block.view,
                                         id: block.id,
+                                        signature: None,
                                     }),
                                 });
                             } else {
@@ -175,10 +181,12 @@ impl EventBuilder {
                 }
                 CarnotMessage::Timeout(msg) => {
                     let msg_view = msg.vote.view;
+                    let voter = msg.voter;
                     if let Some(timeouts) = self.timeout_message.tally_by(
                         msg_view,
                         msg,
-                        engine.overlay().super_majority_threshold(self.id),
+                        voter,
+                        &engine.overlay().super_majority_threshold(self.id),
                     ) {
                         events.push(Event::RootTimeout {
                             timeouts: timeouts.into_iter().map(|v| v.vote).collect(),
@@ -206,13 +214,14 @@ impl EventBuilder {
                         engine.super_majority_threshold()
                     };
 
-                    if let Some(votes) = tally.tally_by(msg_view, msg, threshold) {
+                    if let Some(votes) = tally.tally_by(msg_view, msg, voter, &threshold) {
                         if is_next_view_leader && is_message_from_root_committee {
                             let high_qc = engine.high_qc();
                             events.push(Event::ProposeBlock {
                                 qc: Qc::Aggregated(AggregateQc {
                                     high_qc,
                                     view: msg_view.next(),
+                                    signature: None,
                                 }),
                             });
                         } else {
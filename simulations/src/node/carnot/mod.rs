@@ -35,6 +35,7 @@ use consensus_engine::{
 };
 use nomos_consensus::committee_membership::UpdateableCommitteeMembership;
 use nomos_consensus::network::messages::{ProposalMsg, TimeoutQcMsg};
+use nomos_consensus::timeout::{AdaptiveTimeoutSettings, ViewTimeout};
 use nomos_consensus::{
     leader_selection::UpdateableLeaderSelection,
     network::messages::{NewViewMsg, TimeoutMsg, VoteMsg},
@@ -45,6 +46,15 @@ static RECORD_SETTINGS: std::sync::OnceLock<BTreeMap<String, bool>> = std::sync:
 #[derive(Clone, Default, Deserialize)]
 pub struct CarnotSettings {
     timeout: Duration,
+    #[serde(default)]
+    adaptive_timeout: Option<AdaptiveTimeoutSettings>,
+    /// Time the mempools take to hand a leader the contents of its proposal
+    #[serde(default)]
+    mempool_latency: Duration,
+    /// Fetch the contents of the next proposal while the votes for the parent block are
+    /// gathered, instead of once the quorum certificate is formed
+    #[serde(default)]
+    pipelined: bool,
     record_settings: BTreeMap<String, bool>,
 
     #[serde(default)]
@@ -54,11 +64,17 @@ pub struct CarnotSettings {
 impl CarnotSettings {
     pub fn new(
         timeout: Duration,
+        adaptive_timeout: Option<AdaptiveTimeoutSettings>,
+        mempool_latency: Duration,
+        pipelined: bool,
         record_settings: BTreeMap<String, bool>,
         format: SubscriberFormat,
     ) -> Self {
         Self {
             timeout,
+            adaptive_timeout,
+            mempool_latency,
+            pipelined,
             record_settings,
             format,
         }
@@ -78,6 +94,17 @@ pub struct CarnotNode<O: Overlay> {
     engine: Carnot<O>,
     random_beacon_pk: PrivateKey,
     step_duration: Duration,
+    proposal: Option<PendingProposal>,
+}
+
+/// Proposal whose contents the next leader is fetching
+struct PendingProposal {
+    /// Block extended by the proposal
+    parent: BlockId,
+    /// Time left until the contents are fetched
+    remaining: Duration,
+    /// Set once the votes for the parent block are gathered
+    qc: Option<Qc>,
 }
 
 impl<
@@ -97,7 +124,7 @@ impl<
         let overlay = O::new(overlay_settings);
         let engine = Carnot::from_genesis(id, genesis.header().clone(), overlay);
         let state = CarnotState::from(&engine);
-        let timeout = settings.timeout;
+        let timeout = ViewTimeout::new(settings.timeout, settings.adaptive_timeout);
         RECORD_SETTINGS.get_or_init(|| settings.record_settings.clone());
         // pk is generated in an insecure way, but for simulation purpouses using a rng like smallrng is more useful
         let mut pk_buff = [0; 32];
@@ -114,6 +141,7 @@ impl<
             random_beacon_pk,
             step_duration: Duration::ZERO,
             current_step: 0,
+            proposal: None,
         };
         this.state = CarnotState::from(&this.engine);
         this.state.format = this.settings.format;
@@ -135,7 +163,11 @@ impl<
                             qc: Some(Qc::Standard(StandardQc {
                                 view: vote.view,
                                 id: vote.block,
+                                signature: None,
                             })),
+                            // simulated nodes do not sign their messages
+                            public_key: Default::default(),
+                            signature: Default::default(),
                         }),
                     );
                 }
@@ -150,6 +182,8 @@ impl<
                         CarnotMessage::NewView(NewViewMsg {
                             voter: node,
                             vote: new_view.clone(),
+                            public_key: Default::default(),
+                            signature: Default::default(),
                         }),
                     );
                 }
@@ -164,6 +198,8 @@ impl<
                         CarnotMessage::Timeout(TimeoutMsg {
                             voter: node,
                             vote: timeout.clone(),
+                            public_key: Default::default(),
+                            signature: Default::default(),
                         }),
                     );
                 }
@@ -181,6 +217,9 @@ impl<
                         data: proposal.as_bytes().to_vec().into(),
                         proposal: proposal.header().id,
                         view: proposal.header().view,
+                        leader: self.id,
+                        public_key: Default::default(),
+                        signature: Default::default(),
                     }))
             }
         }
@@ -206,6 +245,14 @@ impl<
                             new = Self::update_overlay_with_block(new, &block);
                             self.engine = new;
                         }
+                        // the next proposal only depends on the block being voted
+                        if self.settings.pipelined && self.engine.is_next_leader() {
+                            self.proposal = Some(PendingProposal {
+                                parent: block.header().id,
+                                remaining: self.settings.mempool_latency,
+                                qc: None,
+                            });
+                        }
                     }
                     Err(_) => {
                         tracing::error!(
@@ -263,18 +310,16 @@ impl<
                 output = Some(Output::Send(out));
                 self.engine = new;
             }
-            Event::ProposeBlock { qc } => {
-                output = Some(Output::BroadcastProposal {
-                    proposal: nomos_core::block::Block::new(
-                        qc.view().next(),
-                        qc.clone(),
-                        [].into_iter(),
-                        [].into_iter(),
-                        self.id,
-                        RandomBeaconState::generate_happy(qc.view().next(), &self.random_beacon_pk),
-                    ),
-                });
-            }
+            Event::ProposeBlock { qc } => match &mut self.proposal {
+                Some(proposal) if proposal.parent == qc.block() => proposal.qc = Some(qc),
+                _ => {
+                    self.proposal = Some(PendingProposal {
+                        parent: qc.block(),
+                        remaining: self.settings.mempool_latency,
+                        qc: Some(qc),
+                    })
+                }
+            },
             // This branch means we already get enough new view msgs for this qc
             // So we can just call approve_new_view
             Event::NewView {
@@ -342,6 +387,26 @@ impl<
         }
     }
 
+    /// The pending proposal, once its contents are fetched and its parent has been approved
+    fn ready_proposal(&mut self) -> Option<Output<CarnotTx, CarnotBlob>> {
+        let proposal = self.proposal.as_ref()?;
+        if proposal.qc.is_none() || !proposal.remaining.is_zero() {
+            return None;
+        }
+        let qc = self.proposal.take()?.qc?;
+        Some(Output::BroadcastProposal {
+            proposal: nomos_core::block::Block::new(
+                qc.view().next(),
+                qc.clone(),
+                [].into_iter(),
+                [].into_iter(),
+                self.id,
+                RandomBeaconState::generate_happy(qc.view().next(), &self.random_beacon_pk),
+                [0; 32],
+            ),
+        })
+    }
+
     fn update_overlay_with_block<Tx: Clone + Eq + Hash>(
         state: Carnot<O>,
         block: &nomos_core::block::Block<Tx, CarnotBlob>,
@@ -418,6 +483,14 @@ impl<
         self.message_cache.update(other_view_messages);
         current_view_messages.append(&mut self.message_cache.retrieve(self.engine.current_view()));
 
+        if let Some(proposal) = &mut self.proposal {
+            proposal.remaining = proposal.remaining.saturating_sub(elapsed);
+        }
+
+        let prev_view = self.engine.current_view();
+        let prev_timeout_qc_view = self.engine.last_view_timeout_qc().map(|qc| qc.view());
+        let prev_committed_view = self.engine.latest_committed_view();
+
         let events = self
             .event_builder
             .step(current_view_messages, &self.engine, elapsed);
@@ -426,6 +499,20 @@ impl<
             self.process_event(event);
         }
 
+        if self.engine.current_view() != prev_view {
+            let timeout = &mut self.event_builder.timeout_handler.timeout;
+            if self.engine.last_view_timeout_qc().map(|qc| qc.view()) != prev_timeout_qc_view {
+                timeout.on_timeout_qc();
+            }
+            if self.engine.latest_committed_view() > prev_committed_view {
+                timeout.on_commit();
+            }
+        }
+
+        if let Some(output) = self.ready_proposal() {
+            self.handle_output(output);
+        }
+
         // update state
         self.state = CarnotState::new(
             self.current_step,
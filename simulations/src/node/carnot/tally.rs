@@ -1,8 +1,8 @@
-use consensus_engine::View;
+use consensus_engine::{NodeId, Quorum, View};
 use std::collections::{HashMap, HashSet};
 
 pub(crate) struct Tally<T: core::hash::Hash + Eq + Clone> {
-    cache: HashMap<View, HashSet<T>>,
+    cache: HashMap<View, (HashSet<T>, HashSet<NodeId>)>,
 }
 
 impl<T: core::hash::Hash + Eq + Clone> Tally<T> {
@@ -12,11 +12,20 @@ impl<T: core::hash::Hash + Eq + Clone> Tally<T> {
         }
     }
 
-    pub fn tally_by(&mut self, view: View, message: T, threshold: usize) -> Option<HashSet<T>> {
-        let entries = self.cache.entry(view).or_default();
+    /// Returns the messages of `view` once the votes of their voters reach `quorum`,
+    /// only the first time it is reached.
+    pub fn tally_by(
+        &mut self,
+        view: View,
+        message: T,
+        voter: NodeId,
+        quorum: &Quorum,
+    ) -> Option<HashSet<T>> {
+        let (entries, voters) = self.cache.entry(view).or_default();
+        let reached = quorum.is_reached(voters.iter());
         entries.insert(message);
-        let entries_len = entries.len();
-        if entries_len == threshold {
+        voters.insert(voter);
+        if !reached && quorum.is_reached(voters.iter()) {
             Some(entries.clone())
         } else {
             None
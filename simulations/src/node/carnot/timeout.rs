@@ -1,4 +1,5 @@
 use consensus_engine::View;
+use nomos_consensus::timeout::ViewTimeout;
 #[cfg(feature = "polars")]
 use polars::export::ahash::HashMap;
 #[cfg(not(feature = "polars"))]
@@ -6,12 +7,12 @@ use std::collections::HashMap;
 use std::time::Duration;
 
 pub(crate) struct TimeoutHandler {
-    pub timeout: Duration,
+    pub timeout: ViewTimeout,
     pub per_view: HashMap<View, Duration>,
 }
 
 impl TimeoutHandler {
-    pub fn new(timeout: Duration) -> Self {
+    pub fn new(timeout: ViewTimeout) -> Self {
         Self {
             timeout,
             per_view: Default::default(),
@@ -19,7 +20,7 @@ impl TimeoutHandler {
     }
 
     pub fn step(&mut self, view: View, elapsed: Duration) -> bool {
-        let timeout = self.per_view.entry(view).or_insert(self.timeout);
+        let timeout = self.per_view.entry(view).or_insert(self.timeout.get());
         *timeout = timeout.saturating_sub(elapsed);
         *timeout == Duration::ZERO
     }
@@ -71,6 +71,9 @@ mod tests {
             InMemoryNetworkInterface, Network, NetworkBehaviourKey,
         },
         node::{
+            carnot::{
+                messages::CarnotMessage, CarnotNode, CarnotRecord, CarnotSettings, CarnotState,
+            },
             dummy::{DummyMessage, DummyNode, DummySettings, DummyState},
             Node, NodeId, NodeIdExt, OverlayState, SharedState, ViewOverlay,
         },
@@ -79,14 +82,18 @@ mod tests {
             tree::{TreeOverlay, TreeSettings},
             Overlay, SimulationOverlay,
         },
-        runner::SimulationRunner,
+        runner::{BoxedNode, SimulationRunner},
         settings::SimulationSettings,
-        streaming::StreamProducer,
+        streaming::{StreamProducer, SubscriberFormat},
+    };
+    use consensus_engine::overlay::{
+        FlatOverlay, FlatOverlaySettings, FreezeMembership, RandomBeaconState, RoundRobin,
     };
     use consensus_engine::View;
     use crossbeam::channel;
     use parking_lot::RwLock;
-    use rand::rngs::mock::StepRng;
+    use rand::rngs::{mock::StepRng, SmallRng};
+    use rand::SeedableRng;
     use std::{
         collections::{BTreeMap, HashMap},
         sync::Arc,
@@ -241,4 +248,106 @@ mod tests {
         let state = nodes[1].state();
         assert_eq!(state.message_count, 10);
     }
+
+    /// Lowest view committed by carnot nodes after running the simulation for some steps
+    fn carnot_committed_view(mempool_latency: Duration, pipelined: bool, steps: usize) -> View {
+        let step_time = Duration::from_millis(100);
+        let settings = SimulationSettings {
+            node_count: 4,
+            step_time,
+            seed: Some(0),
+            ..Default::default()
+        };
+        let node_ids: Vec<NodeId> = (0..settings.node_count).map(NodeId::from_index).collect();
+        let regions = HashMap::from([(Region::Europe, node_ids.clone())]);
+        let behaviour = HashMap::from([(
+            NetworkBehaviourKey::new(Region::Europe, Region::Europe),
+            NetworkBehaviour::new(step_time, 0.0),
+        )]);
+        let mut network = Network::new(RegionsData::new(regions, behaviour), 0);
+        let genesis = nomos_core::block::Block::new(
+            View::new(0),
+            consensus_engine::Block::genesis().parent_qc,
+            [].into_iter(),
+            [].into_iter(),
+            node_ids[0],
+            RandomBeaconState::Sad {
+                entropy: Box::new([0; 32]),
+            },
+            [0; 32],
+        );
+        let mut rng = SmallRng::seed_from_u64(0);
+        let nodes = node_ids
+            .iter()
+            .map(|node_id| {
+                let (node_message_sender, node_message_receiver) = channel::unbounded();
+                let (node_message_broadcast_sender, node_message_broadcast_receiver) =
+                    channel::unbounded();
+                let network_message_receiver = network.connect(
+                    *node_id,
+                    u32::MAX,
+                    node_message_receiver,
+                    node_message_broadcast_receiver,
+                );
+                let network_interface = InMemoryNetworkInterface::new(
+                    *node_id,
+                    node_message_broadcast_sender,
+                    node_message_sender,
+                    network_message_receiver,
+                );
+                Box::new(
+                    CarnotNode::<FlatOverlay<RoundRobin, FreezeMembership>>::new(
+                        *node_id,
+                        CarnotSettings::new(
+                            Duration::from_secs(10),
+                            None,
+                            mempool_latency,
+                            pipelined,
+                            BTreeMap::new(),
+                            SubscriberFormat::default(),
+                        ),
+                        FlatOverlaySettings {
+                            nodes: node_ids.clone(),
+                            leader: RoundRobin::new(),
+                            leader_super_majority_threshold: None,
+                        },
+                        genesis.clone(),
+                        network_interface,
+                        &mut rng,
+                    ),
+                ) as BoxedNode<CarnotSettings, CarnotState>
+            })
+            .collect();
+
+        let mut runner: SimulationRunner<CarnotMessage, CarnotRecord, _, _> =
+            SimulationRunner::new(network, nodes, Default::default(), settings).unwrap();
+        let mut nodes = runner.nodes.write();
+        for _ in 0..steps {
+            runner.inner.step(&mut nodes, step_time);
+        }
+        nodes
+            .iter()
+            .map(|node| node.state().latest_committed_view)
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn carnot_pipelined_proposals() {
+        // without a mempool round trip to hide both modes make the same progress
+        let sequential = carnot_committed_view(Duration::ZERO, false, 100);
+        let pipelined = carnot_committed_view(Duration::ZERO, true, 100);
+        assert!(sequential > View::new(0));
+        assert_eq!(pipelined, sequential);
+
+        // otherwise pipelined leaders fetch the contents while the votes are gathered
+        let mempool_latency = Duration::from_millis(300);
+        let sequential = carnot_committed_view(mempool_latency, false, 100);
+        let pipelined = carnot_committed_view(mempool_latency, true, 100);
+        assert!(sequential > View::new(0));
+        assert!(
+            pipelined > sequential,
+            "pipelined: {pipelined:?}, sequential: {sequential:?}"
+        );
+    }
 }
@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use crate::network::NetworkSettings;
 use crate::streaming::StreamSettings;
 use crate::warding::Ward;
+use nomos_consensus::timeout::AdaptiveTimeoutSettings;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -46,6 +47,16 @@ pub struct NodeSettings {
     pub network_capacity_kbps: u32,
     #[serde(with = "humantime_serde")]
     pub timeout: std::time::Duration,
+    /// Time the mempools take to hand a leader the contents of its proposal
+    #[serde(default, with = "humantime_serde")]
+    pub mempool_latency: std::time::Duration,
+    /// Fetch the contents of the next proposal while the votes for the parent block are
+    /// gathered, as the consensus service does in pipelined mode
+    #[serde(default)]
+    pub pipelined: bool,
+    /// Back off after timeout qcs and decay after commits, starting from `timeout`
+    #[serde(default)]
+    pub adaptive_timeout: Option<AdaptiveTimeoutSettings>,
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
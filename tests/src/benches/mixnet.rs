@@ -2,7 +2,10 @@ use std::time::Duration;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use futures::StreamExt;
-use mixnet_client::{MessageStream, MixnetClient, MixnetClientConfig, MixnetClientMode};
+use mixnet_client::{
+    CoverTrafficConfig, HopDelays, MessageStream, MixnetClient, MixnetClientConfig,
+    MixnetClientMode,
+};
 use rand::{rngs::OsRng, Rng, RngCore};
 use tests::MixNode;
 use tokio::time::Instant;
@@ -33,9 +36,14 @@ async fn setup(msg_size: usize) -> (Vec<MixNode>, MixnetClient<OsRng>, MessageSt
         MixnetClientConfig {
             mode: MixnetClientMode::Sender,
             topology: topology.clone(),
+            topology_provider: Default::default(),
+            topology_refresh_interval: Duration::from_secs(60),
             connection_pool_size: 255,
             max_retries: 3,
             retry_delay: Duration::from_secs(5),
+            hop_delays: HopDelays::default(),
+            cover_traffic: CoverTrafficConfig::default(),
+            acknowledgements: None,
         },
         OsRng,
     );
@@ -48,9 +56,14 @@ async fn setup(msg_size: usize) -> (Vec<MixNode>, MixnetClient<OsRng>, MessageSt
                 node_configs.last().unwrap().client_listen_address,
             ),
             topology,
+            topology_provider: Default::default(),
+            topology_refresh_interval: Duration::from_secs(60),
             connection_pool_size: 255,
             max_retries: 3,
             retry_delay: Duration::from_secs(5),
+            hop_delays: HopDelays::default(),
+            cover_traffic: CoverTrafficConfig::default(),
+            acknowledgements: None,
         },
         OsRng,
     );
@@ -6,6 +6,7 @@ use std::{
 
 use mixnet_node::{MixnetNodeConfig, PRIVATE_KEY_SIZE};
 use mixnet_topology::{Layer, MixnetTopology, Node};
+use mixnode::MixnetNodeServiceSettings;
 use rand::{thread_rng, RngCore};
 use tempfile::NamedTempFile;
 
@@ -26,7 +27,10 @@ impl Drop for MixNode {
 impl MixNode {
     pub async fn spawn(config: MixnetNodeConfig) -> Self {
         let config = mixnode::Config {
-            mixnode: config,
+            mixnode: MixnetNodeServiceSettings {
+                node: config,
+                metrics_listen_address: None,
+            },
             log: Default::default(),
         };
 
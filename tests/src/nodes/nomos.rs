@@ -7,7 +7,7 @@ use crate::{get_available_port, ConsensusConfig, MixnetConfig, Node, SpawnConfig
 use consensus_engine::overlay::{RandomBeaconState, RoundRobin, TreeOverlay, TreeOverlaySettings};
 use consensus_engine::{BlockId, NodeId, Overlay};
 use full_replication::Certificate;
-use mixnet_client::{MixnetClientConfig, MixnetClientMode};
+use mixnet_client::{CoverTrafficConfig, HopDelays, MixnetClientConfig, MixnetClientMode};
 use mixnet_node::MixnetNodeConfig;
 use mixnet_topology::MixnetTopology;
 use nomos_consensus::{CarnotInfo, CarnotSettings};
@@ -15,11 +15,14 @@ use nomos_core::block::Block;
 use nomos_http::backends::axum::AxumBackendSettings;
 use nomos_libp2p::{multiaddr, Multiaddr};
 use nomos_log::{LoggerBackend, LoggerFormat};
-use nomos_mempool::MempoolMetrics;
+use nomos_mempool::{
+    backend::priority::PriorityPoolSettings, validator::TxValidatorSettings, MempoolMetrics,
+};
 use nomos_network::backends::libp2p::Libp2pConfig;
 use nomos_network::NetworkConfig;
 use nomos_node::{Config, Tx};
 // crates
+use bls_signatures::{PrivateKey, Serialize};
 use fraction::Fraction;
 use once_cell::sync::Lazy;
 use rand::{thread_rng, Rng};
@@ -223,17 +226,29 @@ fn create_node_configs(
     consensus: ConsensusConfig,
     mut mixnet: MixnetConfig,
 ) -> (Config, Vec<Config>) {
-    let mut ids = vec![[0; 32]; consensus.n_participants];
-    for id in &mut ids {
-        thread_rng().fill(id);
+    let mut private_keys = vec![[0; 32]; consensus.n_participants];
+    for private_key in &mut private_keys {
+        thread_rng().fill(private_key);
     }
+    let public_keys = private_keys
+        .iter()
+        .map(|private_key| PrivateKey::new(private_key).public_key())
+        .collect::<Vec<_>>();
+    let ids = public_keys
+        .iter()
+        .map(NodeId::from_public_key)
+        .collect::<Vec<_>>();
 
-    let mut configs = ids
+    let mut configs = private_keys
         .iter()
-        .map(|id| {
+        .map(|private_key| {
             create_node_config(
-                ids.iter().copied().map(NodeId::new).collect(),
-                *id,
+                ids.clone(),
+                *private_key,
+                public_keys
+                    .iter()
+                    .map(|public_key| public_key.as_bytes().into_boxed_slice())
+                    .collect(),
                 consensus.threshold,
                 consensus.timeout,
                 mixnet.node_configs.pop(),
@@ -244,10 +259,7 @@ fn create_node_configs(
 
     let overlay = TreeOverlay::new(configs[0].consensus.overlay_settings.clone());
     let next_leader = overlay.next_leader();
-    let next_leader_idx = ids
-        .iter()
-        .position(|&id| NodeId::from(id) == next_leader)
-        .unwrap();
+    let next_leader_idx = ids.iter().position(|&id| id == next_leader).unwrap();
 
     let next_leader_config = configs.swap_remove(next_leader_idx);
 
@@ -257,6 +269,7 @@ fn create_node_configs(
 fn create_node_config(
     nodes: Vec<NodeId>,
     private_key: [u8; 32],
+    public_keys: Vec<Box<[u8]>>,
     threshold: Fraction,
     timeout: Duration,
     mixnet_node_config: Option<MixnetNodeConfig>,
@@ -277,9 +290,14 @@ fn create_node_config(
                 mixnet_client: MixnetClientConfig {
                     mode: mixnet_client_mode,
                     topology: mixnet_topology,
+                    topology_provider: Default::default(),
+                    topology_refresh_interval: Duration::from_secs(60),
                     connection_pool_size: 255,
                     max_retries: 3,
                     retry_delay: Duration::from_secs(5),
+                    hop_delays: HopDelays::default(),
+                    cover_traffic: CoverTrafficConfig::default(),
+                    acknowledgements: None,
                 },
                 mixnet_delay: Duration::ZERO..Duration::from_millis(10),
             },
@@ -298,9 +316,21 @@ fn create_node_config(
                 super_majority_threshold: Some(threshold),
             },
             timeout,
+            adaptive_timeout: None,
             transaction_selector_settings: (),
             blob_selector_settings: (),
+            public_keys,
+            epochs: None,
+            pipelined: false,
+            genesis_balances: vec![],
+        },
+        cl_mempool: PriorityPoolSettings {
+            max_items: 10_000,
+            max_bytes: 16 << 20,
+            max_items_per_sender: 64,
+            ttl: Duration::from_secs(60 * 60),
         },
+        cl_mempool_validator: TxValidatorSettings { max_tx_size: 1024 },
         log: Default::default(),
         http: nomos_http::http::HttpServiceSettings {
             backend: AxumBackendSettings {
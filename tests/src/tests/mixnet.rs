@@ -4,7 +4,10 @@ use std::{
 };
 
 use futures::{Stream, StreamExt};
-use mixnet_client::{MixnetClient, MixnetClientConfig, MixnetClientError, MixnetClientMode};
+use mixnet_client::{
+    CoverTrafficConfig, HopDelays, MixnetClient, MixnetClientConfig, MixnetClientError,
+    MixnetClientMode,
+};
 use mixnet_node::{MixnetNode, MixnetNodeConfig};
 use mixnet_topology::{Layer, MixnetTopology, Node};
 use rand::{rngs::OsRng, RngCore};
@@ -23,9 +26,14 @@ async fn mixnet() {
         MixnetClientConfig {
             mode: MixnetClientMode::Sender,
             topology: topology.clone(),
+            topology_provider: Default::default(),
+            topology_refresh_interval: Duration::from_secs(60),
             connection_pool_size: 255,
             max_retries: 3,
             retry_delay: Duration::from_secs(5),
+            hop_delays: HopDelays::default(),
+            cover_traffic: CoverTrafficConfig::default(),
+            acknowledgements: None,
         },
         OsRng,
     );
@@ -127,9 +135,14 @@ async fn run_nodes_and_destination_client() -> (
         MixnetClientConfig {
             mode: MixnetClientMode::SenderReceiver(config3.client_listen_address.to_string()),
             topology: topology.clone(),
+            topology_provider: Default::default(),
+            topology_refresh_interval: Duration::from_secs(60),
             connection_pool_size: 255,
             max_retries: 3,
             retry_delay: Duration::from_secs(5),
+            hop_delays: HopDelays::default(),
+            cover_traffic: CoverTrafficConfig::default(),
+            acknowledgements: None,
         },
         OsRng,
     );